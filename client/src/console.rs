@@ -0,0 +1,164 @@
+//! Console management, the `/api/console` routes.
+
+use std::sync::Arc;
+
+use gotcha_types::console::{
+    ApiKeyResponse, ChallengePreferences, ConsoleResponse, CreateConsoleRequest,
+    CreateCustomizationProfile, CustomizationProfile, UpdateApiKeyRequest,
+    UpdateChallengePreferences, UpdateConsoleRequest, UpdateCustomizationProfile,
+};
+use reqwest::{Client, RequestBuilder};
+use secrecy::{ExposeSecret, Secret};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::{Result, build_http_client};
+
+/// Client for the console API, authenticated with the access token of a user.
+#[derive(Debug, Clone)]
+pub struct ConsoleClient {
+    http: Client,
+    base_url: Arc<str>,
+    access_token: Arc<Secret<String>>,
+}
+
+impl ConsoleClient {
+    /// Creates a client for the gotcha server at `base_url`, e.g. `https://gotcha.land`.
+    pub fn new(base_url: impl Into<String>, access_token: impl Into<String>) -> Self {
+        Self {
+            http: build_http_client(),
+            base_url: base_url.into().trim_end_matches('/').into(),
+            access_token: Arc::new(Secret::new(access_token.into())),
+        }
+    }
+
+    /// Uses an existing HTTP client, e.g. to share its connection pool. The server rejects requests
+    /// without a user agent, so the client must set one.
+    pub fn with_http_client(mut self, http: Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub async fn consoles(&self) -> Result<Vec<ConsoleResponse>> {
+        self.fetch(self.http.get(self.url(""))).await
+    }
+
+    pub async fn create_console(&self, label: impl Into<String>) -> Result<ConsoleResponse> {
+        let request = CreateConsoleRequest { label: label.into() };
+        self.fetch(self.http.post(self.url("")).json(&request))
+            .await
+    }
+
+    pub async fn update_console(
+        &self,
+        console_id: &Uuid,
+        update: &UpdateConsoleRequest,
+    ) -> Result<()> {
+        self.execute(
+            self.http
+                .patch(self.url(&format!("/{console_id}")))
+                .json(update),
+        )
+        .await
+    }
+
+    pub async fn delete_console(&self, console_id: &Uuid) -> Result<()> {
+        self.execute(self.http.delete(self.url(&format!("/{console_id}"))))
+            .await
+    }
+
+    pub async fn api_keys(&self, console_id: &Uuid) -> Result<Vec<ApiKeyResponse>> {
+        self.fetch(self.http.get(self.url(&format!("/{console_id}/api-key"))))
+            .await
+    }
+
+    pub async fn gen_api_key(&self, console_id: &Uuid) -> Result<ApiKeyResponse> {
+        self.fetch(self.http.post(self.url(&format!("/{console_id}/api-key"))))
+            .await
+    }
+
+    pub async fn update_api_key(
+        &self,
+        console_id: &Uuid,
+        site_key: &str,
+        update: &UpdateApiKeyRequest,
+    ) -> Result<()> {
+        let url = self.url(&format!("/{console_id}/api-key/{site_key}"));
+        self.execute(self.http.patch(url).json(update)).await
+    }
+
+    pub async fn revoke_api_key(&self, console_id: &Uuid, site_key: &str) -> Result<()> {
+        let url = self.url(&format!("/{console_id}/api-key/{site_key}"));
+        self.execute(self.http.delete(url)).await
+    }
+
+    pub async fn challenge_preferences(&self, console_id: &Uuid) -> Result<ChallengePreferences> {
+        let url = self.url(&format!("/{console_id}/challenge-preferences"));
+        self.fetch(self.http.get(url)).await
+    }
+
+    pub async fn update_challenge_preferences(
+        &self,
+        console_id: &Uuid,
+        update: &UpdateChallengePreferences,
+    ) -> Result<()> {
+        let url = self.url(&format!("/{console_id}/challenge-preferences"));
+        self.execute(self.http.patch(url).json(update)).await
+    }
+
+    pub async fn customization_profiles(
+        &self,
+        console_id: &Uuid,
+    ) -> Result<Vec<CustomizationProfile>> {
+        let url = self.url(&format!("/{console_id}/customization-profile"));
+        self.fetch(self.http.get(url)).await
+    }
+
+    pub async fn create_customization_profile(
+        &self,
+        console_id: &Uuid,
+        profile: &CreateCustomizationProfile,
+    ) -> Result<CustomizationProfile> {
+        let url = self.url(&format!("/{console_id}/customization-profile"));
+        self.fetch(self.http.post(url).json(profile)).await
+    }
+
+    pub async fn update_customization_profile(
+        &self,
+        console_id: &Uuid,
+        profile_id: &Uuid,
+        update: &UpdateCustomizationProfile,
+    ) -> Result<()> {
+        let url = self.url(&format!("/{console_id}/customization-profile/{profile_id}"));
+        self.execute(self.http.patch(url).json(update)).await
+    }
+
+    pub async fn delete_customization_profile(
+        &self,
+        console_id: &Uuid,
+        profile_id: &Uuid,
+    ) -> Result<()> {
+        let url = self.url(&format!("/{console_id}/customization-profile/{profile_id}"));
+        self.execute(self.http.delete(url)).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/console{path}", self.base_url)
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        Ok(self.send(request).await?.json().await?)
+    }
+
+    async fn execute(&self, request: RequestBuilder) -> Result<()> {
+        self.send(request).await.map(drop)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        Ok(request
+            .bearer_auth(self.access_token.expose_secret())
+            .send()
+            .await?
+            .error_for_status()?)
+    }
+}
@@ -0,0 +1,133 @@
+//! Axum extractor and middleware guarding routes behind a valid gotcha response.
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .route("/submit", post(|Verified(verification): Verified| async { "welcome human" }))
+//!     .layer(middleware::from_fn_with_state(client.clone(), require_gotcha))
+//!     .with_state(client);
+//! ```
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{ConnectInfo, FromRef, FromRequestParts, Request, State},
+    http::{Extensions, HeaderMap, StatusCode, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use thiserror::Error;
+use url::form_urlencoded;
+
+use crate::{GotchaClient, VerificationResponse};
+
+/// Header carrying the gotcha response, for requests that don't submit a form.
+pub const RESPONSE_HEADER: &str = "gotcha-response";
+/// Form field the widget fills with the gotcha response.
+pub const RESPONSE_FIELD: &str = "gotcha-response";
+/// Maximum body size buffered by [`require_gotcha`] looking for the response field.
+pub const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Successful verification of the gotcha response of a request.
+///
+/// Reuses the verification of [`require_gotcha`] when the route is behind it, otherwise verifies
+/// the response in the [`RESPONSE_HEADER`] header.
+#[derive(Debug, Clone)]
+pub struct Verified(pub VerificationResponse);
+
+#[derive(Debug, Error)]
+pub enum Rejection {
+    #[error("Missing gotcha response")]
+    MissingResponse,
+    #[error("Gotcha verification failed")]
+    Failed(VerificationResponse),
+    #[error("Could not read request body")]
+    InvalidBody,
+    #[error(transparent)]
+    Unavailable(#[from] crate::Error),
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        match self {
+            Rejection::MissingResponse | Rejection::Failed(_) => StatusCode::FORBIDDEN,
+            Rejection::InvalidBody => StatusCode::BAD_REQUEST,
+            Rejection::Unavailable(err) => {
+                tracing::error!(error = ?err, "gotcha server unavailable");
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }
+        .into_response()
+    }
+}
+
+impl<S> FromRequestParts<S> for Verified
+where
+    GotchaClient: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(verification) = parts.extensions.get::<VerificationResponse>() {
+            return Ok(Self(verification.clone()));
+        }
+        let response = header_response(&parts.headers).ok_or(Rejection::MissingResponse)?;
+        let client = GotchaClient::from_ref(state);
+        verify(&client, &response, &parts.extensions)
+            .await
+            .map(Self)
+    }
+}
+
+/// Middleware rejecting requests without a valid gotcha response, read from the
+/// [`RESPONSE_HEADER`] header or the [`RESPONSE_FIELD`] of an url encoded form.
+///
+/// On success the [`VerificationResponse`] is available to handlers through [`Verified`].
+pub async fn require_gotcha(
+    State(client): State<GotchaClient>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Rejection> {
+    let (mut parts, body) = request.into_parts();
+    let (response, body) = match header_response(&parts.headers) {
+        Some(response) => (response, body),
+        None => {
+            let bytes = to_bytes(body, MAX_BODY_SIZE)
+                .await
+                .map_err(|_| Rejection::InvalidBody)?;
+            let response = form_urlencoded::parse(&bytes)
+                .find(|(key, _)| key == RESPONSE_FIELD)
+                .map(|(_, value)| value.into_owned())
+                .ok_or(Rejection::MissingResponse)?;
+            (response, Body::from(bytes))
+        }
+    };
+
+    let verification = verify(&client, &response, &parts.extensions).await?;
+    parts.extensions.insert(verification);
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+fn header_response(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(RESPONSE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+async fn verify(
+    client: &GotchaClient,
+    response: &str,
+    extensions: &Extensions,
+) -> Result<VerificationResponse, Rejection> {
+    // only available when the app is served with `into_make_service_with_connect_info`
+    let remoteip: Option<IpAddr> = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let verification = client.verify(response, remoteip).await?;
+    match verification.success {
+        true => Ok(verification),
+        false => Err(Rejection::Failed(verification)),
+    }
+}
@@ -0,0 +1,33 @@
+//! Rust client for the gotcha server.
+//!
+//! [`GotchaClient`] verifies gotcha responses from a site backend and [`ConsoleClient`] manages
+//! consoles and api keys. With the `axum` feature, [`guard`] provides an extractor and a middleware
+//! that reject requests lacking a valid gotcha response.
+
+use thiserror::Error;
+
+pub mod console;
+#[cfg(feature = "axum")]
+pub mod guard;
+pub mod verification;
+
+pub use console::ConsoleClient;
+pub use gotcha_types::verification::{ChallengeType, ErrorCodes, VerificationResponse};
+pub use verification::GotchaClient;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("request to the gotcha server failed")]
+    Request(#[from] reqwest::Error),
+}
+
+/// The server requires a user agent on every request.
+fn build_http_client() -> reqwest::Client {
+    const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .expect("error building http client")
+}
@@ -0,0 +1,65 @@
+//! Site verification of gotcha responses.
+
+use std::{net::IpAddr, sync::Arc};
+
+use reqwest::Client;
+use secrecy::{ExposeSecret, Secret};
+use tracing::{Level, instrument};
+
+use crate::{Result, VerificationResponse, build_http_client};
+
+/// Client for `/api/siteverify`, holding the secret of an api key.
+#[derive(Debug, Clone)]
+pub struct GotchaClient {
+    http: Client,
+    base_url: Arc<str>,
+    secret: Arc<Secret<String>>,
+}
+
+impl GotchaClient {
+    /// Creates a client for the gotcha server at `base_url`, e.g. `https://gotcha.land`.
+    pub fn new(base_url: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            http: build_http_client(),
+            base_url: base_url.into().trim_end_matches('/').into(),
+            secret: Arc::new(Secret::new(secret.into())),
+        }
+    }
+
+    /// Uses an existing HTTP client, e.g. to share its connection pool. The server rejects requests
+    /// without a user agent, so the client must set one.
+    pub fn with_http_client(mut self, http: Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Verifies a gotcha response token, optionally checking it was solved from `remoteip`.
+    ///
+    /// A token that fails verification is not an error, check [`VerificationResponse::success`]
+    /// and [`VerificationResponse::error_codes`].
+    #[instrument(skip(self, response), err(Debug, level = Level::ERROR))]
+    pub async fn verify(
+        &self,
+        response: &str,
+        remoteip: Option<IpAddr>,
+    ) -> Result<VerificationResponse> {
+        let mut form = vec![
+            ("secret", self.secret.expose_secret().clone()),
+            ("response", response.to_string()),
+        ];
+        if let Some(remoteip) = remoteip {
+            form.push(("remoteip", remoteip.to_string()));
+        }
+
+        let verification = self
+            .http
+            .post(format!("{}/api/siteverify", self.base_url))
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(verification)
+    }
+}
@@ -0,0 +1,176 @@
+use std::net::SocketAddr;
+
+use axum::{Router, middleware, routing::post};
+use gotcha_client::{
+    ErrorCodes, GotchaClient,
+    guard::{RESPONSE_FIELD, RESPONSE_HEADER, Verified, require_gotcha},
+};
+use gotcha_server::{
+    HTTP_CLIENT,
+    tokens::response::{self, ChallengeType, ResponseClaims},
+};
+use gotcha_server_macros::integration_test;
+use reqwest::StatusCode;
+use url::Host;
+
+fn claims(score: f32) -> anyhow::Result<ResponseClaims> {
+    Ok(ResponseClaims {
+        score,
+        addr: [127, 0, 0, 1].into(),
+        host: Host::parse("gotcha-integration.test.com")?,
+        challenge_type: ChallengeType::Interactive,
+        action: None,
+    })
+}
+
+/// Serves a site guarded by gotcha and returns its port.
+async fn serve_guarded_site(client: GotchaClient) -> anyhow::Result<u16> {
+    let protected = Router::new()
+        .route(
+            "/submit",
+            post(|Verified(_): Verified| async { "welcome human" }),
+        )
+        .layer(middleware::from_fn_with_state(
+            client.clone(),
+            require_gotcha,
+        ));
+    let app = Router::new()
+        .merge(protected)
+        .route(
+            "/header",
+            post(|Verified(_): Verified| async { "welcome human" }),
+        )
+        .with_state(client);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+    Ok(port)
+}
+
+#[integration_test]
+async fn verify_successful(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let secret = server.db_api_secret().await;
+    let enc_key = server.db_enconding_key().await;
+    let token = response::encode(claims(0.75)?, &enc_key)?;
+
+    let client = GotchaClient::new(format!("http://localhost:{port}"), secret.as_str());
+    let verification = client.verify(&token, Some([127, 0, 0, 1].into())).await?;
+    assert!(verification.success);
+    assert_eq!(verification.error_codes, None);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn verify_invalid_secret(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let enc_key = server.db_enconding_key().await;
+    let token = response::encode(claims(0.75)?, &enc_key)?;
+
+    let client = GotchaClient::new(format!("http://localhost:{port}/"), "AAABBBCC");
+    let verification = client.verify(&token, None).await?;
+    assert!(!verification.success);
+    assert_eq!(
+        verification.error_codes,
+        Some(vec![ErrorCodes::InvalidInputSecret])
+    );
+
+    Ok(())
+}
+
+#[integration_test]
+async fn guard_accepts_form_response(server: TestContext) -> anyhow::Result<()> {
+    let secret = server.db_api_secret().await;
+    let enc_key = server.db_enconding_key().await;
+    let client = GotchaClient::new(
+        format!("http://localhost:{}", server.port()),
+        secret.as_str(),
+    );
+    let site_port = serve_guarded_site(client).await?;
+    let token = response::encode(claims(0.75)?, &enc_key)?;
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{site_port}/submit"))
+        .form(&[("name", "human"), (RESPONSE_FIELD, &token)])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn guard_rejects_failed_response(server: TestContext) -> anyhow::Result<()> {
+    let secret = server.db_api_secret().await;
+    let enc_key = server.db_enconding_key().await;
+    let client = GotchaClient::new(
+        format!("http://localhost:{}", server.port()),
+        secret.as_str(),
+    );
+    let site_port = serve_guarded_site(client).await?;
+    let token = response::encode(claims(0.3)?, &enc_key)?;
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{site_port}/submit"))
+        .form(&[(RESPONSE_FIELD, &token)])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn guard_rejects_missing_response(server: TestContext) -> anyhow::Result<()> {
+    let secret = server.db_api_secret().await;
+    let client = GotchaClient::new(
+        format!("http://localhost:{}", server.port()),
+        secret.as_str(),
+    );
+    let site_port = serve_guarded_site(client).await?;
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{site_port}/submit"))
+        .form(&[("name", "bot")])
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn extractor_verifies_header(server: TestContext) -> anyhow::Result<()> {
+    let secret = server.db_api_secret().await;
+    let enc_key = server.db_enconding_key().await;
+    let client = GotchaClient::new(
+        format!("http://localhost:{}", server.port()),
+        secret.as_str(),
+    );
+    let site_port = serve_guarded_site(client).await?;
+    let token = response::encode(claims(0.75)?, &enc_key)?;
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{site_port}/header"))
+        .header(RESPONSE_HEADER, &token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{site_port}/header"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
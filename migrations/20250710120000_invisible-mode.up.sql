@@ -0,0 +1,3 @@
+-- Invisible (score only) mode per api key
+alter table public.api_key
+add column invisible boolean not null default false;
@@ -0,0 +1,3 @@
+alter table public.api_key
+drop constraint api_key_score_threshold_range,
+drop column score_threshold;
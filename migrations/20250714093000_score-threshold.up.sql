@@ -0,0 +1,7 @@
+-- Score threshold per api key used by site verification
+alter table public.api_key
+add column score_threshold real not null default 0.5,
+add constraint api_key_score_threshold_range check (
+    score_threshold >= 0
+    and score_threshold <= 1
+);
@@ -0,0 +1,12 @@
+drop table public.console_challenge;
+
+alter table public.challenge_customization
+drop column exclude_webgl_on_low_end,
+drop column escalate_difficulty;
+
+alter table public.challenge
+drop constraint challenge_weight_range,
+drop constraint challenge_difficulty_range,
+drop column weight,
+drop column difficulty,
+drop column requires_webgl;
@@ -0,0 +1,27 @@
+-- Attributes driving which challenge is served
+alter table public.challenge
+add column weight smallint not null default 1,
+add column difficulty smallint not null default 1,
+add column requires_webgl boolean not null default false,
+add constraint challenge_weight_range check (weight >= 0),
+add constraint challenge_difficulty_range check (
+    difficulty >= 1
+    and difficulty <= 5
+);
+
+-- Selection policy per console
+alter table public.challenge_customization
+add column exclude_webgl_on_low_end boolean not null default true,
+add column escalate_difficulty boolean not null default true;
+
+-- Per console overrides of challenges, enabled with their default weight when absent
+create table public.console_challenge (
+    console_id uuid not null,
+    challenge_url character varying not null,
+    enabled boolean not null default true,
+    weight smallint null,
+    constraint console_challenge_pkey primary key (console_id, challenge_url),
+    constraint console_challenge_console_id_fkey foreign key (console_id) references console (id) on delete cascade,
+    constraint console_challenge_challenge_url_fkey foreign key (challenge_url) references challenge (url) on delete cascade,
+    constraint console_challenge_weight_range check (weight >= 0)
+);
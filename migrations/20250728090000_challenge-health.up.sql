@@ -0,0 +1,12 @@
+-- Last health check of each challenge, challenges never checked are considered healthy
+create table public.challenge_health (
+    challenge_url character varying not null,
+    healthy boolean not null default true,
+    status smallint null,
+    latency_ms integer null,
+    error character varying null,
+    consecutive_failures smallint not null default 0,
+    checked_at timestamp with time zone not null default now(),
+    constraint challenge_health_pkey primary key (challenge_url),
+    constraint challenge_health_challenge_url_fkey foreign key (challenge_url) references challenge (url) on delete cascade
+);
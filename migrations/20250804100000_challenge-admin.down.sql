@@ -0,0 +1,6 @@
+alter table public.challenge
+drop constraint challenge_default_small_width_range,
+drop constraint challenge_default_small_height_range,
+drop column default_small_width,
+drop column default_small_height,
+drop column enabled;
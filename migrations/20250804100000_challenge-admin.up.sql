@@ -0,0 +1,16 @@
+-- Small dimensions and a global switch per challenge, managed through the admin api
+alter table public.challenge
+add column default_small_width smallint null,
+add column default_small_height smallint null,
+add column enabled boolean not null default true;
+
+update public.challenge
+set
+    default_small_width = default_width,
+    default_small_height = default_height;
+
+alter table public.challenge
+alter column default_small_width set not null,
+alter column default_small_height set not null,
+add constraint challenge_default_small_width_range check (default_small_width > 0),
+add constraint challenge_default_small_height_range check (default_small_height > 0);
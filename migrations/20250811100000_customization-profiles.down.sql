@@ -0,0 +1,5 @@
+alter table public.api_key
+drop constraint api_key_customization_profile_id_fkey,
+drop column customization_profile_id;
+
+drop table public.customization_profile;
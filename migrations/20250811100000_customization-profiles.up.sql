@@ -0,0 +1,26 @@
+-- Named customization profiles of a console, attached to site keys to override the console defaults
+create table public.customization_profile (
+    id uuid not null default gen_random_uuid (),
+    console_id uuid not null,
+    label character varying not null,
+    width smallint null,
+    height smallint null,
+    small_width smallint null,
+    small_height smallint null,
+    logo_url character varying null,
+    created_at timestamp with time zone not null default now(),
+    constraint customization_profile_pkey primary key (id),
+    constraint customization_profile_console_id_fkey foreign key (console_id) references console (id) on delete cascade,
+    constraint customization_profile_console_unique unique (id, console_id),
+    constraint customization_profile_label_unique unique (console_id, label),
+    constraint customization_profile_width_range check (width > 0),
+    constraint customization_profile_height_range check (height > 0),
+    constraint customization_profile_small_width_range check (small_width > 0),
+    constraint customization_profile_small_height_range check (small_height > 0)
+);
+
+-- A site key may only use a profile of its own console
+alter table public.api_key
+add column customization_profile_id uuid null,
+add constraint api_key_customization_profile_id_fkey foreign key (customization_profile_id, console_id)
+    references customization_profile (id, console_id) on delete set null (customization_profile_id);
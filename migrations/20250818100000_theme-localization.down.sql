@@ -0,0 +1,11 @@
+alter table public.challenge_customization
+drop constraint challenge_customization_theme_mode_range,
+drop constraint challenge_customization_corner_radius_range,
+drop column theme_mode,
+drop column primary_color,
+drop column background_color,
+drop column text_color,
+drop column corner_radius,
+drop column font_family,
+drop column locale,
+drop column strings;
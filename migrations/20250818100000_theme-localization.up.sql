@@ -0,0 +1,15 @@
+-- Theme and localization applied by widgets, per console
+alter table public.challenge_customization
+add column theme_mode character varying not null default 'auto',
+add column primary_color character varying null,
+add column background_color character varying null,
+add column text_color character varying null,
+add column corner_radius smallint null,
+add column font_family character varying null,
+add column locale character varying null,
+add column strings jsonb not null default '{}',
+add constraint challenge_customization_theme_mode_range check (theme_mode in ('light', 'dark', 'auto')),
+add constraint challenge_customization_corner_radius_range check (
+    corner_radius >= 0
+    and corner_radius <= 64
+);
@@ -0,0 +1,5 @@
+alter table public.challenge
+drop constraint challenge_max_attempts_range,
+drop constraint challenge_moves_per_attempt_range,
+drop column max_attempts,
+drop column moves_per_attempt;
@@ -0,0 +1,6 @@
+-- Attempts a challenge allows before failing and moves per attempt, e.g. balls thrown in cup stack
+alter table public.challenge
+add column max_attempts smallint not null default 3,
+add column moves_per_attempt smallint null,
+add constraint challenge_max_attempts_range check (max_attempts between 1 and 10),
+add constraint challenge_moves_per_attempt_range check (moves_per_attempt between 1 and 100);
@@ -0,0 +1,2 @@
+alter table public.challenge_customization
+drop column accessibility_enabled;
@@ -0,0 +1,3 @@
+-- Accessibility alternative per console
+alter table public.challenge_customization
+add column accessibility_enabled boolean not null default true;
@@ -0,0 +1,6 @@
+alter table public.challenge
+drop constraint challenge_kind_check,
+drop constraint challenge_remote_verify,
+drop column kind,
+drop column verify_url,
+drop column verify_secret;
@@ -0,0 +1,10 @@
+-- How a challenge's answers are verified: widgets report their own results, puzzles are checked by
+-- the server and remote challenges through their verify url, with requests signed by their secret
+alter table public.challenge
+add column kind text not null default 'widget',
+add column verify_url text null,
+add column verify_secret text null,
+add constraint challenge_kind_check check (kind in ('widget', 'puzzle', 'remote')),
+add constraint challenge_remote_verify check (
+    kind <> 'remote' or (verify_url is not null and verify_secret is not null)
+);
@@ -0,0 +1,5 @@
+alter table public.challenge_customization
+drop constraint challenge_customization_pow_memory_check,
+drop constraint challenge_customization_pow_algorithm_check,
+drop column pow_memory_kib,
+drop column pow_algorithm;
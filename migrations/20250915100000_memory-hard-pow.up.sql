@@ -0,0 +1,10 @@
+-- Proof of work algorithm per console
+alter table public.challenge_customization
+add column pow_algorithm text not null default 'sha256',
+add column pow_memory_kib integer not null default 4096,
+add constraint challenge_customization_pow_algorithm_check check (
+    pow_algorithm in ('sha256', 'scrypt')
+),
+add constraint challenge_customization_pow_memory_check check (
+    pow_memory_kib between 1024 and 16384
+);
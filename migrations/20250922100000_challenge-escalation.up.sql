@@ -0,0 +1,13 @@
+-- Failed attempts and pre analysis outcome of each client of a site, escalating the difficulty of
+-- the challenges it is served. Rows not updated for an hour are stale and start over.
+create table public.challenge_escalation (
+    site_key character varying not null,
+    addr inet not null,
+    failed_attempts smallint not null default 0,
+    min_difficulty smallint not null default 0,
+    updated_at timestamp with time zone not null default now(),
+    constraint challenge_escalation_pkey primary key (site_key, addr),
+    constraint challenge_escalation_site_key_fkey foreign key (site_key) references api_key (site_key) on delete cascade
+);
+
+create index challenge_escalation_updated_at_idx on public.challenge_escalation (updated_at);
@@ -0,0 +1,2 @@
+alter table public.challenge
+drop column traced;
@@ -0,0 +1,4 @@
+-- Whether a challenge reports how its game was played, results of traced challenges without a
+-- trace can't pass
+alter table public.challenge
+add column traced boolean not null default false;
@@ -0,0 +1,250 @@
+#!/usr/bin/env python3
+"""Synthesizes the spoken digit samples used by the audio challenges.
+
+A small cascade formant synthesizer (Klatt style) with no dependencies beyond the standard
+library, so the samples can be regenerated anywhere without a TTS service. Recordings of a real
+voice can replace the generated files as long as they keep the format: 16 bit mono PCM WAV at
+16 kHz, named `digits/<voice>/<digit>.wav`.
+
+Usage: python3 synthesize.py
+"""
+
+import math
+import random
+import struct
+import wave
+from pathlib import Path
+
+RATE = 16_000
+# Brings the peak of a differentiated glottal pulse to about 1, see `synthesize`.
+PULSE_GAIN = 10_000
+OUT_DIR = Path(__file__).parent / "digits"
+
+# Pitch at the start of a word in Hz, scale of the formants and of the durations. Clips are spoken
+# in one of them and mixed with distractors in the others.
+VOICES = {
+    "a": (130, 1.0, 1.0),
+    "b": (215, 1.16, 0.9),
+    "c": (95, 0.9, 1.12),
+}
+
+# F1, F2, F3 in Hz.
+VOWELS = {
+    "iy": (270, 2290, 3010),
+    "ih": (390, 1990, 2550),
+    "eh": (530, 1840, 2480),
+    "ah": (640, 1190, 2390),
+    "aa": (730, 1090, 2440),
+    "ao": (570, 840, 2410),
+    "uw": (300, 870, 2240),
+    "ax": (500, 1500, 2500),
+    "r": (310, 1060, 1380),
+    "w": (290, 610, 2150),
+    "n": (250, 1700, 2600),
+}
+
+# Frication centre frequency and bandwidth in Hz.
+NOISES = {
+    "s": (5500, 1500),
+    "z": (5000, 1500),
+    "f": (4500, 4000),
+    "th": (4500, 4000),
+    "v": (4000, 4000),
+    "t": (4000, 2000),
+    "k": (2500, 1000),
+}
+
+
+def seg(ms, formants, end=None, voice=1.0, noise=None, noise_gain=0.0, aspiration=0.0):
+    """A segment of `ms` milliseconds gliding from `formants` to `end`."""
+    return {
+        "ms": ms,
+        "start": VOWELS[formants] if isinstance(formants, str) else formants,
+        "end": VOWELS[end] if isinstance(end, str) else end,
+        "voice": voice,
+        "noise": NOISES[noise] if noise else (4000, 4000),
+        "noise_gain": noise_gain,
+        "aspiration": aspiration,
+    }
+
+
+def fricative(ms, name, next_vowel, voice=0.0, gain=1.0):
+    return seg(ms, next_vowel, voice=voice, noise=name, noise_gain=gain)
+
+
+def silence(ms, formants):
+    return seg(ms, formants, voice=0.0)
+
+
+DIGITS = {
+    "0": [
+        fricative(100, "z", "ih", voice=0.3, gain=0.6),
+        seg(110, "ih", "iy"),
+        seg(80, "r"),
+        seg(240, "ao", "uw"),
+    ],
+    "1": [
+        seg(80, "w", voice=0.7),
+        seg(170, "ah"),
+        seg(150, "n", voice=0.45),
+    ],
+    "2": [
+        silence(40, "uw"),
+        fricative(25, "t", "uw", gain=1.2),
+        seg(60, "uw", voice=0.0, aspiration=0.5),
+        seg(280, "uw"),
+    ],
+    "3": [
+        fricative(130, "th", "r", gain=0.35),
+        seg(70, "r", voice=0.8),
+        seg(240, "iy"),
+    ],
+    "4": [
+        fricative(140, "f", "ao", gain=0.4),
+        seg(200, "ao"),
+        seg(110, "ao", "r"),
+    ],
+    "5": [
+        fricative(130, "f", "aa", gain=0.4),
+        seg(280, "aa", "iy"),
+        fricative(90, "v", "iy", voice=0.4, gain=0.3),
+    ],
+    "6": [
+        fricative(150, "s", "ih"),
+        seg(120, "ih"),
+        silence(60, "ih"),
+        fricative(30, "k", "ih", gain=1.2),
+        fricative(150, "s", "ih"),
+    ],
+    "7": [
+        fricative(140, "s", "eh"),
+        seg(130, "eh"),
+        fricative(60, "v", "eh", voice=0.4, gain=0.3),
+        seg(80, "ax"),
+        seg(150, "n", voice=0.45),
+    ],
+    "8": [
+        seg(280, "eh", "iy"),
+        silence(60, "iy"),
+        fricative(50, "t", "iy", gain=1.0),
+    ],
+    "9": [
+        seg(90, "n", voice=0.45),
+        seg(270, "aa", "iy"),
+        seg(150, "n", voice=0.45),
+    ],
+}
+
+
+class Resonator:
+    """Second order digital resonator."""
+
+    def __init__(self):
+        self.y1 = self.y2 = 0.0
+
+    def __call__(self, x, freq, bandwidth):
+        c = -math.exp(-2 * math.pi * bandwidth / RATE)
+        b = 2 * math.exp(-math.pi * bandwidth / RATE) * math.cos(2 * math.pi * freq / RATE)
+        a = 1 - b - c
+        y = a * x + b * self.y1 + c * self.y2
+        self.y2, self.y1 = self.y1, y
+        return y
+
+
+def tracks(segments):
+    """Per sample parameter tracks, linearly interpolated within each segment."""
+    frames = []
+    for s in segments:
+        n = s["ms"] * RATE // 1000
+        end = s["end"] or s["start"]
+        for i in range(n):
+            t = i / n
+            formants = tuple(a + (b - a) * t for a, b in zip(s["start"], end))
+            frames.append((formants, s["voice"], s["noise"], s["noise_gain"], s["aspiration"]))
+    return frames
+
+
+def smooth(values, ms):
+    """Moving average, so formants glide and amplitudes ramp between segments."""
+    width = max(1, ms * RATE // 1000)
+    out, total = [], 0.0
+    for i, v in enumerate(values):
+        total += v
+        if i >= width:
+            total -= values[i - width]
+        out.append(total / min(i + 1, width))
+    return out
+
+
+def synthesize(segments, rng, voice=VOICES["a"]):
+    f0_start, formant_scale, duration_scale = voice
+    segments = [
+        {
+            **s,
+            "ms": round(s["ms"] * duration_scale),
+            "start": tuple(f * formant_scale for f in s["start"]),
+            "end": s["end"] and tuple(f * formant_scale for f in s["end"]),
+        }
+        for s in segments
+    ]
+    frames = tracks(segments)
+    formants = [smooth([f[0][k] for f in frames], 25) for k in range(3)]
+    voice = smooth([f[1] for f in frames], 15)
+    noise_gain = smooth([f[3] for f in frames], 5)
+    aspiration = smooth([f[4] for f in frames], 10)
+
+    cascade = [Resonator() for _ in range(4)]
+    glottal = [Resonator(), Resonator()]
+    frication = Resonator()
+    phase, previous, voiced, hisses = 0.0, 0.0, [], []
+    for i, frame in enumerate(frames):
+        # Pitch falls over the word like a statement.
+        f0 = f0_start * (1 - 0.19 * i / len(frames)) + rng.uniform(-1, 1)
+        phase += f0 / RATE
+        pulse = 0.0
+        if phase >= 1.0:
+            phase -= 1.0
+            pulse = 1.0
+        source = glottal[1](glottal[0](pulse, 0, 100), 0, 200)
+        # Radiation at the lips differentiates the source, which also drops its DC offset.
+        x = (source - previous) * PULSE_GAIN * voice[i] + rng.uniform(-1, 1) * aspiration[i] * 0.3
+        previous = source
+        for k, bandwidth in enumerate((60, 90, 150)):
+            x = cascade[k](x, formants[k][i], bandwidth)
+        x = cascade[3](x, 3500, 250)
+
+        noise_freq, noise_bandwidth = frame[2]
+        hiss = frication(rng.uniform(-1, 1), noise_freq, noise_bandwidth) * noise_gain[i]
+        voiced.append(x)
+        hisses.append(hiss)
+
+    # The paths have very different gains, level them so frication sits below the vowels.
+    voiced_peak = max(abs(v) for v in voiced) or 1.0
+    hiss_peak = max(abs(v) for v in hisses) or 1.0
+    out = [v / voiced_peak + h / hiss_peak * 0.3 for v, h in zip(voiced, hisses)]
+    peak = max(abs(v) for v in out)
+    fade = RATE // 200
+    for i in range(fade):
+        out[i] *= i / fade
+        out[-1 - i] *= i / fade
+    return [v / peak * 0.9 for v in out]
+
+
+def write_wav(path, samples):
+    with wave.open(str(path), "wb") as f:
+        f.setnchannels(1)
+        f.setsampwidth(2)
+        f.setframerate(RATE)
+        f.writeframes(b"".join(struct.pack("<h", round(v * 32767)) for v in samples))
+
+
+def main():
+    for index, (name, voice) in enumerate(VOICES.items()):
+        (OUT_DIR / name).mkdir(parents=True, exist_ok=True)
+        rng = random.Random(index)
+        for digit, segments in DIGITS.items():
+            write_wav(OUT_DIR / name / f"{digit}.wav", synthesize(segments, rng, voice))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,61 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use gotcha_server::analysis::proof_of_work::{PowAlgorithm, PowChallenge};
+
+const SCRYPT: PowAlgorithm = PowAlgorithm::Scrypt { log_n: 12, r: 8 };
+
+fn challenge(algorithm: PowAlgorithm) -> PowChallenge {
+    PowChallenge {
+        nonce: 4077096492,
+        difficulty: algorithm.difficulty(),
+        timestamp: 1739555092,
+        algorithm,
+        puzzles: algorithm.puzzles(),
+    }
+}
+
+fn verify(c: &mut Criterion) {
+    let sha256 = challenge(PowAlgorithm::Sha256);
+    let solutions = sha256.solve();
+    c.bench_function("verify sha256", |b| {
+        b.iter(|| sha256.verify_solution(&solutions))
+    });
+
+    let scrypt = challenge(SCRYPT);
+    let solutions = scrypt.solve();
+    c.bench_function("verify scrypt", |b| {
+        b.iter(|| scrypt.verify_solution(&solutions))
+    });
+}
+
+/// Solving random challenges shows the variance of the total work, a single puzzle of the same
+/// expected work for comparison.
+fn solve(c: &mut Criterion) {
+    c.bench_function("solve sha256", |b| {
+        b.iter_batched(
+            || PowChallenge::for_algorithm(PowAlgorithm::Sha256),
+            |challenge| challenge.solve(),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("solve sha256 single puzzle", |b| {
+        b.iter_batched(
+            || PowChallenge::random(3),
+            |challenge| challenge.solve(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let mut group = c.benchmark_group("scrypt");
+    group.sample_size(10);
+    group.bench_function("solve scrypt", |b| {
+        b.iter_batched(
+            || PowChallenge::for_algorithm(SCRYPT),
+            |challenge| challenge.solve(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, verify, solve);
+criterion_main!(benches);
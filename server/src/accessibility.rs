@@ -0,0 +1,240 @@
+//! Text questions offered as the accessible alternative to the interactive challenges. They can be
+//! answered with a screen reader and a keyboard alone. The answer never leaves the server in the
+//! clear: the question token only carries its hash, keyed by the site's encoding key.
+
+use std::time::Duration;
+
+use rand::{Rng, seq::IndexedRandom};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{encodings::Base64, rate_limit::RateLimiter};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    /// Questions and answers allowed per address in each window.
+    #[serde(with = "crate::serde::as_string")]
+    pub max_requests: u32,
+    #[serde(with = "crate::serde::as_string")]
+    pub window_secs: u64,
+    /// Score of the response tokens, below the passing score of the interactive challenges as
+    /// anyone able to read can answer, and so can a script parsing the questions.
+    #[serde(with = "crate::serde::as_string")]
+    pub score: f32,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self { max_requests: 10, window_secs: 600, score: 0.4 }
+    }
+}
+
+/// Shared state of the accessibility routes.
+#[derive(Debug)]
+pub struct Accessibility {
+    pub score: f32,
+    /// In memory, per server instance, see [`RateLimiter`].
+    pub limiter: RateLimiter,
+}
+
+impl From<AccessibilityConfig> for Accessibility {
+    fn from(config: AccessibilityConfig) -> Self {
+        Self {
+            score: config.score,
+            limiter: RateLimiter::new(config.max_requests, Duration::from_secs(config.window_secs)),
+        }
+    }
+}
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const ORDINALS: [&str; 4] = ["first", "second", "third", "fourth"];
+const WORDS: [&str; 24] = [
+    "apple", "river", "stone", "cloud", "garden", "window", "tiger", "candle", "forest", "bottle",
+    "pencil", "rocket", "orange", "island", "bridge", "guitar", "planet", "mirror", "basket",
+    "dragon", "castle", "jacket", "silver", "lemon",
+];
+
+/// A question and its normalized answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Question {
+    pub prompt: String,
+    pub answer: String,
+}
+
+impl Question {
+    /// Picks a question kind and fills it in, numbers are randomly spelled out or written in
+    /// digits.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        match rng.random_range(0..5) {
+            0 => {
+                let (a, b) = (rng.random_range(1..=20), rng.random_range(1..=20));
+                let prompt = format!("What is {} plus {}?", say(a, rng), say(b, rng));
+                Self { prompt, answer: (a + b).to_string() }
+            }
+            1 => {
+                let a = rng.random_range(10..=30);
+                let b = rng.random_range(1..a);
+                let prompt = format!("What is {} minus {}?", say(a, rng), say(b, rng));
+                Self { prompt, answer: (a - b).to_string() }
+            }
+            2 => {
+                let n = rng.random_range(1..=98);
+                let prompt = format!("Which number comes after {}?", say(n, rng));
+                Self { prompt, answer: (n + 1).to_string() }
+            }
+            3 => {
+                let numbers: Vec<u8> = rand::seq::index::sample(rng, 99, 3)
+                    .into_iter()
+                    .map(|n| n as u8 + 1)
+                    .collect();
+                let prompt = format!(
+                    "Which is the largest number: {}, {} or {}?",
+                    say(numbers[0], rng),
+                    say(numbers[1], rng),
+                    say(numbers[2], rng)
+                );
+                let largest = numbers.iter().max().expect("three numbers were sampled");
+                Self { prompt, answer: largest.to_string() }
+            }
+            _ => {
+                let words: Vec<_> = WORDS.choose_multiple(rng, ORDINALS.len()).collect();
+                let position = rng.random_range(0..words.len());
+                let list = words.iter().map(|w| **w).collect::<Vec<_>>().join(", ");
+                let prompt = format!("What is the {} word in: {list}?", ORDINALS[position]);
+                Self { prompt, answer: words[position].to_string() }
+            }
+        }
+    }
+}
+
+/// Lowercases and trims the answer and writes spelled out numbers in digits, so `Twenty-One.`
+/// matches `21`. Sequences of digits are joined, so `4 8, nine` matches `489` for audio clips.
+pub fn normalize_answer(answer: &str) -> String {
+    let answer = answer
+        .trim()
+        .trim_end_matches(['.', '!'])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    match words_to_number(&answer) {
+        Some(n) => n.to_string(),
+        None => digit_sequence(&answer).unwrap_or(answer),
+    }
+}
+
+/// Hash of the normalized answer carried by the question token, keyed by the site's encoding key so
+/// it can't be brute forced from the token.
+pub fn answer_hash(answer: &str, nonce: u64, enc_key: &Base64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(enc_key.as_str().as_bytes());
+    hasher.update(nonce.to_be_bytes());
+    hasher.update(normalize_answer(answer).as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn say(n: u8, rng: &mut impl Rng) -> String {
+    match rng.random_bool(0.5) {
+        true => number_to_words(n),
+        false => n.to_string(),
+    }
+}
+
+fn number_to_words(n: u8) -> String {
+    match n {
+        0..20 => ONES[n as usize].into(),
+        _ if n.is_multiple_of(10) => TENS[(n / 10) as usize].into(),
+        _ => format!("{}-{}", TENS[(n / 10) as usize], ONES[(n % 10) as usize]),
+    }
+}
+
+fn words_to_number(words: &str) -> Option<u8> {
+    let words = words.replace(' ', "-");
+    (0..100).find(|&n| number_to_words(n) == words)
+}
+
+fn digit_sequence(answer: &str) -> Option<String> {
+    answer
+        .split([' ', ',', '-'])
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.chars().all(|c| c.is_ascii_digit()) {
+            true => Some(part.to_string()),
+            false => ONES[..10]
+                .iter()
+                .position(|word| *word == part)
+                .map(|digit| digit.to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_are_normalized() {
+        assert_eq!(normalize_answer(" Twenty-One. "), "21");
+        assert_eq!(normalize_answer("twenty one"), "21");
+        assert_eq!(normalize_answer("7"), "7");
+        assert_eq!(normalize_answer("  Apple "), "apple");
+        assert_eq!(normalize_answer("4 8, nine-0"), "4890");
+    }
+
+    #[test]
+    fn numbers_round_trip_through_words() {
+        for n in 0..100 {
+            assert_eq!(words_to_number(&number_to_words(n)), Some(n));
+        }
+    }
+
+    #[test]
+    fn questions_have_normalized_answers() {
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let question = Question::random(&mut rng);
+            assert!(question.prompt.ends_with('?'));
+            assert_eq!(normalize_answer(&question.answer), question.answer);
+        }
+    }
+
+    #[test]
+    fn answer_hash_depends_on_key_and_nonce() {
+        let key: Base64 =
+            String::from("dHsFxb7mDHNv+cuI1L9GDW8AhXdWzuq/pwKWceDGq1SG4y2WD7zBwtiY2LHWNg3m")
+                .try_into()
+                .unwrap();
+        let other_key: Base64 =
+            String::from("cutadiY3N7fhf+JsB/cx4V8G4/eb9kJ0smVyNdjp5yKrpWUWV0ff5GzioM3y6p9Y")
+                .try_into()
+                .unwrap();
+
+        assert_eq!(answer_hash("Seven", 1, &key), answer_hash("7", 1, &key));
+        assert_ne!(answer_hash("7", 1, &key), answer_hash("7", 2, &key));
+        assert_ne!(answer_hash("7", 1, &key), answer_hash("7", 1, &other_key));
+    }
+}
@@ -1,2 +1,4 @@
+pub mod attempts;
+pub mod game;
 pub mod interaction;
 pub mod proof_of_work;
@@ -0,0 +1,39 @@
+use super::interaction::Score;
+
+/// Attempts a challenge allows unless configured otherwise.
+pub const DEFAULT_MAX_ATTEMPTS: u8 = 3;
+/// Most attempts a challenge can allow.
+pub const MAX_ATTEMPTS: u8 = 10;
+/// Most moves a challenge can allow per attempt.
+pub const MAX_MOVES_PER_ATTEMPT: u8 = 100;
+
+/// Scores a solved challenge by the attempts it took, from 1 when solved at the first try down
+/// to 0.5 at the last allowed attempt. Reports of no attempts or more than allowed score 0.
+pub fn attempts_analysis(attempts: u8, max_attempts: u8) -> Score {
+    let score = match attempts {
+        0 => 0.,
+        a if a > max_attempts => 0.,
+        _ if max_attempts <= 1 => 1.,
+        a => 1. - 0.5 * (a - 1) as f32 / (max_attempts - 1) as f32,
+    };
+    Score(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_attempt_scores_best() {
+        assert_eq!(attempts_analysis(1, 3).0, 1.);
+        assert_eq!(attempts_analysis(2, 3).0, 0.75);
+        assert_eq!(attempts_analysis(3, 3).0, 0.5);
+        assert_eq!(attempts_analysis(1, 1).0, 1.);
+    }
+
+    #[test]
+    fn impossible_attempts_score_zero() {
+        assert_eq!(attempts_analysis(0, 3).0, 0.);
+        assert_eq!(attempts_analysis(4, 3).0, 0.);
+    }
+}
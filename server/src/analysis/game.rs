@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+
+use super::interaction::Score;
+
+/// Faster than any human can solve a game challenge.
+pub const MIN_SOLVE_TIME_MS: u32 = 500;
+/// Most moves traced across all attempts, more than any widget sends.
+pub const MAX_TRACED_MOVES: usize = 1_000;
+/// Most points traced across all moves.
+pub const MAX_TRACED_POINTS: usize = 20_000;
+/// Ratio between the distance covered by a drag and its path length above which it's a straight
+/// line, which only scripts draw.
+const MAX_STRAIGHTNESS: f32 = 0.999;
+/// Faster than anyone can aim with the keyboard and confirm the move.
+pub const MIN_KEYBOARD_AIM_MS: u32 = 150;
+/// Least spread between the intervals of three or more key presses, only scripts press keys at
+/// a steady pace.
+pub const MIN_KEY_JITTER_MS: u32 = 10;
+/// Score of results of traced challenges without a game, low enough to fail so the next one is
+/// harder.
+pub const UNTRACED_SCORE: f32 = 0.2;
+
+/// How a game challenge was played, reported by widgets built on `gotcha-plugin`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    /// Time from the first attempt until the game was over.
+    pub solve_time_ms: u32,
+    /// Seed the game was generated with, issued by the server along with the challenge.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// One trace per attempt, in order.
+    #[serde(default)]
+    pub traces: Vec<AttemptTrace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttemptTrace {
+    pub success: bool,
+    #[serde(default)]
+    pub moves: Vec<MoveTrace>,
+}
+
+/// A single move, e.g. a drag and release throwing a ball.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveTrace {
+    /// Pointer positions while dragging, in window coordinates.
+    #[serde(default)]
+    pub path: Vec<TracePoint>,
+    /// Impulse applied when released, if the move applies one.
+    #[serde(default)]
+    pub impulse: Option<[f32; 3]>,
+    /// Milliseconds since the attempt started of every key pressed during the move, aiming with
+    /// the keyboard adds a point to the path per key press and one at release.
+    #[serde(default)]
+    pub keys: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TracePoint {
+    pub x: f32,
+    pub y: f32,
+    /// Milliseconds since the attempt started.
+    pub t_ms: u32,
+}
+
+/// Scores a game result by the share of its moves that look human. Games not played on the
+/// `issued_seed`, solved faster than [`MIN_SOLVE_TIME_MS`], without moves or reporting more than
+/// any widget traces score 0.
+pub fn game_analysis(game: &GameResult, issued_seed: Option<u64>) -> Score {
+    let moves: Vec<_> = game.traces.iter().flat_map(|t| &t.moves).collect();
+    let points: usize = moves.iter().map(|m| m.path.len() + m.keys.len()).sum();
+    if issued_seed.is_none()
+        || game.seed != issued_seed
+        || game.solve_time_ms < MIN_SOLVE_TIME_MS
+        || moves.is_empty()
+        || moves.len() > MAX_TRACED_MOVES
+        || points > MAX_TRACED_POINTS
+    {
+        return Score(0.);
+    }
+
+    let human = moves.iter().filter(|m| is_human_move(m)).count();
+    Score(human as f32 / moves.len() as f32)
+}
+
+/// A move is human when it was either dragged or aimed with the keyboard like a person would, see
+/// [`is_human_drag`] and [`is_human_keyboard_aim`].
+fn is_human_move(trace: &MoveTrace) -> bool {
+    let path = &trace.path;
+    if path.windows(2).any(|w| w[1].t_ms < w[0].t_ms) {
+        return false;
+    }
+    if trace
+        .impulse
+        .is_some_and(|impulse| impulse.iter().any(|i| !i.is_finite()))
+    {
+        return false;
+    }
+    is_human_drag(path) || is_human_keyboard_aim(path, &trace.keys)
+}
+
+/// A drag was sampled over time and isn't a perfectly straight line.
+fn is_human_drag(path: &[TracePoint]) -> bool {
+    if path.len() < 3 {
+        return false;
+    }
+    let length: f32 = path
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+        .sum();
+    let (first, last) = (path[0], path[path.len() - 1]);
+    let distance = (last.x - first.x).hypot(last.y - first.y);
+    length > 0. && distance / length < MAX_STRAIGHTNESS
+}
+
+/// Keyboard aims step along straight lines, so they are judged by the key presses instead: at
+/// least an aim and a confirmation, [`MIN_KEYBOARD_AIM_MS`] apart, at an uneven pace when there
+/// are more.
+fn is_human_keyboard_aim(path: &[TracePoint], keys: &[u32]) -> bool {
+    if path.len() < 2 || keys.len() < 2 || keys.windows(2).any(|w| w[1] < w[0]) {
+        return false;
+    }
+    if keys[keys.len() - 1] - keys[0] < MIN_KEYBOARD_AIM_MS {
+        return false;
+    }
+    let intervals: Vec<_> = keys.windows(2).map(|w| w[1] - w[0]).collect();
+    match (intervals.iter().min(), intervals.iter().max()) {
+        (Some(min), Some(max)) if intervals.len() > 1 => max - min >= MIN_KEY_JITTER_MS,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drag(points: &[(f32, f32)]) -> MoveTrace {
+        MoveTrace {
+            path: points
+                .iter()
+                .enumerate()
+                .map(|(i, &(x, y))| TracePoint { x, y, t_ms: i as u32 * 16 })
+                .collect(),
+            impulse: Some([0., 0.01, -0.03]),
+            keys: vec![],
+        }
+    }
+
+    fn keyboard_aim(times_ms: &[u32]) -> MoveTrace {
+        MoveTrace {
+            path: times_ms
+                .iter()
+                .map(|&t_ms| TracePoint { x: 0., y: -100., t_ms })
+                .collect(),
+            impulse: Some([0., 0.01, -0.03]),
+            keys: times_ms.to_vec(),
+        }
+    }
+
+    fn game(solve_time_ms: u32, moves: Vec<MoveTrace>) -> GameResult {
+        GameResult {
+            solve_time_ms,
+            seed: Some(42),
+            traces: vec![AttemptTrace { success: true, moves }],
+        }
+    }
+
+    #[test]
+    fn wobbly_drags_are_human() {
+        let moves = vec![drag(&[
+            (100., 300.),
+            (102., 280.),
+            (99., 250.),
+            (105., 200.),
+        ])];
+        let Score(score) = game_analysis(&game(2_000, moves), Some(42));
+        assert_eq!(score, 1.);
+    }
+
+    #[test]
+    fn straight_or_teleported_drags_are_not() {
+        let moves = vec![
+            drag(&[(100., 300.), (100., 250.), (100., 200.), (100., 150.)]),
+            drag(&[(100., 300.), (100., 150.)]),
+            drag(&[(100., 300.), (102., 280.), (99., 250.), (105., 200.)]),
+        ];
+        let Score(score) = game_analysis(&game(2_000, moves), Some(42));
+        assert!((score - 1. / 3.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn keyboard_moves_are_judged_by_aim_time() {
+        let moves = vec![
+            keyboard_aim(&[400, 700, 900]),
+            keyboard_aim(&[400, 400 + MIN_KEYBOARD_AIM_MS - 1]),
+            keyboard_aim(&[400]),
+        ];
+        let Score(score) = game_analysis(&game(2_000, moves), Some(42));
+        assert!((score - 1. / 3.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn steady_or_missing_key_presses_are_not_human() {
+        let unaimed = MoveTrace { keys: vec![], ..keyboard_aim(&[400, 700]) };
+        let moves = vec![
+            keyboard_aim(&[400, 500, 600, 700]),
+            keyboard_aim(&[400, 503, 600, 700]),
+            unaimed,
+            keyboard_aim(&[400, 520, 600, 700]),
+        ];
+        let Score(score) = game_analysis(&game(2_000, moves), Some(42));
+        assert_eq!(score, 0.25);
+    }
+
+    #[test]
+    fn too_fast_scores_zero() {
+        let moves = vec![drag(&[
+            (100., 300.),
+            (102., 280.),
+            (99., 250.),
+            (105., 200.),
+        ])];
+        let Score(score) = game_analysis(&game(MIN_SOLVE_TIME_MS - 1, moves), Some(42));
+        assert_eq!(score, 0.);
+    }
+
+    #[test]
+    fn empty_traces_score_zero() {
+        let Score(score) = game_analysis(&game(2_000, vec![]), Some(42));
+        assert_eq!(score, 0.);
+    }
+
+    #[test]
+    fn games_not_played_on_the_issued_seed_score_zero() {
+        let moves = vec![drag(&[
+            (100., 300.),
+            (102., 280.),
+            (99., 250.),
+            (105., 200.),
+        ])];
+        let Score(score) = game_analysis(&game(2_000, moves.clone()), Some(7));
+        assert_eq!(score, 0.);
+        let Score(score) = game_analysis(&game(2_000, moves), None);
+        assert_eq!(score, 0.);
+    }
+}
@@ -54,6 +54,7 @@ enum InOut {
 }
 
 /// A score from 0f to 1f of how confident thinks a human interacted with the computer.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Score(pub f32);
 
 /// Takes a group of ordered interactions and computes the score.
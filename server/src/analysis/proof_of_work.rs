@@ -1,62 +1,293 @@
 //! A proof of work is useful for preventing replay attacks. This proof of work is a simple challenge of finding X number of prefix zeros,
 //! that is determined by the difficulty.
+//!
+//! Challenges are split in several sub-puzzles of lower difficulty, each with its own `u64`
+//! solution. The total work is the same as a single harder puzzle but varies much less from one
+//! challenge to the next, and solvers can report their progress puzzle by puzzle.
+//!
+//! SHA-256 is solved orders of magnitude faster by GPUs and ASICs than by the widgets, consoles can
+//! pick memory-hard scrypt instead to narrow the gap. Each scrypt hash costs the solver its memory
+//! cost, so far fewer of them are asked for, and the server verifies a solution with a single hash
+//! per sub-puzzle. That hash costs the server the same memory, so memory-hard verifications are
+//! rate limited by address and site key, and only run once the solutions are shaped right.
+
+use std::{net::IpAddr, time::Duration};
 
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
 
+use crate::{
+    db::DbChallengeCustomization,
+    encodings::{Base64, UrlSafe},
+    rate_limit::RateLimiter,
+};
+
+pub use gotcha_types::console::{
+    MAX_SCRYPT_MEMORY_KIB, MIN_SCRYPT_MEMORY_KIB, PowAlgorithmKind, PowSettings,
+};
+
+/// Difficulty of each sub-puzzle of SHA-256 challenges, in leading zero hex digits.
+pub const SHA256_DIFFICULTY: u16 = 2;
+/// Sub-puzzles of SHA-256 challenges, 4096 hashes on average in total.
+pub const SHA256_PUZZLES: u16 = 16;
+/// Difficulty of each sub-puzzle of scrypt challenges, in leading zero bits.
+pub const SCRYPT_DIFFICULTY: u16 = 5;
+/// Sub-puzzles of scrypt challenges, 64 hashes on average in total to solve against 2 to verify,
+/// so solving costs clients 32 times what verifying costs the server.
+pub const SCRYPT_PUZZLES: u16 = 2;
+// each sub-puzzle takes `2^difficulty` hashes on average to solve and one to verify
+const _: () = assert!(
+    1 << SCRYPT_DIFFICULTY >= 32,
+    "scrypt solving must dominate verifying"
+);
+/// Most sub-puzzles of a challenge, fewer for memory-hard algorithms as each one costs a hash to
+/// verify.
+pub const MAX_PUZZLES: u16 = 64;
+pub const MAX_MEMORY_HARD_PUZZLES: u16 = 8;
+/// Block size of scrypt challenges, making each hash take `memory_kib` KiB for `N = memory_kib`.
+pub const SCRYPT_R: u32 = 8;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PowConfig {
+    /// Memory-hard proofs of work verified per address and site key in each window.
+    #[serde(with = "crate::serde::as_string")]
+    pub max_memory_hard_requests: u32,
+    #[serde(with = "crate::serde::as_string")]
+    pub window_secs: u64,
+}
+
+impl Default for PowConfig {
+    fn default() -> Self {
+        Self { max_memory_hard_requests: 30, window_secs: 600 }
+    }
+}
+
+/// Limits memory-hard verifications by address and site key, in memory like every
+/// [`RateLimiter`].
+pub type PowLimiter = RateLimiter<(IpAddr, Base64<UrlSafe>)>;
+
+impl From<PowConfig> for PowLimiter {
+    fn from(config: PowConfig) -> Self {
+        RateLimiter::new(
+            config.max_memory_hard_requests,
+            Duration::from_secs(config.window_secs),
+        )
+    }
+}
+
+/// Hash a challenge is solved with, encoded in the challenge claims.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "lowercase")]
+pub enum PowAlgorithm {
+    /// `difficulty` is in leading zero hex digits of the hash.
+    #[default]
+    Sha256,
+    /// scrypt with `N = 2^log_n` and `p = 1`, taking `128 * r * N` bytes per hash. `difficulty`
+    /// is in leading zero bits of the hash.
+    Scrypt { log_n: u8, r: u32 },
+}
+
+impl From<&PowAlgorithm> for PowAlgorithmKind {
+    fn from(algorithm: &PowAlgorithm) -> Self {
+        match algorithm {
+            PowAlgorithm::Sha256 => PowAlgorithmKind::Sha256,
+            PowAlgorithm::Scrypt { .. } => PowAlgorithmKind::Scrypt,
+        }
+    }
+}
+
+impl From<&DbChallengeCustomization> for PowSettings {
+    fn from(customization: &DbChallengeCustomization) -> Self {
+        Self {
+            algorithm: customization.pow_algorithm.as_str().into(),
+            memory_kib: customization.pow_memory_kib as u32,
+        }
+    }
+}
+
+impl From<&PowSettings> for PowAlgorithm {
+    fn from(settings: &PowSettings) -> Self {
+        match settings.algorithm {
+            PowAlgorithmKind::Sha256 => PowAlgorithm::Sha256,
+            PowAlgorithmKind::Scrypt => {
+                PowAlgorithm::Scrypt { log_n: settings.memory_kib.ilog2() as u8, r: SCRYPT_R }
+            }
+        }
+    }
+}
+
+impl PowAlgorithm {
+    /// Difficulty of each sub-puzzle served with the algorithm.
+    pub fn difficulty(&self) -> u16 {
+        match self {
+            PowAlgorithm::Sha256 => SHA256_DIFFICULTY,
+            PowAlgorithm::Scrypt { .. } => SCRYPT_DIFFICULTY,
+        }
+    }
+
+    /// Sub-puzzles served with the algorithm.
+    pub fn puzzles(&self) -> u16 {
+        match self {
+            PowAlgorithm::Sha256 => SHA256_PUZZLES,
+            PowAlgorithm::Scrypt { .. } => SCRYPT_PUZZLES,
+        }
+    }
+
+    fn is_valid(&self, difficulty: u16, puzzles: u16) -> bool {
+        match *self {
+            PowAlgorithm::Sha256 => {
+                (1..=32).contains(&difficulty) && (1..=MAX_PUZZLES).contains(&puzzles)
+            }
+            PowAlgorithm::Scrypt { log_n, r } => {
+                let max_bytes = MAX_SCRYPT_MEMORY_KIB as u64 * 1024;
+                (1..=16).contains(&difficulty)
+                    && (1..=MAX_MEMORY_HARD_PUZZLES).contains(&puzzles)
+                    && (1..32).contains(&log_n)
+                    && (1..=64).contains(&r)
+                    && (128 * r as u64) << log_n <= max_bytes
+            }
+        }
+    }
+
+    /// Whether the hash is memory-hard, and too slow to verify on the async runtime.
+    pub fn is_memory_hard(&self) -> bool {
+        !matches!(self, PowAlgorithm::Sha256)
+    }
+}
+
 /// Proof of work challnge that it's sent to the client. The `nonce` and `timestamp` are the sources of randomness and uniqueness,
-/// while difficulty dictates how much prefixing zeros the solver has to find.
+/// while difficulty dictates how much prefixing zeros the solver has to find for each of the `puzzles`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PowChallenge {
     pub nonce: u32,
     pub difficulty: u16,
     pub timestamp: i64,
+    /// SHA-256 when absent, as in the challenges issued before the choice of algorithm.
+    #[serde(default)]
+    pub algorithm: PowAlgorithm,
+    /// Sub-puzzles to solve, each one's index is hashed along with its solution. A single one when
+    /// absent, as in the challenges issued before sub-puzzles.
+    #[serde(default = "PowChallenge::single_puzzle")]
+    pub puzzles: u16,
 }
 
 impl PowChallenge {
+    fn single_puzzle() -> u16 {
+        1
+    }
+
+    /// A single SHA-256 puzzle.
     pub fn random(difficulty: u16) -> Self {
+        Self::random_with(PowAlgorithm::Sha256, difficulty, 1)
+    }
+
+    pub fn random_with(algorithm: PowAlgorithm, difficulty: u16, puzzles: u16) -> Self {
         Self {
             nonce: rand::rng().random::<u32>(),
             difficulty,
             timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+            algorithm,
+            puzzles,
         }
     }
 
-    pub fn verify_solution(&self, solution: u32) -> bool {
-        if self.difficulty == 0 || self.difficulty > 32 {
+    /// The challenge served with `algorithm`.
+    pub fn for_algorithm(algorithm: PowAlgorithm) -> Self {
+        Self::random_with(algorithm, algorithm.difficulty(), algorithm.puzzles())
+    }
+
+    /// Whether the challenge is one the server could have issued and there's a solution for each of
+    /// its sub-puzzles, checked without hashing anything.
+    pub fn is_well_formed(&self, solutions: &[u64]) -> bool {
+        self.algorithm.is_valid(self.difficulty, self.puzzles)
+            && solutions.len() == self.puzzles as usize
+    }
+
+    /// Verifies the solution of every sub-puzzle, with a single hash each whatever the algorithm.
+    pub fn verify_solution(&self, solutions: &[u64]) -> bool {
+        if !self.is_well_formed(solutions) {
             return false;
         }
 
-        let hash = self.hash_solution(solution);
-        Self::is_solution(&hash, self.difficulty)
+        solutions
+            .iter()
+            .enumerate()
+            .all(|(puzzle, &solution)| self.is_solution(&self.hash(puzzle as u16, solution)))
+    }
+
+    pub fn hash_solution(&self, puzzle: u16, solution: u64) -> String {
+        self.hash(puzzle, solution)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
     }
 
-    pub fn hash_solution(&self, solution: u32) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(self.nonce.to_be_bytes());
-        hasher.update(self.difficulty.to_be_bytes());
-        hasher.update(self.timestamp.to_be_bytes());
-        hasher.update(solution.to_be_bytes());
-        format!("{:x}", hasher.finalize())
+    pub fn solve(&self) -> Vec<u64> {
+        self.solve_with_progress(|_, _| {})
     }
 
-    pub fn solve(&self) -> u32 {
-        let mut solution = 0;
-        loop {
-            let hash = self.hash_solution(solution);
-            if Self::is_solution(&hash, self.difficulty) {
-                return solution;
+    /// Solves the sub-puzzles in order, calling `progress` with the number solved and the total
+    /// after each one.
+    pub fn solve_with_progress(&self, mut progress: impl FnMut(u16, u16)) -> Vec<u64> {
+        (0..self.puzzles)
+            .map(|puzzle| {
+                let solution = (0..)
+                    .find(|&solution| self.is_solution(&self.hash(puzzle, solution)))
+                    .expect("a u64 solution space doesn't run out");
+                progress(puzzle + 1, self.puzzles);
+                solution
+            })
+            .collect()
+    }
+
+    fn hash(&self, puzzle: u16, solution: u64) -> [u8; 32] {
+        match self.algorithm {
+            PowAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(self.nonce.to_be_bytes());
+                hasher.update(self.difficulty.to_be_bytes());
+                hasher.update(self.timestamp.to_be_bytes());
+                hasher.update(puzzle.to_be_bytes());
+                hasher.update(solution.to_be_bytes());
+                hasher.finalize().into()
+            }
+            PowAlgorithm::Scrypt { log_n, r } => {
+                let mut salt = [0; 16];
+                salt[..4].copy_from_slice(&self.nonce.to_be_bytes());
+                salt[4..6].copy_from_slice(&self.difficulty.to_be_bytes());
+                salt[6..14].copy_from_slice(&self.timestamp.to_be_bytes());
+                salt[14..].copy_from_slice(&puzzle.to_be_bytes());
+                let params = scrypt::Params::new(log_n, r, 1, 32).expect("valid scrypt params");
+                let mut hash = [0; 32];
+                scrypt::scrypt(&solution.to_be_bytes(), &salt, &params, &mut hash)
+                    .expect("32 bytes is a valid scrypt output length");
+                hash
             }
-            solution += 1;
         }
     }
 
-    fn is_solution(hash: &str, difficulty: u16) -> bool {
-        let prefix = "0".repeat(difficulty as usize);
-        hash.starts_with(&prefix)
+    fn is_solution(&self, hash: &[u8; 32]) -> bool {
+        // hex digits are 4 bits each
+        let bits = match self.algorithm {
+            PowAlgorithm::Sha256 => self.difficulty as u32 * 4,
+            PowAlgorithm::Scrypt { .. } => self.difficulty as u32,
+        };
+        leading_zero_bits(hash) >= bits
+    }
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
     }
+    bits
 }
 
 #[cfg(test)]
@@ -67,9 +298,9 @@ mod tests {
     fn successful_verify_solution() {
         let challenge = PowChallenge::random(4);
 
-        let solution = challenge.solve();
+        let solutions = challenge.solve();
 
-        let result = challenge.verify_solution(solution);
+        let result = challenge.verify_solution(&solutions);
         assert!(result);
     }
 
@@ -77,25 +308,138 @@ mod tests {
     fn failed_verify_solution() {
         let challenge = PowChallenge::random(4);
 
-        let solution = challenge.solve() - 1;
+        let solutions: Vec<_> = challenge
+            .solve()
+            .iter()
+            .map(|s| s.wrapping_sub(1))
+            .collect();
 
-        let result = challenge.verify_solution(solution);
+        let result = challenge.verify_solution(&solutions);
         assert!(!result);
     }
 
+    #[test]
+    fn every_puzzle_is_solved() {
+        let challenge = PowChallenge::for_algorithm(PowAlgorithm::Sha256);
+        let mut progress = vec![];
+
+        let mut solutions =
+            challenge.solve_with_progress(|solved, total| progress.push((solved, total)));
+
+        assert_eq!(solutions.len(), SHA256_PUZZLES as usize);
+        assert_eq!(progress.last(), Some(&(SHA256_PUZZLES, SHA256_PUZZLES)));
+        assert_eq!(progress.len(), SHA256_PUZZLES as usize);
+        assert!(challenge.verify_solution(&solutions));
+        assert!(!challenge.verify_solution(&solutions[1..]));
+
+        solutions.push(0);
+        assert!(!challenge.verify_solution(&solutions));
+    }
+
+    #[test]
+    fn puzzles_depend_on_their_index() {
+        let challenge = PowChallenge::random_with(PowAlgorithm::Sha256, 4, 2);
+
+        assert_ne!(challenge.hash_solution(0, 1), challenge.hash_solution(1, 1));
+        assert_ne!(
+            challenge.hash_solution(0, 1),
+            challenge.hash_solution(0, 1 << 32)
+        );
+    }
+
+    #[test]
+    fn scrypt_solutions_verify() {
+        let settings = PowSettings { algorithm: PowAlgorithmKind::Scrypt, memory_kib: 1024 };
+        let algorithm = PowAlgorithm::from(&settings);
+        assert_eq!(algorithm, PowAlgorithm::Scrypt { log_n: 10, r: 8 });
+        let challenge = PowChallenge::for_algorithm(algorithm);
+
+        let solutions = challenge.solve();
+
+        assert!(challenge.verify_solution(&solutions));
+        assert!(leading_zero_bits(&challenge.hash(0, solutions[0])) >= SCRYPT_DIFFICULTY as u32);
+        let sha256 = PowChallenge { algorithm: PowAlgorithm::Sha256, ..challenge };
+        assert_ne!(
+            sha256.hash_solution(0, solutions[0]),
+            challenge.hash_solution(0, solutions[0])
+        );
+    }
+
+    #[test]
+    fn scrypt_matches_the_widget() {
+        // solved by `widget-api/src/proof-of-work.ts` on the same challenge
+        let challenge = PowChallenge {
+            nonce: 4077096492,
+            difficulty: 4,
+            timestamp: 1739555092,
+            algorithm: PowAlgorithm::Scrypt { log_n: 10, r: 8 },
+            puzzles: 2,
+        };
+
+        assert_eq!(challenge.solve(), [0, 13]);
+        assert_eq!(
+            challenge.hash_solution(1, 13),
+            "0652d184016a0fb7821347b79366dea6e66679a7d70f80d332e402e9ac0aa60d"
+        );
+    }
+
+    #[test]
+    fn rejects_expensive_or_impossible_challenges() {
+        let scrypt = PowAlgorithm::Scrypt { log_n: 10, r: 8 };
+        let too_hard = PowChallenge::random_with(scrypt, 17, 1);
+        let too_big = PowChallenge::random_with(PowAlgorithm::Scrypt { log_n: 20, r: 8 }, 4, 1);
+        let too_many = PowChallenge::random_with(scrypt, 1, MAX_MEMORY_HARD_PUZZLES + 1);
+        let none = PowChallenge::random_with(PowAlgorithm::Sha256, 1, 0);
+
+        assert!(!too_hard.is_well_formed(&[0]));
+        assert!(!too_hard.verify_solution(&[0]));
+        assert!(!too_big.verify_solution(&[0]));
+        assert!(!too_many.verify_solution(&[0; MAX_MEMORY_HARD_PUZZLES as usize + 1]));
+        assert!(!none.verify_solution(&[]));
+        assert!(!PowChallenge::random(33).verify_solution(&[0]));
+    }
+
+    #[test]
+    fn claims_without_algorithm_are_sha256() {
+        let challenge: PowChallenge =
+            serde_json::from_str(r#"{"nonce":1,"difficulty":3,"timestamp":2,"puzzles":1}"#)
+                .unwrap();
+        assert_eq!(challenge.algorithm, PowAlgorithm::Sha256);
+
+        let legacy: PowChallenge =
+            serde_json::from_str(r#"{"nonce":1,"difficulty":3,"timestamp":2}"#).unwrap();
+        assert_eq!(legacy.puzzles, 1);
+
+        let challenge = PowChallenge::random_with(PowAlgorithm::Scrypt { log_n: 12, r: 8 }, 4, 2);
+        let json = serde_json::to_value(&challenge).unwrap();
+        assert_eq!(json["algorithm"]["name"], "scrypt");
+        assert_eq!(json["algorithm"]["log_n"], 12);
+        assert_eq!(json["puzzles"], 2);
+    }
+
+    #[test]
+    fn settings_bound_memory() {
+        let settings = |memory_kib| PowSettings { algorithm: PowAlgorithmKind::Scrypt, memory_kib };
+
+        assert!(settings(4096).is_valid());
+        assert!(!settings(3000).is_valid());
+        assert!(!settings(512).is_valid());
+        assert!(!settings(32768).is_valid());
+    }
+
     #[test]
     #[ignore = "useful for manually test values"]
     fn verify_specific_solution() {
-        let challenge = PowChallenge { nonce: 4077096492, difficulty: 4, timestamp: 1739555092 };
+        let challenge = PowChallenge {
+            nonce: 4077096492,
+            difficulty: 4,
+            timestamp: 1739555092,
+            algorithm: PowAlgorithm::Sha256,
+            puzzles: 1,
+        };
 
         let solution = 13062;
-        let hash = challenge.hash_solution(solution);
-        eprintln!("{hash}");
-
-        let actual_solution = challenge.solve();
-        eprintln!("{actual_solution}");
-
-        let result = challenge.verify_solution(solution);
+        let result = challenge.verify_solution(&[solution]);
         assert!(result);
     }
 }
@@ -0,0 +1,194 @@
+//! Protocol of challenges verified by the server. A [`Challenge`] issues an instance from a seed
+//! and scores answers to it. Seeds are derived from the nonce of a signed instance token and the
+//! site's encoding key, so the server issues the instance again to verify an answer instead of
+//! keeping it, and clients can't work out the seed from the token.
+//!
+//! Challenges are registered by [`ChallengeKind`] in the `challenge` table. New ones implement
+//! [`Challenge`] and get a kind, or are hosted anywhere and verified through a callback URL, see
+//! [`remote`].
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    analysis::interaction::Score, db::DbChallenge, encodings::Base64,
+    tokens::response::ChallengeType,
+};
+
+pub mod audio;
+pub mod puzzle;
+pub mod remote;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A challenge the server can issue instances of and verify answers to.
+pub trait Challenge: Send + Sync {
+    /// Issues the instance of `seed`. Must be deterministic, answers are verified against the
+    /// instance issued again from the same seed.
+    fn issue(&self, seed: Seed) -> BoxFuture<'_, anyhow::Result<Instance>>;
+
+    /// Scores an answer to `instance`, 0 for a wrong one.
+    fn verify<'a>(
+        &'a self,
+        instance: &'a Instance,
+        answer: &'a Value,
+    ) -> BoxFuture<'a, anyhow::Result<Score>>;
+}
+
+/// How the answers of a registered challenge are verified.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeKind {
+    /// Reports its own results through the `GotchaWidgetLib` callbacks.
+    #[default]
+    Widget,
+    /// Slider puzzle rendered and checked by the server, see [`puzzle`].
+    Puzzle,
+    /// Issues and verifies instances through its verify URL, see [`remote`].
+    Remote,
+}
+
+impl ChallengeKind {
+    /// Path recorded in the response token of an answered instance.
+    pub fn challenge_type(&self) -> ChallengeType {
+        match self {
+            ChallengeKind::Widget => ChallengeType::Interactive,
+            ChallengeKind::Puzzle => ChallengeType::Puzzle,
+            ChallengeKind::Remote => ChallengeType::Remote,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeKind::Widget => "widget",
+            ChallengeKind::Puzzle => "puzzle",
+            ChallengeKind::Remote => "remote",
+        }
+    }
+}
+
+impl From<&str> for ChallengeKind {
+    fn from(kind: &str) -> Self {
+        match kind {
+            "puzzle" => ChallengeKind::Puzzle,
+            "remote" => ChallengeKind::Remote,
+            _ => ChallengeKind::Widget,
+        }
+    }
+}
+
+/// Secret seed of an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seed(pub [u8; 32]);
+
+impl Seed {
+    /// Seed of the instance token with `nonce`, keyed by the site's encoding key.
+    pub fn derive(nonce: u64, enc_key: &Base64) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(enc_key.as_str().as_bytes());
+        hasher.update(b"instance");
+        hasher.update(nonce.to_be_bytes());
+        Self(hasher.finalize().into())
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+/// Instance of a challenge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub seed: Seed,
+    /// What the client renders the instance from, it must not give the answer away.
+    pub data: Value,
+}
+
+/// The challenge verifying answers of a registered one, `None` for widgets.
+pub fn registered(
+    challenge: &DbChallenge,
+    puzzles: &puzzle::Puzzles,
+) -> anyhow::Result<Option<Box<dyn Challenge>>> {
+    Ok(match ChallengeKind::from(challenge.kind.as_str()) {
+        ChallengeKind::Widget => None,
+        ChallengeKind::Puzzle => Some(Box::new(puzzles.clone())),
+        ChallengeKind::Remote => Some(Box::new(remote::RemoteChallenge::try_from(challenge)?)),
+    })
+}
+
+/// Nonces of instances already answered, so each gets a single answer. Each is kept until its own
+/// token expires, tokens of every kind share them.
+#[derive(Debug, Default)]
+pub struct AnsweredNonces(Mutex<HashMap<u64, Instant>>);
+
+impl AnsweredNonces {
+    /// Marks the instance answered until `timeout` from now, `false` if it already was.
+    pub fn answer(&self, nonce: u64, timeout: Duration) -> bool {
+        let now = Instant::now();
+        let mut answered = self.0.lock().expect("answered nonces lock poisoned");
+        answered.retain(|_, expiry| *expiry > now);
+        answered.insert(nonce, now + timeout).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_depend_on_key_and_nonce() {
+        let key: Base64 =
+            String::from("dHsFxb7mDHNv+cuI1L9GDW8AhXdWzuq/pwKWceDGq1SG4y2WD7zBwtiY2LHWNg3m")
+                .try_into()
+                .unwrap();
+        let other_key: Base64 =
+            String::from("cutadiY3N7fhf+JsB/cx4V8G4/eb9kJ0smVyNdjp5yKrpWUWV0ff5GzioM3y6p9Y")
+                .try_into()
+                .unwrap();
+
+        assert_eq!(Seed::derive(1, &key), Seed::derive(1, &key));
+        assert_ne!(Seed::derive(1, &key), Seed::derive(2, &key));
+        assert_ne!(Seed::derive(1, &key), Seed::derive(1, &other_key));
+        assert_eq!(Seed::derive(1, &key).to_hex().len(), 64);
+    }
+
+    #[test]
+    fn nonces_are_answered_once() {
+        let answered = AnsweredNonces::default();
+        let timeout = Duration::from_secs(60);
+
+        assert!(answered.answer(1, timeout));
+        assert!(!answered.answer(1, timeout));
+        assert!(answered.answer(2, Duration::ZERO));
+        assert!(answered.answer(2, timeout));
+    }
+
+    #[test]
+    fn nonces_expire_with_their_own_token() {
+        let answered = AnsweredNonces::default();
+
+        assert!(answered.answer(1, Duration::from_secs(300)));
+        // a shorter lived token answered later doesn't evict the first
+        assert!(answered.answer(2, Duration::ZERO));
+        assert!(!answered.answer(1, Duration::ZERO));
+    }
+
+    #[test]
+    fn kinds_round_trip_through_strings() {
+        for kind in [
+            ChallengeKind::Widget,
+            ChallengeKind::Puzzle,
+            ChallengeKind::Remote,
+        ] {
+            assert_eq!(ChallengeKind::from(kind.as_str()), kind);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Audio challenges for users who can't see the interactive ones: a clip of random spoken digits
+//! mixed from the bundled samples (`assets/audio/digits/<voice>`). Each clip is spoken in a random
+//! voice at a random pitch, every digit with its own pitch and tempo jitter, over background noise
+//! of a random colour and with quieter distractor digits in the other voices overlapping the real
+//! ones, so clips don't repeat the samples and get in the way of speech recognition. Everything is
+//! generated here, no speech service is involved.
+
+use std::{io::Cursor, sync::LazyLock};
+
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use rand::Rng;
+use thiserror::Error;
+
+pub const SAMPLE_RATE: u32 = 16_000;
+/// Digits spoken in each clip.
+pub const DIGITS: usize = 6;
+
+const WAV_SPEC: WavSpec = WavSpec {
+    channels: 1,
+    sample_rate: SAMPLE_RATE,
+    bits_per_sample: 16,
+    sample_format: SampleFormat::Int,
+};
+
+/// Voices the samples are bundled in, see `assets/audio/synthesize.py`.
+const VOICES: usize = 3;
+/// Pitch of the whole clip, and how far each digit strays from it.
+const PITCH: (f32, f32) = (0.88, 1.15);
+const PITCH_JITTER: (f32, f32) = (0.96, 1.04);
+/// Speed of each digit, independent of its pitch.
+const TEMPO: (f32, f32) = (0.85, 1.2);
+const GAIN: (f32, f32) = (0.75, 1.0);
+/// Distractors are sped up, so they're higher pitched as well as quieter than the digits to type.
+const DISTRACTOR_SPEED: (f32, f32) = (1.35, 1.7);
+const DISTRACTOR_GAIN: (f32, f32) = (0.18, 0.3);
+const DISTRACTOR_CHANCE: f64 = 0.6;
+const NOISE_GAIN: (f32, f32) = (0.04, 0.08);
+/// Low pass coefficient of the noise, from a low hum to a hiss.
+const NOISE_COLOUR: (f32, f32) = (0.05, 0.5);
+/// Grains [`stretch`] overlaps, 20 ms long.
+const GRAIN: usize = SAMPLE_RATE as usize / 50;
+const LEAD_SECS: (f32, f32) = (0.4, 0.7);
+const GAP_SECS: (f32, f32) = (0.25, 0.6);
+
+macro_rules! digit_samples {
+    ($voice:literal) => {
+        digit_samples!($voice: "0", "1", "2", "3", "4", "5", "6", "7", "8", "9")
+    };
+    ($voice:literal: $($digit:literal),*) => {
+        [$(include_bytes!(concat!("../../assets/audio/digits/", $voice, "/", $digit, ".wav"))
+            .as_slice()),*]
+    };
+}
+
+static SAMPLES: LazyLock<[[Vec<f32>; 10]; VOICES]> = LazyLock::new(|| {
+    [
+        digit_samples!("a"),
+        digit_samples!("b"),
+        digit_samples!("c"),
+    ]
+    .map(|voice| {
+        voice.map(|wav| decode_wav(wav).expect("bundled digit samples must be 16 bit mono PCM WAV"))
+    })
+});
+
+#[derive(Error, Debug)]
+pub enum WavError {
+    #[error(transparent)]
+    Wav(#[from] hound::Error),
+    #[error("Unsupported WAV format: {channels} channels, {bits} bits, {rate} Hz")]
+    UnsupportedFormat { channels: u16, bits: u16, rate: u32 },
+}
+
+/// A clip and the digits spoken in it.
+#[derive(Debug, Clone)]
+pub struct AudioClip {
+    pub digits: String,
+    /// 16 bit mono PCM WAV.
+    pub wav: Vec<u8>,
+}
+
+impl AudioClip {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let digits: Vec<usize> = (0..DIGITS).map(|_| rng.random_range(0..10)).collect();
+        let voice = rng.random_range(0..VOICES);
+        let pitch = rng.random_range(PITCH.0..PITCH.1);
+
+        let mut track = Vec::new();
+        let mut cursor = secs(rng.random_range(LEAD_SECS.0..LEAD_SECS.1));
+        for &digit in &digits {
+            let sample = &SAMPLES[voice][digit];
+            let spoken = stretch(
+                &resample(
+                    sample,
+                    pitch * rng.random_range(PITCH_JITTER.0..PITCH_JITTER.1),
+                ),
+                (sample.len() as f32 / rng.random_range(TEMPO.0..TEMPO.1)) as usize,
+            );
+            mix(
+                &mut track,
+                &spoken,
+                cursor,
+                rng.random_range(GAIN.0..GAIN.1),
+            );
+
+            if rng.random_bool(DISTRACTOR_CHANCE) {
+                let other = (voice + rng.random_range(1..VOICES)) % VOICES;
+                let distractor = resample(
+                    &SAMPLES[other][rng.random_range(0..10)],
+                    rng.random_range(DISTRACTOR_SPEED.0..DISTRACTOR_SPEED.1),
+                );
+                let start =
+                    (cursor + rng.random_range(0..spoken.len())).saturating_sub(spoken.len() / 2);
+                let gain = rng.random_range(DISTRACTOR_GAIN.0..DISTRACTOR_GAIN.1);
+                mix(&mut track, &distractor, start, gain);
+            }
+            cursor += spoken.len() + secs(rng.random_range(GAP_SECS.0..GAP_SECS.1));
+        }
+        track.resize(track.len().max(cursor), 0.0);
+
+        let gain = rng.random_range(NOISE_GAIN.0..NOISE_GAIN.1);
+        let colour = rng.random_range(NOISE_COLOUR.0..NOISE_COLOUR.1);
+        add_noise(&mut track, gain, colour, rng);
+        normalize(&mut track);
+
+        Self {
+            digits: digits.iter().map(|d| d.to_string()).collect(),
+            wav: encode_wav(&track),
+        }
+    }
+}
+
+fn secs(secs: f32) -> usize {
+    (secs * SAMPLE_RATE as f32) as usize
+}
+
+/// Plays `samples` `speed` times faster, which also shifts their pitch.
+fn resample(samples: &[f32], speed: f32) -> Vec<f32> {
+    let len = (samples.len() as f32 / speed) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f32 * speed;
+            let (index, frac) = (pos as usize, pos.fract());
+            let a = samples[index.min(samples.len() - 1)];
+            let b = samples[(index + 1).min(samples.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Stretches `samples` to `len` without changing their pitch: overlaps Hann windowed grains read
+/// at the original speed, each shifted a little to line up with the one before (WSOLA).
+fn stretch(samples: &[f32], len: usize) -> Vec<f32> {
+    let hop = GRAIN / 2;
+    let tolerance = GRAIN / 4;
+    let window: Vec<f32> = (0..GRAIN)
+        .map(|i| {
+            (std::f32::consts::PI * i as f32 / GRAIN as f32)
+                .sin()
+                .powi(2)
+        })
+        .collect();
+    let ratio = samples.len() as f32 / len.max(1) as f32;
+    let grain = |from: usize| samples.get(from..).unwrap_or_default().iter().take(GRAIN);
+
+    let mut out = vec![0.0; len];
+    let mut weight = vec![0.0f32; len];
+    let mut previous = 0;
+    for start in (0..len).step_by(hop) {
+        let nominal = (start as f32 * ratio) as usize;
+        // Where the previous grain would naturally carry on, the closest match to it is taken.
+        let natural: Vec<f32> = grain(previous + hop).copied().collect();
+        let from = (nominal.saturating_sub(tolerance)..=nominal + tolerance)
+            .max_by(|&a, &b| {
+                let score = |from| grain(from).zip(&natural).map(|(a, b)| a * b).sum::<f32>();
+                score(a).total_cmp(&score(b))
+            })
+            .unwrap_or(nominal);
+        previous = from;
+
+        for ((out, weight), (sample, w)) in out[start..]
+            .iter_mut()
+            .zip(&mut weight[start..])
+            .zip(grain(from).zip(&window))
+        {
+            *out += sample * w;
+            *weight += w;
+        }
+    }
+    for (sample, weight) in out.iter_mut().zip(weight) {
+        if weight > 1e-3 {
+            *sample /= weight;
+        }
+    }
+    out
+}
+
+fn mix(track: &mut Vec<f32>, samples: &[f32], start: usize, gain: f32) {
+    if track.len() < start + samples.len() {
+        track.resize(start + samples.len(), 0.0);
+    }
+    for (out, sample) in track[start..].iter_mut().zip(samples) {
+        *out += sample * gain;
+    }
+}
+
+/// Mixes in white noise, low passed by `colour` so it sounds like a hum and a hiss rather than
+/// static.
+fn add_noise(track: &mut [f32], gain: f32, colour: f32, rng: &mut impl Rng) {
+    let mut low = 0.0;
+    for sample in track {
+        let white: f32 = rng.random_range(-1.0..1.0);
+        low += (white - low) * colour;
+        *sample += (low * 2.0 + white * 0.3) * gain;
+    }
+}
+
+fn normalize(track: &mut [f32]) {
+    let peak = track.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak > 0.0 {
+        track.iter_mut().for_each(|s| *s *= 0.9 / peak);
+    }
+}
+
+/// Encodes samples in `[-1, 1]` as 16 bit mono PCM WAV at [`SAMPLE_RATE`].
+pub fn encode_wav(samples: &[f32]) -> Vec<u8> {
+    let mut wav = Cursor::new(Vec::with_capacity(44 + samples.len() * 2));
+    let mut writer = WavWriter::new(&mut wav, WAV_SPEC).expect("writing wav header to memory");
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        writer
+            .write_sample(sample)
+            .expect("writing wav samples to memory");
+    }
+    writer.finalize().expect("writing wav to memory");
+    wav.into_inner()
+}
+
+/// Decodes 16 bit mono PCM WAV at [`SAMPLE_RATE`], the only format the samples come in.
+pub fn decode_wav(wav: &[u8]) -> Result<Vec<f32>, WavError> {
+    let reader = WavReader::new(wav)?;
+    let spec = reader.spec();
+    if spec != WAV_SPEC {
+        return Err(WavError::UnsupportedFormat {
+            channels: spec.channels,
+            bits: spec.bits_per_sample,
+            rate: spec.sample_rate,
+        });
+    }
+    Ok(reader
+        .into_samples::<i16>()
+        .map(|sample| sample.map(|s| s as f32 / i16::MAX as f32))
+        .collect::<Result<_, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_samples_decode() {
+        for (voice, samples) in SAMPLES.iter().enumerate() {
+            for (digit, samples) in samples.iter().enumerate() {
+                assert!(
+                    samples.len() > secs(0.2),
+                    "sample of {digit} in voice {voice} is too short"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wav_round_trips() {
+        let samples = [0.0, 0.5, -0.5, 1.0, -1.0];
+        let decoded = decode_wav(&encode_wav(&samples)).unwrap();
+
+        assert_eq!(decoded.len(), samples.len());
+        for (a, b) in decoded.iter().zip(samples) {
+            assert!((a - b).abs() < 1e-4);
+        }
+        assert!(matches!(decode_wav(b"RIFF....AVI "), Err(WavError::Wav(_))));
+    }
+
+    #[test]
+    fn speed_changes_length() {
+        let samples = vec![0.0; 1000];
+
+        assert_eq!(resample(&samples, 2.0).len(), 500);
+        assert_eq!(resample(&samples, 0.5).len(), 2000);
+    }
+
+    #[test]
+    fn stretch_keeps_pitch() {
+        let period = 40;
+        let tone: Vec<f32> = (0..4000)
+            .map(|i| (std::f32::consts::TAU * i as f32 / period as f32).sin())
+            .collect();
+        let stretched = stretch(&tone, 6000);
+
+        assert_eq!(stretched.len(), 6000);
+        assert_eq!(stretch(&tone, 2000).len(), 2000);
+        // A tone keeps its period, so it's still in phase with itself a period later.
+        let middle = &stretched[2000..2200];
+        let shifted = &stretched[2000 + period..2200 + period];
+        let error = middle
+            .iter()
+            .zip(shifted)
+            .map(|(a, b)| (a - b).abs())
+            .sum::<f32>();
+        assert!(error / 200.0 < 0.2, "stretched tone drifted by {error}");
+    }
+
+    #[test]
+    fn clips_speak_random_digits() {
+        let mut rng = rand::rng();
+        let clip = AudioClip::random(&mut rng);
+        let samples = decode_wav(&clip.wav).unwrap();
+
+        assert_eq!(clip.digits.len(), DIGITS);
+        assert!(clip.digits.chars().all(|c| c.is_ascii_digit()));
+        assert!(samples.len() > secs(DIGITS as f32 * 0.5));
+        assert!(samples.iter().all(|s| s.abs() <= 0.91));
+        assert_ne!(AudioClip::random(&mut rng).wav, clip.wav);
+    }
+}
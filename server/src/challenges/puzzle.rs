@@ -0,0 +1,377 @@
+//! Slider puzzle rendered by the server, the fallback for when the interactive challenges can't
+//! load (e.g. no WebGL). A piece is cut out of a random picture and the user slides it back into
+//! its hole. The picture is generated from a [`Seed`], so the server regenerates it to check the
+//! answer without storing it.
+
+use std::{io::Cursor, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use base64::prelude::*;
+use image::{ImageFormat, Rgba, RgbaImage};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{BoxFuture, Challenge, Instance, Seed};
+use crate::{analysis::interaction::Score, rate_limit::RateLimiter};
+
+pub const WIDTH: u32 = 280;
+pub const HEIGHT: u32 = 160;
+/// Side of the square part of the piece.
+pub const PIECE_SIZE: u32 = 44;
+/// Radius of the knob sticking out of the right of the piece and of the notch cut in its top.
+const KNOB_RADIUS: u32 = 8;
+/// Width of the piece image, including its knob.
+pub const PIECE_WIDTH: u32 = PIECE_SIZE + KNOB_RADIUS;
+/// Share of the picture left under the holes, the rest is darkened. Both holes are shaded alike so
+/// neither gives the answer away.
+const HOLE_SHADE: f32 = 0.5;
+const BLOBS: usize = 14;
+const NOISE: i16 = 14;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PuzzleConfig {
+    /// Score of the response tokens, below the passing score of the interactive challenges as
+    /// sliders are easier for bots.
+    #[serde(with = "crate::serde::as_string")]
+    pub score: f32,
+    /// Pixels the piece may be off its hole by.
+    #[serde(with = "crate::serde::as_string")]
+    pub tolerance: u32,
+    /// Puzzles served per address in each window.
+    #[serde(with = "crate::serde::as_string")]
+    pub max_requests: u32,
+    #[serde(with = "crate::serde::as_string")]
+    pub window_secs: u64,
+}
+
+impl Default for PuzzleConfig {
+    fn default() -> Self {
+        Self { score: 0.4, tolerance: 5, max_requests: 20, window_secs: 600 }
+    }
+}
+
+/// Puzzle settings, shared by the puzzle routes and puzzles registered as challenges.
+#[derive(Debug, Clone)]
+pub struct Puzzles {
+    pub score: f32,
+    pub tolerance: u32,
+    /// In memory, per server instance, see [`RateLimiter`]. Checked by the puzzle route and when
+    /// issuing instances of the puzzles registered as challenges.
+    pub limiter: Arc<RateLimiter>,
+}
+
+impl From<PuzzleConfig> for Puzzles {
+    fn from(config: PuzzleConfig) -> Self {
+        Self {
+            score: config.score,
+            tolerance: config.tolerance,
+            limiter: Arc::new(RateLimiter::new(
+                config.max_requests,
+                Duration::from_secs(config.window_secs),
+            )),
+        }
+    }
+}
+
+impl Puzzles {
+    pub fn is_solved(&self, puzzle: &Puzzle, answer_x: u32) -> bool {
+        puzzle.target_x.abs_diff(answer_x) <= self.tolerance
+    }
+}
+
+/// What clients render a puzzle instance from.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PuzzleData {
+    /// PNG of the picture with the hole, encoded in base64.
+    pub background: String,
+    /// PNG of the piece to slide into the hole, encoded in base64.
+    pub piece: String,
+    /// Top of the piece over the picture, it only slides horizontally. The decoy hole is on the
+    /// same row, so it doesn't give the answer away.
+    pub piece_y: u32,
+}
+
+/// Answer to a puzzle instance.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PuzzleAnswer {
+    /// Left of the piece where the user dropped it.
+    pub x: u32,
+}
+
+impl Challenge for Puzzles {
+    fn issue(&self, seed: Seed) -> BoxFuture<'_, anyhow::Result<Instance>> {
+        Box::pin(async move {
+            let puzzle = Puzzle::render(seed).await?;
+            let data = serde_json::to_value(PuzzleData::from(puzzle))?;
+            Ok(Instance { seed, data })
+        })
+    }
+
+    fn verify<'a>(
+        &'a self,
+        instance: &'a Instance,
+        answer: &'a Value,
+    ) -> BoxFuture<'a, anyhow::Result<Score>> {
+        Box::pin(async move {
+            let PuzzleAnswer { x } = PuzzleAnswer::deserialize(answer)
+                .context("puzzle answer must be an object with the piece `x`")?;
+            Ok(
+                match self.is_solved(&Puzzle::render(instance.seed).await?, x) {
+                    true => Score(self.score),
+                    false => Score(0.),
+                },
+            )
+        })
+    }
+}
+
+impl From<Puzzle> for PuzzleData {
+    fn from(puzzle: Puzzle) -> Self {
+        Self {
+            background: BASE64_STANDARD.encode(puzzle.background),
+            piece: BASE64_STANDARD.encode(puzzle.piece),
+            piece_y: puzzle.piece_y,
+        }
+    }
+}
+
+/// A rendered puzzle and where its piece fits.
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    /// PNG of the picture with the hole, [`WIDTH`] by [`HEIGHT`].
+    pub background: Vec<u8>,
+    /// PNG of the piece, [`PIECE_WIDTH`] by [`PIECE_SIZE`], transparent around its outline.
+    pub piece: Vec<u8>,
+    /// Top of the piece, it only slides horizontally.
+    pub piece_y: u32,
+    /// Left of the piece when it fits its hole.
+    pub target_x: u32,
+    /// Left of the decoy hole.
+    pub decoy_x: u32,
+}
+
+impl Puzzle {
+    /// Generates the puzzle off the async runtime, encoding its PNGs takes a few milliseconds of
+    /// CPU.
+    pub async fn render(seed: Seed) -> anyhow::Result<Self> {
+        tokio::task::spawn_blocking(move || Self::generate(seed))
+            .await
+            .context("rendering puzzle")
+    }
+
+    pub fn generate(Seed(seed): Seed) -> Self {
+        let mut rng = StdRng::from_seed(seed);
+        let mut picture = picture(&mut rng);
+
+        // leaves room for the piece to start at the left
+        let target_x = rng.random_range(PIECE_SIZE + 16..WIDTH - PIECE_WIDTH - 4);
+        let piece_y = rng.random_range(KNOB_RADIUS + 4..HEIGHT - PIECE_SIZE - 4);
+
+        let mut piece = RgbaImage::new(PIECE_WIDTH, PIECE_SIZE);
+        for (x, y, pixel) in piece.enumerate_pixels_mut() {
+            if in_piece(x, y) {
+                let color = picture.get_pixel(target_x + x, piece_y + y).0;
+                pixel.0 = match on_outline(x, y) {
+                    true => blend(color, [255, 255, 255, 255], 0.6),
+                    false => color,
+                };
+            }
+        }
+
+        // a decoy hole on the same row, away from the real one
+        let decoy_x = loop {
+            let x = rng.random_range(0..WIDTH - PIECE_WIDTH);
+            if x.abs_diff(target_x) > PIECE_WIDTH {
+                break x;
+            }
+        };
+        cut_hole(&mut picture, decoy_x, piece_y);
+        cut_hole(&mut picture, target_x, piece_y);
+
+        Self {
+            background: encode_png(&picture),
+            piece: encode_png(&piece),
+            piece_y,
+            target_x,
+            decoy_x,
+        }
+    }
+}
+
+/// Gradient covered in translucent blobs and grain, busy enough that the hole's edges don't stand
+/// out on their own.
+fn picture(rng: &mut impl Rng) -> RgbaImage {
+    let (from, to) = (random_color(rng), random_color(rng));
+    let mut picture = RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let t = (x + y) as f32 / (WIDTH + HEIGHT) as f32;
+        Rgba(blend(from, to, t))
+    });
+
+    for _ in 0..BLOBS {
+        let (cx, cy) = (rng.random_range(0..WIDTH), rng.random_range(0..HEIGHT));
+        let radius = rng.random_range(8..40u32);
+        let color = random_color(rng);
+        let alpha = rng.random_range(0.3..0.7);
+        for (x, y, pixel) in picture.enumerate_pixels_mut() {
+            let (dx, dy) = (x.abs_diff(cx), y.abs_diff(cy));
+            if dx * dx + dy * dy <= radius * radius {
+                pixel.0 = blend(pixel.0, color, alpha);
+            }
+        }
+    }
+
+    for pixel in picture.pixels_mut() {
+        let grain = rng.random_range(-NOISE..=NOISE);
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as i16 + grain).clamp(0, 255) as u8;
+        }
+    }
+    picture
+}
+
+fn cut_hole(picture: &mut RgbaImage, left: u32, top: u32) {
+    for y in 0..PIECE_SIZE {
+        for x in 0..PIECE_WIDTH {
+            if !in_piece(x, y) {
+                continue;
+            }
+            let pixel = picture.get_pixel_mut(left + x, top + y);
+            pixel.0 = match on_outline(x, y) {
+                true => blend(pixel.0, [255, 255, 255, 255], 0.5),
+                false => blend([0, 0, 0, 255], pixel.0, HOLE_SHADE),
+            };
+        }
+    }
+}
+
+/// Whether a point relative to the top left of the piece is inside its outline: a square with a
+/// knob on the right and a notch in the top.
+fn in_piece(x: u32, y: u32) -> bool {
+    let within = |cx: u32, cy: u32| {
+        let (dx, dy) = (x.abs_diff(cx), y.abs_diff(cy));
+        dx * dx + dy * dy <= KNOB_RADIUS * KNOB_RADIUS
+    };
+    let body = x < PIECE_SIZE && y < PIECE_SIZE && !within(PIECE_SIZE / 2, 0);
+    let knob = within(PIECE_SIZE, PIECE_SIZE / 2);
+    body || knob
+}
+
+fn on_outline(x: u32, y: u32) -> bool {
+    in_piece(x, y)
+        && (x == 0
+            || y == 0
+            || !in_piece(x - 1, y)
+            || !in_piece(x + 1, y)
+            || !in_piece(x, y - 1)
+            || !in_piece(x, y + 1))
+}
+
+fn random_color(rng: &mut impl Rng) -> [u8; 4] {
+    [rng.random(), rng.random(), rng.random(), 255]
+}
+
+/// Mixes `t` of `to` into `from`.
+fn blend(from: [u8; 4], to: [u8; 4], t: f32) -> [u8; 4] {
+    let mut out = from;
+    for (out, (a, b)) in out.iter_mut().zip(from.iter().zip(to)).take(3) {
+        *out = (*a as f32 + (b as f32 - *a as f32) * t).round() as u8;
+    }
+    out
+}
+
+fn encode_png(image: &RgbaImage) -> Vec<u8> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .expect("encoding png to memory");
+    png.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn seed(n: u8) -> Seed {
+        Seed([n; 32])
+    }
+
+    #[test]
+    fn puzzles_are_reproducible_from_their_seed() {
+        let a = Puzzle::generate(seed(7));
+        let b = Puzzle::generate(seed(7));
+        let c = Puzzle::generate(seed(8));
+
+        assert_eq!(a.background, b.background);
+        assert_eq!(a.piece, b.piece);
+        assert_eq!(a.target_x, b.target_x);
+        assert_ne!(a.background, c.background);
+    }
+
+    #[test]
+    fn images_have_the_puzzle_sizes() {
+        let puzzle = Puzzle::generate(seed(1));
+        let background = image::load_from_memory(&puzzle.background).unwrap();
+        let piece = image::load_from_memory(&puzzle.piece).unwrap().to_rgba8();
+
+        assert_eq!((background.width(), background.height()), (WIDTH, HEIGHT));
+        assert_eq!((piece.width(), piece.height()), (PIECE_WIDTH, PIECE_SIZE));
+        assert!(puzzle.target_x + PIECE_WIDTH <= WIDTH);
+        assert_eq!(
+            piece.get_pixel(PIECE_SIZE / 2, 0).0[3],
+            0,
+            "notch is transparent"
+        );
+        assert_eq!(piece.get_pixel(PIECE_SIZE / 2, PIECE_SIZE / 2).0[3], 255);
+    }
+
+    #[test]
+    fn decoy_is_cut_like_the_hole() {
+        let puzzle = Puzzle::generate(seed(4));
+        let background = image::load_from_memory(&puzzle.background)
+            .unwrap()
+            .to_rgba8();
+
+        // the picture is drawn first, the same from the same seed
+        let mut picture = picture(&mut StdRng::from_seed(seed(4).0));
+        cut_hole(&mut picture, puzzle.target_x, puzzle.piece_y);
+        cut_hole(&mut picture, puzzle.decoy_x, puzzle.piece_y);
+
+        assert!(puzzle.decoy_x.abs_diff(puzzle.target_x) > PIECE_WIDTH);
+        assert!(background == picture, "holes are cut alike");
+    }
+
+    #[test]
+    fn answers_within_tolerance_solve() {
+        let puzzles = Puzzles::from(PuzzleConfig::default());
+        let puzzle = Puzzle::generate(seed(1));
+
+        assert!(puzzles.is_solved(&puzzle, puzzle.target_x));
+        assert!(puzzles.is_solved(&puzzle, puzzle.target_x + 5));
+        assert!(!puzzles.is_solved(&puzzle, puzzle.target_x - 6));
+    }
+
+    #[tokio::test]
+    async fn instances_are_verified_from_their_seed() {
+        let puzzles = Puzzles::from(PuzzleConfig::default());
+        let instance = puzzles.issue(seed(3)).await.unwrap();
+        let data = PuzzleData::deserialize(&instance.data).unwrap();
+        let Puzzle { target_x, piece_y, .. } = Puzzle::generate(seed(3));
+
+        assert_eq!(data.piece_y, piece_y);
+        let Score(score) = puzzles
+            .verify(&instance, &json!({ "x": target_x }))
+            .await
+            .unwrap();
+        assert_eq!(score, 0.4);
+        let Score(score) = puzzles
+            .verify(&instance, &json!({ "x": target_x + 20 }))
+            .await
+            .unwrap();
+        assert_eq!(score, 0.);
+        assert!(puzzles.verify(&instance, &json!(target_x)).await.is_err());
+    }
+}
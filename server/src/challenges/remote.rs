@@ -0,0 +1,169 @@
+//! Challenges hosted anywhere and verified through a callback. The server POSTs JSON to the
+//! challenge's verify URL to issue an instance from a seed and to score an answer to it. Requests
+//! are signed with the secret handed out when the challenge was registered:
+//!
+//! - `X-Gotcha-Timestamp`: unix seconds when the request was sent.
+//! - `X-Gotcha-Signature`: `sha256=<hex HMAC-SHA256 of "{timestamp}.{body}" keyed by the secret>`.
+//!
+//! Callbacks should reject requests whose signature doesn't match or whose timestamp is stale.
+
+use std::time::Duration;
+
+use anyhow::{Context, anyhow};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use time::OffsetDateTime;
+use url::Url;
+
+use super::{BoxFuture, Challenge, Instance, Seed};
+use crate::{HTTP_CLIENT, analysis::interaction::Score, db::DbChallenge};
+
+pub const TIMESTAMP_HEADER: &str = "X-Gotcha-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Gotcha-Signature";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Body of the callback requests.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum CallbackRequest {
+    /// Issue the instance of `seed`, the same seed must always give the same instance.
+    Issue { seed: String },
+    /// Score `answer` to the instance issued from `seed`.
+    Verify { seed: String, data: Value, answer: Value },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssueResponse {
+    pub data: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyResponse {
+    /// From 0 for a wrong answer to 1, clamped.
+    pub score: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteChallenge {
+    pub verify_url: Url,
+    pub secret: String,
+}
+
+impl TryFrom<&DbChallenge> for RemoteChallenge {
+    type Error = anyhow::Error;
+
+    fn try_from(challenge: &DbChallenge) -> Result<Self, Self::Error> {
+        let verify_url = challenge
+            .verify_url
+            .as_deref()
+            .ok_or_else(|| anyhow!("remote challenge {} has no verify url", challenge.url))?;
+        let secret = challenge
+            .verify_secret
+            .clone()
+            .ok_or_else(|| anyhow!("remote challenge {} has no verify secret", challenge.url))?;
+        Ok(Self { verify_url: verify_url.parse()?, secret })
+    }
+}
+
+impl RemoteChallenge {
+    async fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: &CallbackRequest,
+    ) -> anyhow::Result<T> {
+        let body = serde_json::to_string(request)?;
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let response = HTTP_CLIENT
+            .post(self.verify_url.clone())
+            .timeout(TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, sign(&self.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("calling back {}", self.verify_url))?
+            .error_for_status()?;
+        Ok(response.json().await?)
+    }
+}
+
+impl Challenge for RemoteChallenge {
+    fn issue(&self, seed: Seed) -> BoxFuture<'_, anyhow::Result<Instance>> {
+        Box::pin(async move {
+            let IssueResponse { data } = self
+                .call(&CallbackRequest::Issue { seed: seed.to_hex() })
+                .await?;
+            Ok(Instance { seed, data })
+        })
+    }
+
+    fn verify<'a>(
+        &'a self,
+        instance: &'a Instance,
+        answer: &'a Value,
+    ) -> BoxFuture<'a, anyhow::Result<Score>> {
+        Box::pin(async move {
+            let request = CallbackRequest::Verify {
+                seed: instance.seed.to_hex(),
+                data: instance.data.clone(),
+                answer: answer.clone(),
+            };
+            let VerifyResponse { score } = self.call(&request).await?;
+            Ok(Score(score.clamp(0., 1.)))
+        })
+    }
+}
+
+/// Value of the [`SIGNATURE_HEADER`] of a callback request.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn signatures_cover_timestamp_and_body() {
+        let signature = sign("secret", 1, "{}");
+
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(signature, sign("secret", 1, "{}"));
+        assert_ne!(signature, sign("other", 1, "{}"));
+        assert_ne!(signature, sign("secret", 2, "{}"));
+        assert_ne!(signature, sign("secret", 1, "{ }"));
+    }
+
+    #[test]
+    fn requests_are_tagged_by_type() {
+        let request = CallbackRequest::Verify {
+            seed: "ab".into(),
+            data: json!({ "q": 1 }),
+            answer: json!(2),
+        };
+
+        assert_eq!(
+            serde_json::to_value(request).unwrap(),
+            json!({ "type": "verify", "seed": "ab", "data": { "q": 1 }, "answer": 2 })
+        );
+    }
+
+    #[test]
+    fn needs_verify_url_and_secret() {
+        let mut challenge = DbChallenge::new("https://challenge.com".into());
+        assert!(RemoteChallenge::try_from(&challenge).is_err());
+
+        challenge.verify_url = Some("https://challenge.com/verify".into());
+        challenge.verify_secret = Some("secret".into());
+        let remote = RemoteChallenge::try_from(&challenge).unwrap();
+        assert_eq!(remote.verify_url.as_str(), "https://challenge.com/verify");
+    }
+}
@@ -3,6 +3,15 @@ use std::path::PathBuf;
 use secrecy::Secret;
 use serde::Deserialize;
 
+use crate::{
+    accessibility::AccessibilityConfig,
+    analysis::proof_of_work::PowConfig,
+    challenges::puzzle::PuzzleConfig,
+    health::HealthCheckConfig,
+    logging::LoggingConfig,
+    telemetry::{MetricsConfig, OtlpConfig},
+};
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub application: ApplicationConfig,
@@ -16,6 +25,20 @@ pub struct ApplicationConfig {
     pub port: u16,
     pub serve_dir: PathBuf,
     pub auth_origin: String,
+    #[serde(default)]
+    pub health_check: HealthCheckConfig,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    #[serde(default)]
+    pub puzzle: PuzzleConfig,
+    #[serde(default)]
+    pub proof_of_work: PowConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub otlp: OtlpConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
 }
 
 #[derive(Debug, Deserialize)]
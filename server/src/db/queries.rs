@@ -1,9 +1,10 @@
 //! Abstraction for database queries.
 
-use std::{fmt::Debug, ops::DerefMut};
+use std::{collections::BTreeMap, fmt::Debug, net::IpAddr, ops::DerefMut};
 
 use anyhow::Context;
-use sqlx::{PgExecutor, Postgres, Transaction, prelude::*};
+use sqlx::{PgExecutor, Postgres, Transaction, prelude::*, types::Json};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::encodings::{Base64, UrlSafe};
@@ -26,6 +27,9 @@ pub struct DbApiKey {
     #[sqlx(try_from = "String")]
     pub secret: Base64,
     pub label: Option<String>,
+    pub invisible: bool,
+    pub score_threshold: f32,
+    pub customization_profile_id: Option<Uuid>,
 }
 
 impl TryFrom<DbApiKeyInternal> for DbApiKey {
@@ -46,6 +50,9 @@ impl TryFrom<DbApiKeyInternal> for DbApiKey {
                 .try_into()
                 .context("could not convert secret from string")?,
             label: value.label,
+            invisible: value.invisible,
+            score_threshold: value.score_threshold,
+            customization_profile_id: value.customization_profile_id,
         })
     }
 }
@@ -57,6 +64,9 @@ struct DbApiKeyInternal {
     pub encoding_key: String,
     pub secret: String,
     pub label: Option<String>,
+    pub invisible: bool,
+    pub score_threshold: f32,
+    pub customization_profile_id: Option<Uuid>,
 }
 
 // Extension trait to try to map nested types inside a result type.
@@ -130,7 +140,8 @@ pub async fn fetch_api_key_by_site_key(
 ) -> Result<Option<DbApiKey>> {
     sqlx::query_as!(
         DbApiKeyInternal,
-        "select site_key, encoding_key, secret, label from api_key where site_key = $1",
+        "select site_key, encoding_key, secret, label, invisible, score_threshold, customization_profile_id
+        from api_key where site_key = $1",
         site_key.as_str()
     )
     .fetch_optional(exec)
@@ -145,7 +156,8 @@ pub async fn fetch_api_key_by_secret(
 ) -> Result<Option<DbApiKey>> {
     sqlx::query_as!(
         DbApiKeyInternal,
-        "select site_key, encoding_key, secret, label from api_key where secret = $1",
+        "select site_key, encoding_key, secret, label, invisible, score_threshold, customization_profile_id
+        from api_key where secret = $1",
         secret.as_str()
     )
     .fetch_optional(exec)
@@ -160,7 +172,8 @@ pub async fn fetch_api_keys(
 ) -> Result<Vec<DbApiKey>> {
     sqlx::query_as!(
         DbApiKeyInternal,
-        "select site_key, encoding_key, secret, label from api_key where console_id = $1 order by created_at",
+        "select site_key, encoding_key, secret, label, invisible, score_threshold, customization_profile_id
+        from api_key where console_id = $1 order by created_at",
         console_id
     )
     .fetch_all(exec)
@@ -242,6 +255,9 @@ pub(crate) async fn with_console_insert_api_key(
 #[derive(Debug)]
 pub struct DbUpdateApiKey<'a> {
     pub label: Option<&'a str>,
+    pub invisible: Option<bool>,
+    pub score_threshold: Option<f32>,
+    pub customization_profile_id: Option<Option<Uuid>>,
 }
 
 pub async fn update_api_key(
@@ -250,9 +266,23 @@ pub async fn update_api_key(
     console_id: &Uuid,
     update: DbUpdateApiKey<'_>,
 ) -> Result<RowsAffected> {
+    let (should_update_profile, profile_value) = match update.customization_profile_id {
+        None => (false, None),
+        Some(value) => (true, value),
+    };
+
     let res = sqlx::query!(
-        "update api_key set label = coalesce($1, label) where site_key = $2 and console_id = $3",
+        "update api_key set
+            label = coalesce($1, label),
+            invisible = coalesce($2, invisible),
+            score_threshold = coalesce($3, score_threshold),
+            customization_profile_id = case when $4 then $5 else customization_profile_id end
+        where site_key = $6 and console_id = $7",
         update.label,
+        update.invisible,
+        update.score_threshold,
+        should_update_profile,
+        profile_value,
         site_key,
         console_id
     )
@@ -399,6 +429,25 @@ pub struct DbChallenge {
     pub small_width: i16,
     pub small_height: i16,
     pub logo_url: Option<String>,
+    pub weight: i16,
+    pub difficulty: i16,
+    pub requires_webgl: bool,
+    /// Whether admins allow serving it.
+    pub enabled: bool,
+    /// Attempts allowed before the challenge is failed.
+    pub max_attempts: i16,
+    /// Moves per attempt, meaning is up to the challenge, `None` keeps its own default.
+    pub moves_per_attempt: Option<i16>,
+    /// Whether it reports game traces, see [`crate::analysis::game`].
+    pub traced: bool,
+    /// Whether the last health check succeeded, challenges never checked are healthy.
+    pub healthy: bool,
+    /// How answers are verified, see [`crate::challenges::ChallengeKind`].
+    pub kind: String,
+    /// Callback of remote challenges.
+    pub verify_url: Option<String>,
+    /// Signs the requests to `verify_url`.
+    pub verify_secret: Option<String>,
 }
 
 impl DbChallenge {
@@ -411,6 +460,17 @@ impl DbChallenge {
             small_width: 360,
             small_height: 500,
             logo_url: None,
+            weight: 1,
+            difficulty: 1,
+            requires_webgl: false,
+            enabled: true,
+            max_attempts: 3,
+            moves_per_attempt: None,
+            traced: false,
+            healthy: true,
+            kind: "widget".into(),
+            verify_url: None,
+            verify_secret: None,
         }
     }
 }
@@ -423,9 +483,23 @@ pub async fn fetch_challenges(exec: impl PgExecutor<'_> + Send) -> Result<Vec<Db
             label,
             default_width as width,
             default_height as height,
-            default_width as small_width,
-            default_height as small_height,
-            default_logo_url as logo_url
+            default_small_width as small_width,
+            default_small_height as small_height,
+            default_logo_url as logo_url,
+            weight,
+            difficulty,
+            requires_webgl,
+            enabled,
+            max_attempts,
+            moves_per_attempt,
+            traced,
+            coalesce(
+                (select healthy from challenge_health where challenge_url = challenge.url),
+                true
+            ) as \"healthy!\",
+            kind,
+            verify_url,
+            verify_secret
         from challenge"
     )
     .fetch_all(exec)
@@ -433,6 +507,91 @@ pub async fn fetch_challenges(exec: impl PgExecutor<'_> + Send) -> Result<Vec<Db
     .map(Ok)?
 }
 
+pub async fn fetch_challenge(
+    exec: impl PgExecutor<'_> + Send,
+    url: &str,
+) -> Result<Option<DbChallenge>> {
+    sqlx::query_as!(
+        DbChallenge,
+        "select
+            url,
+            label,
+            default_width as width,
+            default_height as height,
+            default_small_width as small_width,
+            default_small_height as small_height,
+            default_logo_url as logo_url,
+            weight,
+            difficulty,
+            requires_webgl,
+            enabled,
+            max_attempts,
+            moves_per_attempt,
+            traced,
+            coalesce(
+                (select healthy from challenge_health where challenge_url = challenge.url),
+                true
+            ) as \"healthy!\",
+            kind,
+            verify_url,
+            verify_secret
+        from challenge
+        where url = $1",
+        url,
+    )
+    .fetch_optional(exec)
+    .await
+    .map(Ok)?
+}
+
+/// Fetches challenges ordered by creation, `limit` at a time.
+pub async fn fetch_challenges_page(
+    exec: impl PgExecutor<'_> + Send,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<DbChallenge>> {
+    sqlx::query_as!(
+        DbChallenge,
+        "select
+            url,
+            label,
+            default_width as width,
+            default_height as height,
+            default_small_width as small_width,
+            default_small_height as small_height,
+            default_logo_url as logo_url,
+            weight,
+            difficulty,
+            requires_webgl,
+            enabled,
+            max_attempts,
+            moves_per_attempt,
+            traced,
+            coalesce(
+                (select healthy from challenge_health where challenge_url = challenge.url),
+                true
+            ) as \"healthy!\",
+            kind,
+            verify_url,
+            verify_secret
+        from challenge
+        order by created_at, url
+        offset $1 limit $2",
+        offset,
+        limit,
+    )
+    .fetch_all(exec)
+    .await
+    .map(Ok)?
+}
+
+pub async fn count_challenges(exec: impl PgExecutor<'_> + Send) -> Result<i64> {
+    sqlx::query_scalar!("select count(*) as \"count!\" from challenge")
+        .fetch_one(exec)
+        .await
+        .map(Ok)?
+}
+
 pub async fn fetch_challenges_with_customization(
     exec: impl PgExecutor<'_> + Send,
     site_key: &Base64<UrlSafe>,
@@ -440,19 +599,34 @@ pub async fn fetch_challenges_with_customization(
     sqlx::query_as!(
         DbChallenge,
         "select
-            c.url,
+            c.url as \"url!\",
             c.label,
-            coalesce(cc.width, c.default_width) as \"width!\",
-            coalesce(cc.height, c.default_height) as \"height!\",
-            coalesce(cc.small_width, c.default_width) as \"small_width!\",
-            coalesce(cc.small_height, c.default_height) as \"small_height!\",
-            coalesce(cc.logo_url, c.default_logo_url) as logo_url
+            coalesce(p.width, cc.width, c.default_width) as \"width!\",
+            coalesce(p.height, cc.height, c.default_height) as \"height!\",
+            coalesce(p.small_width, cc.small_width, c.default_small_width) as \"small_width!\",
+            coalesce(p.small_height, cc.small_height, c.default_small_height) as \"small_height!\",
+            coalesce(p.logo_url, cc.logo_url, c.default_logo_url) as logo_url,
+            coalesce(ch.weight, c.weight) as \"weight!\",
+            c.difficulty as \"difficulty!\",
+            c.requires_webgl as \"requires_webgl!\",
+            c.enabled as \"enabled!\",
+            c.max_attempts as \"max_attempts!\",
+            c.moves_per_attempt,
+            c.traced as \"traced!\",
+            coalesce(
+                (select healthy from public.challenge_health where challenge_url = c.url),
+                true
+            ) as \"healthy!\",
+            c.kind as \"kind!\",
+            c.verify_url,
+            c.verify_secret
         from public.challenge c
-        left join public.challenge_customization cc on cc.console_id = (
-            select console_id
-            from public.api_key
-            where site_key = $1
-        )",
+        left join public.api_key k on k.site_key = $1
+        left join public.customization_profile p on p.id = k.customization_profile_id
+        left join public.challenge_customization cc on cc.console_id = k.console_id
+        left join public.console_challenge ch
+            on ch.console_id = k.console_id and ch.challenge_url = c.url
+        where coalesce(ch.enabled, true)",
         site_key.as_str(),
     )
     .fetch_all(exec)
@@ -465,17 +639,109 @@ pub async fn insert_challenge(
     challenge: &DbChallenge,
 ) -> Result<()> {
     sqlx::query!(
-        "insert into challenge (url, default_width, default_height, default_logo_url) values ($1, $2, $3, $4)",
+        "insert into challenge (
+            url, label, default_width, default_height, default_small_width, default_small_height,
+            default_logo_url, weight, difficulty, requires_webgl, enabled, max_attempts,
+            moves_per_attempt, traced, kind, verify_url, verify_secret
+        )
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)",
         challenge.url,
+        challenge.label,
         challenge.width,
         challenge.height,
-        challenge.logo_url
+        challenge.small_width,
+        challenge.small_height,
+        challenge.logo_url,
+        challenge.weight,
+        challenge.difficulty,
+        challenge.requires_webgl,
+        challenge.enabled,
+        challenge.max_attempts,
+        challenge.moves_per_attempt,
+        challenge.traced,
+        challenge.kind,
+        challenge.verify_url,
+        challenge.verify_secret,
     )
     .execute(exec)
     .await?;
     Ok(())
 }
 
+#[derive(Debug, Default)]
+pub struct DbUpdateChallenge<'a> {
+    pub label: Option<Option<&'a str>>,
+    pub width: Option<i16>,
+    pub height: Option<i16>,
+    pub small_width: Option<i16>,
+    pub small_height: Option<i16>,
+    pub logo_url: Option<Option<&'a str>>,
+    pub weight: Option<i16>,
+    pub difficulty: Option<i16>,
+    pub requires_webgl: Option<bool>,
+    pub enabled: Option<bool>,
+    pub max_attempts: Option<i16>,
+    pub moves_per_attempt: Option<Option<i16>>,
+    pub traced: Option<bool>,
+}
+
+pub async fn update_challenge(
+    exec: impl PgExecutor<'_> + Send,
+    challenge_url: &str,
+    update: &DbUpdateChallenge<'_>,
+) -> Result<RowsAffected> {
+    let (should_update_label, label_value) = match update.label {
+        None => (false, None),
+        Some(value) => (true, value),
+    };
+    let (should_update_logo_url, logo_url_value) = match update.logo_url {
+        None => (false, None),
+        Some(value) => (true, value),
+    };
+    let (should_update_moves, moves_value) = match update.moves_per_attempt {
+        None => (false, None),
+        Some(value) => (true, value),
+    };
+
+    let res = sqlx::query!(
+        "update challenge set
+            label = case when $1 then $2 else label end,
+            default_width = coalesce($3, default_width),
+            default_height = coalesce($4, default_height),
+            default_small_width = coalesce($5, default_small_width),
+            default_small_height = coalesce($6, default_small_height),
+            default_logo_url = case when $7 then $8 else default_logo_url end,
+            weight = coalesce($9, weight),
+            difficulty = coalesce($10, difficulty),
+            requires_webgl = coalesce($11, requires_webgl),
+            enabled = coalesce($12, enabled),
+            max_attempts = coalesce($13, max_attempts),
+            moves_per_attempt = case when $14 then $15 else moves_per_attempt end,
+            traced = coalesce($16, traced)
+        where url = $17",
+        should_update_label,
+        label_value,
+        update.width,
+        update.height,
+        update.small_width,
+        update.small_height,
+        should_update_logo_url,
+        logo_url_value,
+        update.weight,
+        update.difficulty,
+        update.requires_webgl,
+        update.enabled,
+        update.max_attempts,
+        should_update_moves,
+        moves_value,
+        update.traced,
+        challenge_url,
+    )
+    .execute(exec)
+    .await?;
+    Ok(RowsAffected(res.rows_affected()))
+}
+
 pub async fn delete_challenge(
     exec: impl PgExecutor<'_> + Send,
     challenge_url: &str,
@@ -496,6 +762,166 @@ pub async fn delete_challenge_like(
     Ok(RowsAffected(res.rows_affected()))
 }
 
+#[derive(Debug)]
+pub struct DbChallengeHealth {
+    pub challenge_url: String,
+    pub healthy: bool,
+    pub status: Option<i16>,
+    pub latency_ms: Option<i32>,
+    pub error: Option<String>,
+    pub consecutive_failures: i16,
+    pub checked_at: OffsetDateTime,
+}
+
+/// Outcome of probing a challenge url.
+#[derive(Debug)]
+pub struct DbHealthProbe {
+    pub challenge_url: String,
+    pub success: bool,
+    pub status: Option<i16>,
+    pub latency_ms: Option<i32>,
+    pub error: Option<String>,
+}
+
+pub async fn fetch_challenge_health(
+    exec: impl PgExecutor<'_> + Send,
+) -> Result<Vec<DbChallengeHealth>> {
+    sqlx::query_as!(
+        DbChallengeHealth,
+        "select challenge_url, healthy, status, latency_ms, error, consecutive_failures, checked_at
+        from challenge_health"
+    )
+    .fetch_all(exec)
+    .await
+    .map(Ok)?
+}
+
+/// Records a probe of a challenge. The challenge turns unhealthy once `failure_threshold` probes
+/// failed in a row and healthy again on the first successful one.
+pub async fn record_challenge_health(
+    exec: impl PgExecutor<'_> + Send,
+    probe: &DbHealthProbe,
+    failure_threshold: i32,
+) -> Result<DbChallengeHealth> {
+    sqlx::query_as!(
+        DbChallengeHealth,
+        "insert into challenge_health
+            (challenge_url, healthy, status, latency_ms, error, consecutive_failures, checked_at)
+        values ($1, $2 or $6 > 1, $3, $4, $5, case when $2 then 0 else 1 end, now())
+        on conflict (challenge_url) do update set
+            healthy = $2 or challenge_health.consecutive_failures + 1 < $6,
+            status = excluded.status,
+            latency_ms = excluded.latency_ms,
+            error = excluded.error,
+            consecutive_failures = case when $2 then 0 else challenge_health.consecutive_failures + 1 end,
+            checked_at = excluded.checked_at
+        returning challenge_url, healthy, status, latency_ms, error, consecutive_failures, checked_at",
+        probe.challenge_url,
+        probe.success,
+        probe.status,
+        probe.latency_ms,
+        probe.error,
+        failure_threshold,
+    )
+    .fetch_one(exec)
+    .await
+    .map(Ok)?
+}
+
+/// Escalation of the challenges served to a client of a site.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DbEscalation {
+    pub failed_attempts: i16,
+    pub min_difficulty: i16,
+}
+
+/// Escalation of `addr` on the site, `None` when nothing was recorded in the last hour.
+pub async fn fetch_escalation(
+    exec: impl PgExecutor<'_> + Send,
+    site_key: &Base64<UrlSafe>,
+    addr: IpAddr,
+) -> Result<Option<DbEscalation>> {
+    sqlx::query_as!(
+        DbEscalation,
+        "select failed_attempts, min_difficulty from challenge_escalation
+        where site_key = $1 and addr = $2 and updated_at > now() - interval '1 hour'",
+        site_key.as_str(),
+        addr as _,
+    )
+    .fetch_optional(exec)
+    .await
+    .map(Ok)?
+}
+
+/// Counts a failed attempt of `addr`, starting over when its escalation is stale.
+pub async fn record_failed_attempt(
+    exec: impl PgExecutor<'_> + Send,
+    site_key: &Base64<UrlSafe>,
+    addr: IpAddr,
+) -> Result<()> {
+    sqlx::query!(
+        "insert into challenge_escalation (site_key, addr, failed_attempts)
+        values ($1, $2, 1)
+        on conflict (site_key, addr) do update set
+            failed_attempts = case
+                when challenge_escalation.updated_at > now() - interval '1 hour'
+                then least(challenge_escalation.failed_attempts + 1, 255)
+                else 1
+            end,
+            min_difficulty = case
+                when challenge_escalation.updated_at > now() - interval '1 hour'
+                then challenge_escalation.min_difficulty
+                else 0
+            end,
+            updated_at = now()",
+        site_key.as_str(),
+        addr as _,
+    )
+    .execute(exec)
+    .await?;
+    Ok(())
+}
+
+/// Raises the lowest difficulty served to `addr`, it's never lowered until the escalation is stale.
+pub async fn record_min_difficulty(
+    exec: impl PgExecutor<'_> + Send,
+    site_key: &Base64<UrlSafe>,
+    addr: IpAddr,
+    min_difficulty: i16,
+) -> Result<()> {
+    sqlx::query!(
+        "insert into challenge_escalation (site_key, addr, min_difficulty)
+        values ($1, $2, $3)
+        on conflict (site_key, addr) do update set
+            failed_attempts = case
+                when challenge_escalation.updated_at > now() - interval '1 hour'
+                then challenge_escalation.failed_attempts
+                else 0
+            end,
+            min_difficulty = case
+                when challenge_escalation.updated_at > now() - interval '1 hour'
+                then greatest(challenge_escalation.min_difficulty, excluded.min_difficulty)
+                else excluded.min_difficulty
+            end,
+            updated_at = now()",
+        site_key.as_str(),
+        addr as _,
+        min_difficulty,
+    )
+    .execute(exec)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_stale_escalations(exec: impl PgExecutor<'_> + Send) -> Result<RowsAffected> {
+    let res = sqlx::query!(
+        "delete from challenge_escalation where updated_at <= now() - interval '1 hour'"
+    )
+    .execute(exec)
+    .await?;
+    Ok(RowsAffected(res.rows_affected()))
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct DbChallengeCustomization {
     pub width: i16,
@@ -503,6 +929,25 @@ pub struct DbChallengeCustomization {
     pub small_width: i16,
     pub small_height: i16,
     pub logo_url: Option<String>,
+    pub exclude_webgl_on_low_end: bool,
+    pub escalate_difficulty: bool,
+    /// Offer the accessibility alternative to the interactive challenges.
+    pub accessibility_enabled: bool,
+    /// One of `light`, `dark` or `auto`.
+    pub theme_mode: String,
+    pub primary_color: Option<String>,
+    pub background_color: Option<String>,
+    pub text_color: Option<String>,
+    pub corner_radius: Option<i16>,
+    pub font_family: Option<String>,
+    /// Locale of the widgets, the user's one when `None`.
+    pub locale: Option<String>,
+    /// Overrides of the widgets strings by key.
+    pub strings: Json<BTreeMap<String, String>>,
+    /// One of `sha256` or `scrypt`.
+    pub pow_algorithm: String,
+    /// Memory cost of memory-hard proof of work algorithms.
+    pub pow_memory_kib: i32,
 }
 
 impl Default for DbChallengeCustomization {
@@ -513,6 +958,19 @@ impl Default for DbChallengeCustomization {
             small_width: 360,
             small_height: 500,
             logo_url: None,
+            exclude_webgl_on_low_end: true,
+            escalate_difficulty: true,
+            accessibility_enabled: true,
+            theme_mode: "auto".into(),
+            primary_color: None,
+            background_color: None,
+            text_color: None,
+            corner_radius: None,
+            font_family: None,
+            locale: None,
+            strings: Json::default(),
+            pow_algorithm: "sha256".into(),
+            pow_memory_kib: 4096,
         }
     }
 }
@@ -523,7 +981,26 @@ pub async fn fetch_challenge_customization(
 ) -> Result<Option<DbChallengeCustomization>> {
     sqlx::query_as!(
         DbChallengeCustomization,
-        "select width, height, small_width, small_height, logo_url from challenge_customization where console_id = $1",
+        "select
+            width,
+            height,
+            small_width,
+            small_height,
+            logo_url,
+            exclude_webgl_on_low_end,
+            escalate_difficulty,
+            accessibility_enabled,
+            theme_mode,
+            primary_color,
+            background_color,
+            text_color,
+            corner_radius,
+            font_family,
+            locale,
+            strings as \"strings: Json<BTreeMap<String, String>>\",
+            pow_algorithm,
+            pow_memory_kib
+        from challenge_customization where console_id = $1",
         console_id
     )
     .fetch_optional(exec)
@@ -531,6 +1008,41 @@ pub async fn fetch_challenge_customization(
     .map(Ok)?
 }
 
+pub async fn fetch_challenge_customization_by_site_key(
+    exec: impl PgExecutor<'_> + Send,
+    site_key: &Base64<UrlSafe>,
+) -> Result<Option<DbChallengeCustomization>> {
+    sqlx::query_as!(
+        DbChallengeCustomization,
+        "select
+            cc.width,
+            cc.height,
+            cc.small_width,
+            cc.small_height,
+            cc.logo_url,
+            cc.exclude_webgl_on_low_end,
+            cc.escalate_difficulty,
+            cc.accessibility_enabled,
+            cc.theme_mode,
+            cc.primary_color,
+            cc.background_color,
+            cc.text_color,
+            cc.corner_radius,
+            cc.font_family,
+            cc.locale,
+            cc.strings as \"strings: Json<BTreeMap<String, String>>\",
+            cc.pow_algorithm,
+            cc.pow_memory_kib
+        from public.challenge_customization cc
+        join public.api_key k on k.console_id = cc.console_id
+        where k.site_key = $1",
+        site_key.as_str()
+    )
+    .fetch_optional(exec)
+    .await
+    .map(Ok)?
+}
+
 pub async fn insert_challenge_customization(
     exec: impl PgExecutor<'_> + Send,
     console_id: &Uuid,
@@ -538,13 +1050,34 @@ pub async fn insert_challenge_customization(
 ) -> Result<()> {
     sqlx::query_as!(
         DbChallengeCustomization,
-        "insert into challenge_customization (console_id, width, height, small_width, small_height, logo_url) values ($1, $2, $3, $4, $5, $6)",
+        "insert into challenge_customization (
+            console_id, width, height, small_width, small_height, logo_url,
+            exclude_webgl_on_low_end, escalate_difficulty, accessibility_enabled, theme_mode,
+            primary_color, background_color, text_color, corner_radius, font_family, locale,
+            strings, pow_algorithm, pow_memory_kib
+        )
+        values (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19
+        )",
         console_id,
         insert.width,
         insert.height,
         insert.small_width,
         insert.small_height,
         insert.logo_url,
+        insert.exclude_webgl_on_low_end,
+        insert.escalate_difficulty,
+        insert.accessibility_enabled,
+        insert.theme_mode,
+        insert.primary_color,
+        insert.background_color,
+        insert.text_color,
+        insert.corner_radius,
+        insert.font_family,
+        insert.locale,
+        insert.strings as _,
+        insert.pow_algorithm,
+        insert.pow_memory_kib,
     )
     .execute(exec)
     .await?;
@@ -558,6 +1091,37 @@ pub struct DbUpdateChallengeCustomization<'a> {
     pub small_width: Option<i16>,
     pub small_height: Option<i16>,
     pub logo_url: Option<Option<&'a str>>,
+    pub exclude_webgl_on_low_end: Option<bool>,
+    pub escalate_difficulty: Option<bool>,
+    pub accessibility_enabled: Option<bool>,
+    /// Replaces the whole theme.
+    pub theme: Option<DbTheme<'a>>,
+    /// Replaces the locale and every string override.
+    pub localization: Option<DbLocalization<'a>>,
+    /// Replaces the proof of work algorithm and its memory cost.
+    pub proof_of_work: Option<DbProofOfWork<'a>>,
+}
+
+#[derive(Debug)]
+pub struct DbProofOfWork<'a> {
+    pub algorithm: &'a str,
+    pub memory_kib: i32,
+}
+
+#[derive(Debug)]
+pub struct DbTheme<'a> {
+    pub mode: &'a str,
+    pub primary_color: Option<&'a str>,
+    pub background_color: Option<&'a str>,
+    pub text_color: Option<&'a str>,
+    pub corner_radius: Option<i16>,
+    pub font_family: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct DbLocalization<'a> {
+    pub locale: Option<&'a str>,
+    pub strings: &'a BTreeMap<String, String>,
 }
 
 pub async fn update_challenge_customization(
@@ -569,6 +1133,8 @@ pub async fn update_challenge_customization(
         None => (false, None),
         Some(value) => (true, value),
     };
+    let theme = update.theme.as_ref();
+    let localization = update.localization.as_ref();
 
     let res = sqlx::query!(
         "update challenge_customization set
@@ -576,14 +1142,42 @@ pub async fn update_challenge_customization(
             height = coalesce($2, height),
             small_width = coalesce($3, small_width),
             small_height = coalesce($4, small_height),
-            logo_url = case when $5 then $6 else logo_url end
-        where console_id = $7",
+            logo_url = case when $5 then $6 else logo_url end,
+            exclude_webgl_on_low_end = coalesce($7, exclude_webgl_on_low_end),
+            escalate_difficulty = coalesce($8, escalate_difficulty),
+            theme_mode = case when $9 then $10 else theme_mode end,
+            primary_color = case when $9 then $11 else primary_color end,
+            background_color = case when $9 then $12 else background_color end,
+            text_color = case when $9 then $13 else text_color end,
+            corner_radius = case when $9 then $14 else corner_radius end,
+            font_family = case when $9 then $15 else font_family end,
+            locale = case when $16 then $17 else locale end,
+            strings = case when $16 then $18 else strings end,
+            accessibility_enabled = coalesce($19, accessibility_enabled),
+            pow_algorithm = coalesce($20, pow_algorithm),
+            pow_memory_kib = coalesce($21, pow_memory_kib)
+        where console_id = $22",
         update.width,
         update.height,
         update.small_width,
         update.small_height,
         should_update_logo_url,
         logo_url_value,
+        update.exclude_webgl_on_low_end,
+        update.escalate_difficulty,
+        theme.is_some(),
+        theme.map(|t| t.mode),
+        theme.and_then(|t| t.primary_color),
+        theme.and_then(|t| t.background_color),
+        theme.and_then(|t| t.text_color),
+        theme.and_then(|t| t.corner_radius),
+        theme.and_then(|t| t.font_family),
+        localization.is_some(),
+        localization.and_then(|l| l.locale),
+        localization.map(|l| Json(l.strings)) as _,
+        update.accessibility_enabled,
+        update.proof_of_work.as_ref().map(|pow| pow.algorithm),
+        update.proof_of_work.as_ref().map(|pow| pow.memory_kib),
         console_id
     )
     .execute(exec)
@@ -594,3 +1188,153 @@ pub async fn update_challenge_customization(
         r => Ok(RowsAffected(r)),
     }
 }
+
+/// Customization overriding the console one for the site keys it's attached to, `None` fields
+/// fall back to the console customization.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DbCustomizationProfile {
+    pub id: Uuid,
+    pub label: String,
+    pub width: Option<i16>,
+    pub height: Option<i16>,
+    pub small_width: Option<i16>,
+    pub small_height: Option<i16>,
+    pub logo_url: Option<String>,
+}
+
+pub async fn fetch_customization_profiles(
+    exec: impl PgExecutor<'_> + Send,
+    console_id: &Uuid,
+) -> Result<Vec<DbCustomizationProfile>> {
+    sqlx::query_as!(
+        DbCustomizationProfile,
+        "select id, label, width, height, small_width, small_height, logo_url
+        from customization_profile where console_id = $1 order by created_at",
+        console_id
+    )
+    .fetch_all(exec)
+    .await
+    .map(Ok)?
+}
+
+pub async fn insert_customization_profile(
+    exec: impl PgExecutor<'_> + Send,
+    console_id: &Uuid,
+    profile: &DbCustomizationProfile,
+) -> Result<()> {
+    sqlx::query!(
+        "insert into customization_profile
+            (id, console_id, label, width, height, small_width, small_height, logo_url)
+        values ($1, $2, $3, $4, $5, $6, $7, $8)",
+        profile.id,
+        console_id,
+        profile.label,
+        profile.width,
+        profile.height,
+        profile.small_width,
+        profile.small_height,
+        profile.logo_url,
+    )
+    .execute(exec)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct DbUpdateCustomizationProfile<'a> {
+    pub label: Option<&'a str>,
+    pub width: Option<i16>,
+    pub height: Option<i16>,
+    pub small_width: Option<i16>,
+    pub small_height: Option<i16>,
+    pub logo_url: Option<Option<&'a str>>,
+}
+
+pub async fn update_customization_profile(
+    exec: impl PgExecutor<'_> + Send,
+    console_id: &Uuid,
+    profile_id: &Uuid,
+    update: &DbUpdateCustomizationProfile<'_>,
+) -> Result<RowsAffected> {
+    let (should_update_logo_url, logo_url_value) = match update.logo_url {
+        None => (false, None),
+        Some(value) => (true, value),
+    };
+
+    let res = sqlx::query!(
+        "update customization_profile set
+            label = coalesce($1, label),
+            width = coalesce($2, width),
+            height = coalesce($3, height),
+            small_width = coalesce($4, small_width),
+            small_height = coalesce($5, small_height),
+            logo_url = case when $6 then $7 else logo_url end
+        where id = $8 and console_id = $9",
+        update.label,
+        update.width,
+        update.height,
+        update.small_width,
+        update.small_height,
+        should_update_logo_url,
+        logo_url_value,
+        profile_id,
+        console_id,
+    )
+    .execute(exec)
+    .await?;
+    Ok(RowsAffected(res.rows_affected()))
+}
+
+pub async fn delete_customization_profile(
+    exec: impl PgExecutor<'_> + Send,
+    console_id: &Uuid,
+    profile_id: &Uuid,
+) -> Result<RowsAffected> {
+    let res = sqlx::query!(
+        "delete from customization_profile where id = $1 and console_id = $2",
+        profile_id,
+        console_id
+    )
+    .execute(exec)
+    .await?;
+    Ok(RowsAffected(res.rows_affected()))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DbConsoleChallenge {
+    pub challenge_url: String,
+    pub enabled: bool,
+    pub weight: Option<i16>,
+}
+
+pub async fn fetch_console_challenges(
+    exec: impl PgExecutor<'_> + Send,
+    console_id: &Uuid,
+) -> Result<Vec<DbConsoleChallenge>> {
+    sqlx::query_as!(
+        DbConsoleChallenge,
+        "select challenge_url, enabled, weight from console_challenge where console_id = $1 order by challenge_url",
+        console_id
+    )
+    .fetch_all(exec)
+    .await
+    .map(Ok)?
+}
+
+pub async fn upsert_console_challenge(
+    exec: impl PgExecutor<'_> + Send,
+    console_id: &Uuid,
+    upsert: &DbConsoleChallenge,
+) -> Result<()> {
+    sqlx::query!(
+        "insert into console_challenge (console_id, challenge_url, enabled, weight) values ($1, $2, $3, $4)
+        on conflict (console_id, challenge_url) do update set enabled = excluded.enabled, weight = excluded.weight",
+        console_id,
+        upsert.challenge_url,
+        upsert.enabled,
+        upsert.weight,
+    )
+    .execute(exec)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,302 @@
+//! Background health checks of challenges and health endpoints of the server. Each challenge url
+//! is fetched periodically, the status and latency are recorded and challenges failing repeatedly
+//! are no longer served.
+//!
+//! `/healthz` answers as long as the server is up, `/readyz` also checks the dependencies needed
+//! to serve requests and answers 503 until they are all available. Both are public, so readiness
+//! is only checked every few seconds and the errors of the checks are logged rather than served.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, anyhow, bail, ensure};
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+use jsonwebtoken::jwk::JwkSet;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    Connection, PgPool,
+    migrate::{Migrate, Migrator},
+};
+use tokio::{sync::Mutex, task::JoinSet, time::MissedTickBehavior};
+
+use crate::{
+    HTTP_CLIENT,
+    db::{self, DbChallengeHealth, DbHealthProbe},
+};
+
+/// Time given to each readiness check before failing it.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(3);
+/// Time a readiness report is served for before checking again.
+const READINESS_CACHE: Duration = Duration::from_secs(5);
+
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+/// Of the background checks, which only run on hosted servers: on AWS Lambda nothing runs between
+/// invocations, so challenges are never checked and served as healthy.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HealthCheckConfig {
+    /// Seconds between two rounds of checks.
+    #[serde(with = "crate::serde::as_string")]
+    pub interval_secs: u64,
+    /// Seconds to wait for a challenge before considering it down.
+    #[serde(with = "crate::serde::as_string")]
+    pub timeout_secs: u64,
+    /// Failed checks in a row before a challenge is excluded.
+    #[serde(with = "crate::serde::as_string")]
+    pub failure_threshold: u16,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self { interval_secs: 300, timeout_secs: 10, failure_threshold: 2 }
+    }
+}
+
+/// Spawns a task checking every challenge each [`HealthCheckConfig::interval_secs`].
+pub fn spawn_health_checker(
+    pool: PgPool,
+    config: HealthCheckConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = check_challenges(&pool, &config).await {
+                tracing::error!(error = ?err, "failed to check challenges health");
+            }
+            // escalations are only read for an hour, drop the stale ones along the way
+            if let Err(err) = db::delete_stale_escalations(&pool).await {
+                tracing::error!(error = ?err, "failed to delete stale escalations");
+            }
+        }
+    })
+}
+
+/// Checks every challenge concurrently.
+pub async fn check_challenges(pool: &PgPool, config: &HealthCheckConfig) -> db::Result<()> {
+    let mut checks = JoinSet::new();
+    for challenge in db::fetch_challenges(pool).await? {
+        let (pool, config) = (pool.clone(), config.clone());
+        checks.spawn(async move { check_challenge(&pool, &challenge.url, &config).await });
+    }
+    while let Some(check) = checks.join_next().await {
+        match check {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => tracing::error!(error = ?err, "failed to record challenge health"),
+            Err(err) => tracing::error!(error = ?err, "challenge health check panicked"),
+        }
+    }
+    Ok(())
+}
+
+/// Fetches a challenge url and records the outcome.
+pub async fn check_challenge(
+    pool: &PgPool,
+    url: &str,
+    config: &HealthCheckConfig,
+) -> db::Result<DbChallengeHealth> {
+    let probe = probe(url, Duration::from_secs(config.timeout_secs)).await;
+    if !probe.success {
+        tracing::warn!(?probe, "challenge health check failed");
+    }
+    db::record_challenge_health(pool, &probe, config.failure_threshold.into()).await
+}
+
+async fn probe(url: &str, timeout: Duration) -> DbHealthProbe {
+    let start = Instant::now();
+    let response = HTTP_CLIENT.get(url).timeout(timeout).send().await;
+    let latency_ms = i32::try_from(start.elapsed().as_millis()).ok();
+
+    match response {
+        Ok(response) => DbHealthProbe {
+            challenge_url: url.into(),
+            success: response.status().is_success(),
+            status: Some(response.status().as_u16() as i16),
+            latency_ms,
+            error: None,
+        },
+        Err(err) => DbHealthProbe {
+            challenge_url: url.into(),
+            success: false,
+            status: None,
+            latency_ms,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: Status,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub checks: BTreeMap<&'static str, CheckReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub status: Status,
+    pub latency_ms: u64,
+    /// Logged but not served, it may tell about the infrastructure.
+    #[serde(skip)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Readiness {
+    pool: PgPool,
+    auth_origin: String,
+    serve_dir: Option<PathBuf>,
+    /// Last report and when it was checked.
+    cache: Arc<Mutex<Option<(Instant, HealthReport)>>>,
+}
+
+/// Router serving `/healthz` and `/readyz`, the static files are only checked when `serve_dir` is
+/// given.
+pub fn router(pool: PgPool, auth_origin: String, serve_dir: Option<PathBuf>) -> Router {
+    Router::new()
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+        .with_state(Readiness { pool, auth_origin, serve_dir, cache: Default::default() })
+}
+
+async fn liveness() -> Json<HealthReport> {
+    Json(HealthReport { status: Status::Ok, checks: BTreeMap::new() })
+}
+
+async fn readiness(State(readiness): State<Readiness>) -> (StatusCode, Json<HealthReport>) {
+    let report = readiness.cached_check().await;
+    let status = match report.status {
+        Status::Ok => StatusCode::OK,
+        Status::Fail => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (status, Json(report))
+}
+
+impl Readiness {
+    /// Checks at most once every [`READINESS_CACHE`], requests in the meantime wait for the
+    /// checks in flight and get the same report.
+    async fn cached_check(&self) -> HealthReport {
+        let mut cache = self.cache.lock().await;
+        match &*cache {
+            Some((checked_at, report)) if checked_at.elapsed() < READINESS_CACHE => report.clone(),
+            _ => {
+                let report = self.check().await;
+                *cache = Some((Instant::now(), report.clone()));
+                report
+            }
+        }
+    }
+
+    /// Runs every check concurrently.
+    async fn check(&self) -> HealthReport {
+        let (database, migrations, jwks) = tokio::join!(
+            timed(check_database(&self.pool)),
+            timed(check_migrations(&self.pool)),
+            timed(check_jwks(&self.auth_origin)),
+        );
+        let mut checks = BTreeMap::from([
+            ("database", database),
+            ("migrations", migrations),
+            ("jwks", jwks),
+        ]);
+        if let Some(serve_dir) = &self.serve_dir {
+            checks.insert(
+                "serve_dir",
+                timed(async { check_serve_dir(serve_dir) }).await,
+            );
+        }
+
+        let mut status = Status::Ok;
+        for (name, check) in checks.iter().filter(|(_, c)| c.status == Status::Fail) {
+            tracing::warn!(check = name, error = ?check.error, "readiness check failed");
+            status = Status::Fail;
+        }
+        HealthReport { status, checks }
+    }
+}
+
+async fn timed(check: impl Future<Output = anyhow::Result<()>>) -> CheckReport {
+    let start = Instant::now();
+    let result = tokio::time::timeout(READINESS_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("timed out after {READINESS_TIMEOUT:?}")));
+    CheckReport {
+        status: if result.is_ok() {
+            Status::Ok
+        } else {
+            Status::Fail
+        },
+        latency_ms: start.elapsed().as_millis() as u64,
+        error: result.err().map(|err| format!("{err:#}")),
+    }
+}
+
+async fn check_database(pool: &PgPool) -> anyhow::Result<()> {
+    pool.acquire().await?.ping().await?;
+    Ok(())
+}
+
+/// Every migration embedded in the binary must be applied unchanged, migrations applied by a newer
+/// version of the server are fine.
+async fn check_migrations(pool: &PgPool) -> anyhow::Result<()> {
+    let mut conn = pool.acquire().await?;
+    if let Some(version) = conn.dirty_version().await? {
+        bail!("migration {version} is partially applied");
+    }
+    let applied: HashMap<_, _> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| (migration.version, migration.checksum))
+        .collect();
+
+    let mut pending = Vec::new();
+    for migration in MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+    {
+        match applied.get(&migration.version) {
+            None => pending.push(migration.version),
+            Some(checksum) if *checksum != migration.checksum => {
+                bail!(
+                    "migration {} was modified after being applied",
+                    migration.version
+                )
+            }
+            Some(_) => {}
+        }
+    }
+    ensure!(pending.is_empty(), "pending migrations {pending:?}");
+    Ok(())
+}
+
+/// The keys verifying console users' tokens, see [`crate::routes::middleware::require_auth`].
+async fn check_jwks(auth_origin: &str) -> anyhow::Result<()> {
+    let jwks: JwkSet = HTTP_CLIENT
+        .get(format!("{auth_origin}/.well-known/jwks.json"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    ensure!(!jwks.keys.is_empty(), "no keys in jwks");
+    Ok(())
+}
+
+fn check_serve_dir(serve_dir: &Path) -> anyhow::Result<()> {
+    std::fs::read_dir(serve_dir).with_context(|| format!("cannot read {}", serve_dir.display()))?;
+    Ok(())
+}
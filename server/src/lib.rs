@@ -1,8 +1,13 @@
 use std::sync::{Arc, LazyLock};
 
+use accessibility::Accessibility;
+use analysis::proof_of_work::PowLimiter;
 use axum::Router;
+use challenges::{AnsweredNonces, puzzle::Puzzles};
 use configuration::ApplicationConfig;
 use http_cache_reqwest::{CACacheManager, Cache, CacheMode, HttpCache, HttpCacheOptions};
+use logging::{JsonFields, JsonFormat, LogFormat, LoggingConfig, RedactedFields};
+use opentelemetry_sdk::trace::SdkTracer;
 use reqwest::Client;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use sqlx::PgPool;
@@ -17,12 +22,19 @@ mod aws_lambda {
 #[cfg(feature = "aws-lambda")]
 use aws_lambda::*;
 
+pub mod accessibility;
 pub mod analysis;
+pub mod challenges;
 pub mod configuration;
 pub mod db;
 pub mod encodings;
+pub mod health;
+pub mod logging;
+pub mod rate_limit;
 pub mod routes;
+pub mod selection;
 mod serde;
+pub mod telemetry;
 pub mod test_helpers;
 pub mod tokens;
 
@@ -52,10 +64,22 @@ pub static HTTP_CACHE_CLIENT: LazyLock<ClientWithMiddleware> = LazyLock::new(||
 pub struct AppState {
     pub pool: PgPool,
     pub auth_origin: String,
+    pub accessibility: Accessibility,
+    pub puzzles: Puzzles,
+    pub pow_limiter: PowLimiter,
+    /// Nonces of the puzzles and challenge instances answered.
+    pub answered: AnsweredNonces,
 }
 
 pub fn app(config: ApplicationConfig, pool: PgPool) -> Router {
-    let state = AppState { pool, auth_origin: config.auth_origin };
+    let state = AppState {
+        pool: pool.clone(),
+        auth_origin: config.auth_origin.clone(),
+        accessibility: config.accessibility.into(),
+        puzzles: config.puzzle.into(),
+        pow_limiter: config.proof_of_work.into(),
+        answered: AnsweredNonces::default(),
+    };
 
     let router = Router::new().nest("/api", api(state));
     #[cfg(not(feature = "aws-lambda"))]
@@ -69,9 +93,19 @@ pub fn app(config: ApplicationConfig, pool: PgPool) -> Router {
             .expect("serve dir not found");
         tracing::info!("Serving files from: {:?}", serve_dir);
 
-        router.fallback_service(ServeDir::new(serve_dir))
+        router
+            .merge(health::router(
+                pool,
+                config.auth_origin,
+                Some(serve_dir.clone()),
+            ))
+            .fallback_service(ServeDir::new(serve_dir))
     };
-    let router = router.layer(TraceLayer::new_for_http());
+    #[cfg(feature = "aws-lambda")]
+    let router = router.merge(health::router(pool, config.auth_origin, None));
+    let router = router
+        .layer(axum::middleware::from_fn(telemetry::track_requests))
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::request_span));
     #[cfg(feature = "aws-lambda")]
     let router = router.layer(MapRequestLayer::new(extractors::extract_lambda_source_ip));
 
@@ -89,12 +123,39 @@ fn api(state: AppState) -> Router {
 }
 
 pub fn init_tracing() {
+    init_tracing_with(&LoggingConfig::default(), None);
+}
+
+/// Also exports spans to `tracer` when given, see [`telemetry::otlp_tracer_provider`].
+pub fn init_tracing_with(config: &LoggingConfig, tracer: Option<SdkTracer>) {
+    let policy = Arc::new(config.redaction.clone());
+    let (pretty, json) = match config.format {
+        LogFormat::Pretty => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_test_writer()
+                    .fmt_fields(RedactedFields::new(policy)),
+            ),
+            None,
+        ),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_test_writer()
+                    .event_format(JsonFormat::new(Arc::clone(&policy)))
+                    .fmt_fields(JsonFields::new(policy)),
+            ),
+        ),
+    };
     let _ = tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| format!("{}=debug", env!("CARGO_CRATE_NAME")).into()),
         )
-        .with(tracing_subscriber::fmt::layer().with_test_writer())
+        .with(pretty)
+        .with(json)
+        .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
         .try_init();
 }
 
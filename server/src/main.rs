@@ -1,4 +1,4 @@
-use gotcha_server::{configuration::Config, db};
+use gotcha_server::{configuration::Config, db, health, telemetry};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -12,16 +12,28 @@ async fn main() -> anyhow::Result<()> {
 
 #[cfg(not(feature = "aws-lambda"))]
 async fn hosted_main() -> anyhow::Result<()> {
-    gotcha_server::init_tracing();
-    tracing::debug!("Running on hosted environment");
-
     let Config { application: app_conf, database: db_conf, .. } =
         gotcha_server::get_configuration()?;
+    let tracer_provider =
+        telemetry::otlp_tracer_provider(&app_conf.otlp, &app_conf.logging.redaction)?;
+    gotcha_server::init_tracing_with(
+        &app_conf.logging,
+        tracer_provider.as_ref().map(telemetry::tracer),
+    );
+    tracing::debug!("Running on hosted environment");
     tracing::info!(config = ?app_conf, "Application config");
     tracing::info!(config = ?db_conf, "Database config");
 
     let pool = db::connect_database(db_conf);
     _ = gotcha_server::db_dev_populate(&pool).await;
+    let _health_checker = health::spawn_health_checker(pool.clone(), app_conf.health_check.clone());
+
+    if app_conf.metrics.enabled {
+        let addr = format!("{}:{}", app_conf.metrics.host, app_conf.metrics.port);
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!("Serving metrics on {}", listener.local_addr()?);
+        tokio::spawn(axum::serve(listener, telemetry::router(pool.clone())).into_future());
+    }
 
     let addr = format!("{}:{}", app_conf.host, app_conf.port);
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -32,21 +44,50 @@ async fn hosted_main() -> anyhow::Result<()> {
         gotcha_server::app(app_conf, pool)
             .into_make_service_with_connect_info::<std::net::SocketAddr>(),
     )
+    .with_graceful_shutdown(shutdown_signal())
     .await
     .unwrap();
 
+    if let Some(provider) = tracer_provider {
+        provider.shutdown()?;
+    }
     Ok(())
 }
 
+/// Resolves on ctrl-c or SIGTERM.
+#[cfg(not(feature = "aws-lambda"))]
+async fn shutdown_signal() {
+    let ctrl_c = tokio::signal::ctrl_c();
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => _ = sigterm.recv().await,
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down");
+}
+
 #[cfg(feature = "aws-lambda")]
 async fn lambda_main() -> anyhow::Result<()> {
-    lambda_http::tracing::init_default_subscriber();
-    tracing::debug!("Running on AWS Lambda environment");
-
     let Config { application: app_conf, database: db_conf, .. } =
         gotcha_server::get_configuration()?;
+    gotcha_server::init_tracing_with(&app_conf.logging, None);
+    tracing::debug!("Running on AWS Lambda environment");
     tracing::info!(?app_conf, "Application config");
     tracing::info!(?db_conf, "Database config");
+    if app_conf.otlp.endpoint.is_some() {
+        tracing::warn!("Spans aren't exported over OTLP on AWS Lambda, ignoring `otlp.endpoint`");
+    }
+    tracing::info!("Challenge health checks don't run on AWS Lambda, every challenge is healthy");
 
     let pool = db::connect_database(db_conf);
 
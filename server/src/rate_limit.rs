@@ -0,0 +1,80 @@
+//! In memory rate limiting by client address, or by any key including it. Each server instance
+//! keeps its own counts.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Fixed window limiter, each key gets `max_requests` per window starting at its first request.
+/// Counts live in this process only: behind several instances, or on Lambda where each concurrent
+/// request may get its own, a key gets `max_requests` from each of them.
+#[derive(Debug)]
+pub struct RateLimiter<K = IpAddr> {
+    max_requests: u32,
+    window: Duration,
+    windows: Mutex<HashMap<K, Window>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    start: Instant,
+    requests: u32,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(max_requests: u32, window: Duration) -> Self {
+        Self { max_requests, window, windows: Mutex::default() }
+    }
+
+    /// Counts a request from `key`, `false` once it's over the limit of its current window.
+    pub fn check(&self, key: K) -> bool {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("rate limiter lock poisoned");
+        windows.retain(|_, window| now.duration_since(window.start) < self.window);
+        let window = windows
+            .entry(key)
+            .or_insert(Window { start: now, requests: 0 });
+        window.requests += 1;
+        window.requests <= self.max_requests
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    const ADDR: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn limits_requests_per_address() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+
+        assert!(limiter.check(ADDR));
+        assert!(limiter.check(ADDR));
+        assert!(!limiter.check(ADDR));
+        assert!(limiter.check(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+    }
+
+    #[test]
+    fn limits_requests_per_key() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+
+        assert!(limiter.check((ADDR, "a")));
+        assert!(!limiter.check((ADDR, "a")));
+        assert!(limiter.check((ADDR, "b")));
+    }
+
+    #[test]
+    fn expired_windows_start_over() {
+        let limiter = RateLimiter::new(1, Duration::ZERO);
+
+        assert!(limiter.check(ADDR));
+        assert!(limiter.check(ADDR));
+    }
+}
@@ -1,17 +1,21 @@
 use std::sync::Arc;
 
-use admin::{add_challenge, remove_challenge};
+use admin::{add_challenge, list_challenges, remove_challenge, update_challenge};
 use axum::{
     Router,
     routing::{delete, get, patch, post},
 };
 use challenge::{
-    get_challenge, get_proof_of_work_challenge, process_accessibility_challenge, process_challenge,
-    process_pre_analysis,
+    get_accessibility_challenge, get_audio_challenge, get_challenge, get_challenge_instance,
+    get_proof_of_work_challenge, get_puzzle_challenge, process_accessibility_challenge,
+    process_challenge, process_challenge_instance, process_invisible, process_pre_analysis,
+    process_puzzle_challenge,
 };
+use compat::{hcaptcha_site_verify, recaptcha_site_verify, turnstile_site_verify};
 use console::{
-    create_console, delete_console, gen_api_key, get_api_keys, get_consoles, revoke_api_key,
-    update_api_key, update_console,
+    create_console, create_customization_profile, delete_console, delete_customization_profile,
+    gen_api_key, get_api_keys, get_consoles, get_customization_profiles, revoke_api_key,
+    update_api_key, update_console, update_customization_profile,
 };
 use middleware::{
     block_bot_agent, require_admin, require_auth, validate_api_key, validate_console_id,
@@ -25,6 +29,7 @@ use crate::{
 
 pub mod admin;
 pub mod challenge;
+pub mod compat;
 pub mod console;
 mod errors;
 pub mod extractors;
@@ -36,12 +41,19 @@ pub fn challenge(state: &Arc<AppState>) -> Router {
     Router::new()
         .route("/", get(get_challenge))
         .route("/proof-of-work", get(get_proof_of_work_challenge))
+        .route("/accessibility", get(get_accessibility_challenge))
+        .route("/audio", get(get_audio_challenge))
+        .route("/puzzle", get(get_puzzle_challenge))
+        .route("/instance", get(get_challenge_instance))
         .route("/process", post(process_challenge))
         .route("/process-pre-analysis", post(process_pre_analysis))
         .route(
             "/process-accessibility",
             post(process_accessibility_challenge),
         )
+        .route("/process-puzzle", post(process_puzzle_challenge))
+        .route("/process-instance", post(process_challenge_instance))
+        .route("/process-invisible", post(process_invisible))
         .layer(axum::middleware::from_fn(block_bot_agent))
         .with_state(state)
 }
@@ -50,6 +62,9 @@ pub fn verification(state: &Arc<AppState>) -> Router {
     let state = Arc::clone(state);
     Router::new()
         .route("/siteverify", post(site_verify))
+        .route("/recaptcha/api/siteverify", post(recaptcha_site_verify))
+        .route("/hcaptcha/siteverify", post(hcaptcha_site_verify))
+        .route("/turnstile/v0/siteverify", post(turnstile_site_verify))
         .layer(axum::middleware::from_fn(block_bot_agent))
         .with_state(state)
 }
@@ -75,6 +90,12 @@ pub fn console(state: &Arc<AppState>) -> Router {
         .route("/", get(get_challenge_preferences))
         .route("/", patch(update_challenge_preferences));
 
+    let customization_profile = Router::new()
+        .route("/", get(get_customization_profiles))
+        .route("/", post(create_customization_profile))
+        .route("/{profile_id}", patch(update_customization_profile))
+        .route("/{profile_id}", delete(delete_customization_profile));
+
     Router::new()
         .route("/", get(get_consoles))
         .route("/", post(create_console))
@@ -85,6 +106,7 @@ pub fn console(state: &Arc<AppState>) -> Router {
                 .route("/", delete(delete_console))
                 .nest("/api-key", api_key)
                 .nest("/challenge-preferences", challenge_preferences)
+                .nest("/customization-profile", customization_profile)
                 .layer(axum::middleware::from_fn_with_state(
                     Arc::clone(&state),
                     validate_console_id,
@@ -100,7 +122,9 @@ pub fn console(state: &Arc<AppState>) -> Router {
 pub fn admin(state: &Arc<AppState>) -> Router {
     let state = Arc::clone(state);
     Router::new()
+        .route("/challenge", get(list_challenges))
         .route("/challenge", post(add_challenge))
+        .route("/challenge", patch(update_challenge))
         .route("/challenge", delete(remove_challenge))
         .layer(axum::middleware::from_fn_with_state(
             Arc::clone(&state),
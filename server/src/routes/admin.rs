@@ -1,19 +1,131 @@
 //! `/api/admin` routes.
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use axum::{Json, extract::State};
+use axum::{
+    Json,
+    extract::{Query, State},
+};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use tracing::{Level, instrument};
 use url::Url;
 
 use crate::{
     AppState,
-    db::{self, RowsAffected},
+    analysis::attempts::{DEFAULT_MAX_ATTEMPTS, MAX_ATTEMPTS, MAX_MOVES_PER_ATTEMPT},
+    challenges::ChallengeKind,
+    db::{self, DbChallenge, DbChallengeHealth, DbUpdateChallenge, RowsAffected},
+    encodings::{Base64, KEY_SIZE, Standard},
+    selection::MAX_DIFFICULTY,
+    serde::nested_option,
 };
 
 use super::errors::AdminError;
 
+/// Challenges listed when no limit is given.
+pub const DEFAULT_PAGE_LIMIT: u32 = 50;
+/// Most challenges listed at once.
+pub const MAX_PAGE_LIMIT: u32 = 200;
+
+/// Challenge as listed to admins.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChallengeInfo {
+    /// Public URL.
+    pub url: String,
+    pub label: Option<String>,
+    /// Default width.
+    pub width: u16,
+    /// Default height.
+    pub height: u16,
+    /// Default width on small screens.
+    pub small_width: u16,
+    /// Default height on small screens.
+    pub small_height: u16,
+    /// Default logo.
+    pub logo_url: Option<String>,
+    pub weight: u16,
+    pub difficulty: u8,
+    pub requires_webgl: bool,
+    /// Whether it may be served, regardless of its health.
+    pub enabled: bool,
+    pub max_attempts: u8,
+    pub moves_per_attempt: Option<u8>,
+    /// Whether it reports how its game was played, results without a trace can't pass then.
+    pub traced: bool,
+    pub kind: ChallengeKind,
+    /// Where remote challenges are called back.
+    pub verify_url: Option<String>,
+    /// Last health check, `None` if it was never checked.
+    pub health: Option<ChallengeHealth>,
+}
+
+/// Outcome of the last health check of a challenge.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChallengeHealth {
+    /// Whether the challenge is served.
+    pub healthy: bool,
+    /// HTTP status, `None` if the request failed.
+    pub status: Option<u16>,
+    pub latency_ms: Option<u32>,
+    pub error: Option<String>,
+    pub consecutive_failures: u16,
+    #[serde(with = "time::serde::rfc3339")]
+    pub checked_at: OffsetDateTime,
+}
+
+/// Expected params for the list challenges route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListParams {
+    #[serde(default)]
+    pub offset: u32,
+    /// Defaults to [`DEFAULT_PAGE_LIMIT`], at most [`MAX_PAGE_LIMIT`].
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// A page of challenges.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChallengePage {
+    pub challenges: Vec<ChallengeInfo>,
+    /// Number of challenges across all pages.
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// Lists challenges along with their health.
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn list_challenges(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<ChallengePage>, AdminError> {
+    let ListParams { offset, limit } = params;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+
+    let total = db::count_challenges(&state.pool).await?;
+    let mut health: HashMap<_, _> = db::fetch_challenge_health(&state.pool)
+        .await?
+        .into_iter()
+        .map(|health| (health.challenge_url.clone(), health))
+        .collect();
+    let challenges = db::fetch_challenges_page(&state.pool, offset.into(), limit.into())
+        .await?
+        .into_iter()
+        .map(|challenge| {
+            let health = health.remove(&challenge.url);
+            ChallengeInfo::new(challenge, health)
+        })
+        .collect();
+
+    Ok(Json(ChallengePage {
+        challenges,
+        total: total as u64,
+        offset,
+        limit,
+    }))
+}
+
 /// Expected payload for new challenge route.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AddChallenge {
@@ -23,32 +135,200 @@ pub struct AddChallenge {
     pub width: u16,
     /// Default height.
     pub height: u16,
+    /// Default width on small screens, defaults to `width`.
+    #[serde(default)]
+    pub small_width: Option<u16>,
+    /// Default height on small screens, defaults to `height`.
+    #[serde(default)]
+    pub small_height: Option<u16>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Default logo.
+    #[serde(default)]
+    pub logo_url: Option<String>,
+    /// Relative chance of being served, defaults to 1.
+    #[serde(default)]
+    pub weight: Option<u16>,
+    /// Difficulty from 1 to 5, defaults to 1.
+    #[serde(default)]
+    pub difficulty: Option<u8>,
+    /// Wether it's too heavy for low-end devices.
+    #[serde(default)]
+    pub requires_webgl: bool,
+    /// Attempts allowed from 1 to 10, defaults to 3.
+    #[serde(default)]
+    pub max_attempts: Option<u8>,
+    /// Moves per attempt from 1 to 100, defaults to what the challenge does.
+    #[serde(default)]
+    pub moves_per_attempt: Option<u8>,
+    /// Whether it reports how its game was played, like widgets built on `gotcha-plugin`.
+    #[serde(default)]
+    pub traced: bool,
+    /// How answers are verified, defaults to `widget`.
+    #[serde(default)]
+    pub kind: ChallengeKind,
+    /// Where `remote` challenges are called back, required for them.
+    #[serde(default)]
+    pub verify_url: Option<String>,
 }
 
-/// Adds a new challenge to the database.
+/// Response payload of new challenge route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddedChallenge {
+    /// Secret signing the callbacks of `remote` challenges, only handed out here.
+    pub verify_secret: Option<String>,
+}
+
+/// Adds a new challenge to the database. Remote challenges get a secret to check their callbacks
+/// come from the server, see [`crate::challenges::remote`].
 #[instrument(skip(state), err(Debug, level = Level::ERROR))]
 pub async fn add_challenge(
     State(state): State<Arc<AppState>>,
     Json(challenge): Json<AddChallenge>,
-) -> Result<(), AdminError> {
-    let AddChallenge { url, width, height } = challenge;
-    let _ = Url::parse(&url).map_err(|_| AdminError::InvalidUrl)?;
+) -> Result<Json<AddedChallenge>, AdminError> {
+    let AddChallenge {
+        url,
+        width,
+        height,
+        small_width,
+        small_height,
+        label,
+        logo_url,
+        weight,
+        difficulty,
+        requires_webgl,
+        max_attempts,
+        moves_per_attempt,
+        traced,
+        kind,
+        verify_url,
+    } = challenge;
+    validate_url(&url)?;
+    if let Some(logo_url) = &logo_url {
+        validate_url(logo_url)?;
+    }
+    let (verify_url, verify_secret) = match kind {
+        ChallengeKind::Remote => {
+            let verify_url = verify_url.ok_or(AdminError::InvalidUrl)?;
+            validate_url(&verify_url)?;
+            let secret = Base64::<Standard>::random::<KEY_SIZE>().as_str().to_owned();
+            (Some(verify_url), Some(secret))
+        }
+        _ => (None, None),
+    };
 
     db::insert_challenge(
         &state.pool,
-        &db::DbChallenge {
+        &DbChallenge {
             url,
-            width: width as i16,
-            height: height as i16,
-            small_width: width as i16,
-            small_height: height as i16,
-            logo_url: None,
-            label: None,
+            label,
+            width: validate_dimension(width)?,
+            height: validate_dimension(height)?,
+            small_width: validate_dimension(small_width.unwrap_or(width))?,
+            small_height: validate_dimension(small_height.unwrap_or(height))?,
+            logo_url,
+            weight: validate_weight(weight.unwrap_or(1))?,
+            difficulty: validate_difficulty(difficulty.unwrap_or(1))?,
+            requires_webgl,
+            enabled: true,
+            max_attempts: validate_max_attempts(max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS))?,
+            moves_per_attempt: moves_per_attempt
+                .map(validate_moves_per_attempt)
+                .transpose()?,
+            traced,
+            healthy: true,
+            kind: kind.as_str().into(),
+            verify_url,
+            verify_secret: verify_secret.clone(),
         },
     )
     .await?;
 
-    Ok(())
+    Ok(Json(AddedChallenge { verify_secret }))
+}
+
+/// Expected payload for update challenge route, absent fields are left unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateChallenge {
+    /// Public URL of the challenge to update.
+    pub url: String,
+    #[serde(
+        default,
+        deserialize_with = "nested_option::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub label: Option<Option<String>>,
+    #[serde(default)]
+    pub width: Option<u16>,
+    #[serde(default)]
+    pub height: Option<u16>,
+    #[serde(default)]
+    pub small_width: Option<u16>,
+    #[serde(default)]
+    pub small_height: Option<u16>,
+    #[serde(
+        default,
+        deserialize_with = "nested_option::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub logo_url: Option<Option<String>>,
+    #[serde(default)]
+    pub weight: Option<u16>,
+    #[serde(default)]
+    pub difficulty: Option<u8>,
+    #[serde(default)]
+    pub requires_webgl: Option<bool>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub max_attempts: Option<u8>,
+    #[serde(
+        default,
+        deserialize_with = "nested_option::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub moves_per_attempt: Option<Option<u8>>,
+    #[serde(default)]
+    pub traced: Option<bool>,
+}
+
+/// Updates a challenge.
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn update_challenge(
+    State(state): State<Arc<AppState>>,
+    Json(update): Json<UpdateChallenge>,
+) -> Result<(), AdminError> {
+    if let Some(Some(logo_url)) = &update.logo_url {
+        validate_url(logo_url)?;
+    }
+
+    let RowsAffected(updated) = db::update_challenge(
+        &state.pool,
+        &update.url,
+        &DbUpdateChallenge {
+            label: update.label.as_ref().map(|l| l.as_deref()),
+            width: update.width.map(validate_dimension).transpose()?,
+            height: update.height.map(validate_dimension).transpose()?,
+            small_width: update.small_width.map(validate_dimension).transpose()?,
+            small_height: update.small_height.map(validate_dimension).transpose()?,
+            logo_url: update.logo_url.as_ref().map(|l| l.as_deref()),
+            weight: update.weight.map(validate_weight).transpose()?,
+            difficulty: update.difficulty.map(validate_difficulty).transpose()?,
+            requires_webgl: update.requires_webgl,
+            enabled: update.enabled,
+            max_attempts: update.max_attempts.map(validate_max_attempts).transpose()?,
+            moves_per_attempt: update
+                .moves_per_attempt
+                .map(|moves| moves.map(validate_moves_per_attempt).transpose())
+                .transpose()?,
+            traced: update.traced,
+        },
+    )
+    .await?;
+    match updated {
+        0 => Err(AdminError::NotFound(update.url)),
+        _ => Ok(()),
+    }
 }
 
 /// Expected payload for delete challenge route.
@@ -70,3 +350,78 @@ pub async fn remove_challenge(
         _ => Ok(()),
     }
 }
+
+fn validate_url(url: &str) -> Result<(), AdminError> {
+    Url::parse(url)
+        .map(|_| ())
+        .map_err(|_| AdminError::InvalidUrl)
+}
+
+fn validate_dimension(value: u16) -> Result<i16, AdminError> {
+    match i16::try_from(value) {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(AdminError::InvalidDimensions),
+    }
+}
+
+fn validate_weight(weight: u16) -> Result<i16, AdminError> {
+    i16::try_from(weight).map_err(|_| AdminError::InvalidSelection)
+}
+
+fn validate_difficulty(difficulty: u8) -> Result<i16, AdminError> {
+    match (1..=MAX_DIFFICULTY).contains(&difficulty) {
+        true => Ok(difficulty.into()),
+        false => Err(AdminError::InvalidSelection),
+    }
+}
+
+fn validate_max_attempts(max_attempts: u8) -> Result<i16, AdminError> {
+    match (1..=MAX_ATTEMPTS).contains(&max_attempts) {
+        true => Ok(max_attempts.into()),
+        false => Err(AdminError::InvalidAttempts),
+    }
+}
+
+fn validate_moves_per_attempt(moves: u8) -> Result<i16, AdminError> {
+    match (1..=MAX_MOVES_PER_ATTEMPT).contains(&moves) {
+        true => Ok(moves.into()),
+        false => Err(AdminError::InvalidAttempts),
+    }
+}
+
+impl ChallengeInfo {
+    fn new(challenge: DbChallenge, health: Option<DbChallengeHealth>) -> Self {
+        Self {
+            url: challenge.url,
+            label: challenge.label,
+            width: challenge.width as u16,
+            height: challenge.height as u16,
+            small_width: challenge.small_width as u16,
+            small_height: challenge.small_height as u16,
+            logo_url: challenge.logo_url,
+            weight: challenge.weight as u16,
+            difficulty: challenge.difficulty as u8,
+            requires_webgl: challenge.requires_webgl,
+            enabled: challenge.enabled,
+            max_attempts: challenge.max_attempts as u8,
+            moves_per_attempt: challenge.moves_per_attempt.map(|moves| moves as u8),
+            traced: challenge.traced,
+            kind: ChallengeKind::from(challenge.kind.as_str()),
+            verify_url: challenge.verify_url,
+            health: health.map(ChallengeHealth::from),
+        }
+    }
+}
+
+impl From<DbChallengeHealth> for ChallengeHealth {
+    fn from(health: DbChallengeHealth) -> Self {
+        Self {
+            healthy: health.healthy,
+            status: health.status.map(|status| status as u16),
+            latency_ms: health.latency_ms.map(|latency| latency as u32),
+            error: health.error,
+            consecutive_failures: health.consecutive_failures as u16,
+            checked_at: health.checked_at,
+        }
+    }
+}
@@ -7,7 +7,7 @@ use axum::{
     Json,
     extract::{ConnectInfo, Query, State},
 };
-use rand::Rng;
+use base64::prelude::*;
 use serde::{Deserialize, Serialize};
 use tracing::{Level, Span, instrument};
 use url::{Host, Url};
@@ -15,23 +15,40 @@ use url::{Host, Url};
 use super::errors::ChallengeError;
 use crate::{
     AppState,
+    accessibility::{Question, answer_hash},
     analysis::{
         self,
+        attempts::DEFAULT_MAX_ATTEMPTS,
+        game::GameResult,
         interaction::{Interaction, Score},
-        proof_of_work::PowChallenge,
+        proof_of_work::{PowAlgorithm, PowChallenge, PowSettings},
     },
-    db::{self, DbChallenge},
+    challenges::{self, ChallengeKind, Seed, audio::AudioClip, puzzle::Puzzle},
+    db::{self, DbChallenge, DbChallengeCustomization, DbEscalation},
     encodings::{Base64, UrlSafe},
+    selection::{self, DeviceClass, SelectionContext, SelectionPolicy},
+    telemetry,
     tokens::{
-        self, pow_challenge,
-        response::{self, ResponseClaims},
+        self,
+        accessibility::{QUESTION_TIMEOUT, QuestionClaims},
+        game::{GAME_TIMEOUT, GameClaims},
+        instance::{INSTANCE_TIMEOUT, InstanceClaims},
+        pow_challenge,
+        puzzle::{PUZZLE_TIMEOUT, PuzzleClaims},
+        response::{self, ChallengeType, ResponseClaims},
     },
 };
 
+pub use gotcha_types::console::{Localization, Theme, ThemeMode};
+
 /// Expected params for get challenge route.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChallengeParams {
     pub site_key: Option<Base64<UrlSafe>>,
+    /// Class of the device that will render the challenge, a hint that never lowers the
+    /// difficulty.
+    #[serde(default)]
+    pub device: DeviceClass,
 }
 
 /// Response payload of get challenge route.
@@ -49,23 +66,99 @@ pub struct GetChallenge {
     pub small_height: u16,
     /// Custom logo URL.
     pub logo_url: Option<String>,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default)]
+    pub localization: Localization,
+    #[serde(default)]
+    pub attempts: AttemptLimits,
+    /// How answers are verified, challenges other than widgets fetch an instance to render.
+    #[serde(default)]
+    pub kind: ChallengeKind,
+    /// Seed game challenges generate their randomness from, issued when the site key is valid.
+    #[serde(default)]
+    pub game: Option<GameSeed>,
 }
 
-/// Fetches challenges a responds with one of them randomly and its customization.
-/// If `site_key` param is absent it responds with the defaults.
+/// Seed of a game challenge along with the token its result is reported with, see
+/// [`ChallengeResults::seed_token`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSeed {
+    pub seed: u64,
+    pub token: String,
+}
+
+/// How many tries the user gets, enforced by the challenge itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttemptLimits {
+    /// Attempts allowed before the challenge is failed.
+    pub max_attempts: u8,
+    /// Moves per attempt, e.g. balls to throw. `None` keeps the challenge default.
+    #[serde(default)]
+    pub moves_per_attempt: Option<u8>,
+}
+
+/// Fetches challenges and responds with one of them and its customization, chosen according to
+/// the console's selection policy and the failures recorded for the address. If `site_key` param
+/// is absent it responds with the defaults.
 #[instrument(skip(state), err(Debug, level = Level::ERROR))]
 pub async fn get_challenge(
     Query(query): Query<ChallengeParams>,
     State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
 ) -> Result<Json<GetChallenge>, ChallengeError> {
-    let challenges = match query.site_key {
-        Some(site_key) => db::fetch_challenges_with_customization(&state.pool, &site_key).await,
-        None => db::fetch_challenges(&state.pool).await,
+    let (challenges, customization) = match &query.site_key {
+        Some(site_key) => {
+            let challenges = db::fetch_challenges_with_customization(&state.pool, site_key)
+                .await
+                .context("failed to fetch challenges")?;
+            let customization =
+                db::fetch_challenge_customization_by_site_key(&state.pool, site_key)
+                    .await
+                    .context("failed to fetch challenge customization")?
+                    .unwrap_or_default();
+            (challenges, customization)
+        }
+        None => (
+            db::fetch_challenges(&state.pool)
+                .await
+                .context("failed to fetch challenges")?,
+            DbChallengeCustomization::default(),
+        ),
+    };
+    let escalation = match &query.site_key {
+        Some(site_key) => db::fetch_escalation(&state.pool, site_key, addr.ip())
+            .await
+            .context("failed to fetch escalation")?
+            .unwrap_or_default(),
+        None => DbEscalation::default(),
+    };
+    let policy = SelectionPolicy::from(&customization);
+    let ctx = SelectionContext { device: query.device, ..SelectionContext::from(&escalation) };
+    let challenge = selection::choose_challenge(challenges, &policy, &ctx, &mut rand::rng())
+        .ok_or(ChallengeError::NoMatchingChallenge)?;
+    let enc_key = match &query.site_key {
+        Some(site_key) => db::fetch_api_key_by_site_key(&state.pool, site_key)
+            .await
+            .context("failed to fetch api key by site key while getting challenge")?
+            .map(|api_key| api_key.encoding_key),
+        None => None,
+    };
+    let mut challenge = GetChallenge {
+        theme: Theme::from(&customization),
+        localization: Localization::from(customization),
+        ..challenge.try_into()?
+    };
+    if let Some(enc_key) = enc_key {
+        // the seed round trips through the page, it must fit a javascript number
+        let seed = rand::random::<u64>() >> 11;
+        let claims = GameClaims { challenge: challenge.url.to_string(), seed, addr: addr.ip() };
+        let token = tokens::game::encode(claims, &enc_key).context("failed encoding jwt game")?;
+        challenge.game = Some(GameSeed { seed, token });
     }
-    .context("failed to fetch challenges")?;
-    let challenge = choose_challenge(challenges).ok_or(ChallengeError::NoMatchingChallenge)?;
+    telemetry::challenge_served("widget");
 
-    Ok(Json(challenge.try_into()?))
+    Ok(Json(challenge))
 }
 
 /// Expected params for get proof of work route.
@@ -82,8 +175,8 @@ pub struct PowResponse {
     pub token: String,
 }
 
-/// Constructs a unique proof of work challenge and encodes it in a JWT.
-/// Difficulty hardcoded to 3. Future work may include customizing it in an admin page.
+/// Constructs a unique proof of work challenge and encodes it in a JWT. The algorithm is the
+/// console's choice, difficulties are fixed per algorithm.
 #[instrument(skip(state), err(Debug, level = Level::ERROR))]
 pub async fn get_proof_of_work_challenge(
     Query(query): Query<PowParams>,
@@ -94,9 +187,16 @@ pub async fn get_proof_of_work_challenge(
         .context("failed to fetch api key by site key while getting proof of work")?
         .ok_or(ChallengeError::InvalidKey)?
         .encoding_key;
+    let settings = db::fetch_challenge_customization_by_site_key(&state.pool, &query.site_key)
+        .await
+        .context("failed to fetch challenge customization while getting proof of work")?
+        .map(|customization| PowSettings::from(&customization))
+        .unwrap_or_default();
+    let algorithm = PowAlgorithm::from(&settings);
+    telemetry::pow_issued(&algorithm);
 
     Ok(Json(PowResponse {
-        token: pow_challenge::encode(PowChallenge::random(3), &enc_key)
+        token: pow_challenge::encode(PowChallenge::for_algorithm(algorithm), &enc_key)
             .context("failed encoding jwt response")?,
     }))
 }
@@ -116,6 +216,17 @@ pub struct ChallengeResults {
     /// The list of interactions performed while solving the challenge.
     #[serde(default)]
     pub interactions: Vec<Interaction>,
+    /// Attempts used, the fewer the better the score. Absent scores as the last attempt allowed.
+    #[serde(default)]
+    pub attempts: Option<u8>,
+    /// How the game was played, sent by game challenges. Absent scores
+    /// [`analysis::game::UNTRACED_SCORE`] at best for challenges that are traced.
+    #[serde(default)]
+    pub game: Option<GameResult>,
+    /// Token of the [`GameSeed`] served with the challenge, the game is only scored when played
+    /// on its seed.
+    #[serde(default)]
+    pub seed_token: Option<String>,
 }
 
 /// Response payload of processing the challenge route.
@@ -133,7 +244,10 @@ pub struct ChallengeResponse {
         %site_key = results.site_key,
         ?hostname = results.hostname,
         %challenge = results.challenge,
+        ?attempts = results.attempts,
+        seed = results.game.as_ref().and_then(|game| game.seed),
         interaction_score,
+        game_score,
     )
 )]
 pub async fn process_challenge(
@@ -144,22 +258,60 @@ pub async fn process_challenge(
     // TODO: potentially heavy CPU operation - offload to rayon
     let Score(score) = analysis::interaction::interaction_analysis(&results.interactions);
     Span::current().record("interaction_score", score);
+    let challenge = db::fetch_challenge(&state.pool, results.challenge.as_str())
+        .await
+        .context("failed to fetch challenge while processing challenge")?;
     let score = match results.success {
         true => {
             tracing::warn!("interaction analysis disabled");
-            1.
+            let max_attempts = challenge
+                .as_ref()
+                .map_or(DEFAULT_MAX_ATTEMPTS, |c| c.max_attempts as u8);
+            // unreported attempts are taken as the last one allowed, never scoring better
+            let attempts = results.attempts.unwrap_or(max_attempts);
+            let Score(score) = analysis::attempts::attempts_analysis(attempts, max_attempts);
+            score
         }
         false => 0.,
     };
+    let enc_key = db::fetch_api_key_by_site_key(&state.pool, &results.site_key)
+        .await
+        .context("failed to fetch api key by site key while processing challenge")?
+        .ok_or(ChallengeError::InvalidKey)?
+        .encoding_key;
+    let score = match &results.game {
+        Some(game) => {
+            let issued_seed = results
+                .seed_token
+                .as_deref()
+                .and_then(|token| tokens::game::decode(token, &enc_key).ok())
+                .filter(|claims| {
+                    claims.addr == addr.ip()
+                        && claims.challenge == results.challenge.as_str()
+                        && game.seed == Some(claims.seed)
+                        && state.answered.answer(claims.seed, GAME_TIMEOUT)
+                })
+                .map(|claims| claims.seed);
+            let Score(game_score) = analysis::game::game_analysis(game, issued_seed);
+            Span::current().record("game_score", game_score);
+            score.min(game_score)
+        }
+        // challenges that don't trace their games are scored on their attempts alone
+        None if challenge.is_some_and(|c| c.traced) => score.min(analysis::game::UNTRACED_SCORE),
+        None => score,
+    };
+    record_attempt(&state, &results.site_key, addr, score).await?;
 
     Ok(Json(ChallengeResponse {
         token: response::encode(
-            ResponseClaims { score, addr: addr.ip(), host: results.hostname },
-            &db::fetch_api_key_by_site_key(&state.pool, &results.site_key)
-                .await
-                .context("failed to fetch api key by site key while processing challenge")?
-                .ok_or(ChallengeError::InvalidKey)?
-                .encoding_key,
+            ResponseClaims {
+                score,
+                addr: addr.ip(),
+                host: results.hostname,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
+            &enc_key,
         )
         .context("failed encoding jwt response")?,
     }))
@@ -184,19 +336,52 @@ pub struct PreAnalysisRequest {
 pub struct ProofOfWork {
     /// JWT with proof of work challenge.
     pub challenge: String,
-    /// Solution for the proof of work challenge.
-    pub solution: u32,
+    /// Solutions for each sub-puzzle of the proof of work challenge. Also read from the single
+    /// `solution` sent before sub-puzzles.
+    #[serde(
+        alias = "solution",
+        deserialize_with = "crate::serde::single_or_sequence::deserialize"
+    )]
+    pub solutions: Vec<u64>,
 }
 
 impl ProofOfWork {
-    pub fn verify(&self, dec_key: &Base64) -> Result<bool, jsonwebtoken::errors::Error> {
+    /// Only hashes solutions shaped like the challenge. Memory-hard ones cost the server the memory
+    /// of a hash per sub-puzzle, so they're rate limited by address and site key and verified on
+    /// the blocking pool, they take milliseconds.
+    pub async fn verify(
+        &self,
+        state: &AppState,
+        addr: SocketAddr,
+        site_key: &Base64<UrlSafe>,
+        dec_key: &Base64,
+    ) -> Result<bool, ChallengeError> {
         let pow_challenge = tokens::pow_challenge::decode(&self.challenge, dec_key.as_str())
             .inspect_err(|_| {
                 Span::current().record("pow_jwt", &self.challenge);
+                telemetry::pow_verified(None, false);
             })?;
         Span::current().record("pow_decoded", tracing::field::debug(&pow_challenge));
 
-        Ok(pow_challenge.verify_solution(self.solution))
+        let algorithm = pow_challenge.algorithm;
+        if !pow_challenge.is_well_formed(&self.solutions) {
+            telemetry::pow_verified(Some(&algorithm), false);
+            return Ok(false);
+        }
+        let valid = if algorithm.is_memory_hard() {
+            if !state.pow_limiter.check((addr.ip(), site_key.clone())) {
+                return Err(ChallengeError::TooManyRequests);
+            }
+            let solutions = self.solutions.clone();
+            // a panic isn't a wrong answer, it's answered as an internal error and logged
+            tokio::task::spawn_blocking(move || pow_challenge.verify_solution(&solutions))
+                .await
+                .context("failed to verify memory-hard proof of work")?
+        } else {
+            pow_challenge.verify_solution(&self.solutions)
+        };
+        telemetry::pow_verified(Some(&algorithm), valid);
+        Ok(valid)
     }
 }
 
@@ -207,8 +392,12 @@ impl ProofOfWork {
 pub enum PreAnalysisResponse {
     /// Success case, the pre analysis is confident it's a trusted computer.
     Success { response: ChallengeResponse },
-    /// Failure case, the user will then be required to solve a captcha.
-    Failure,
+    /// Failure case, the user will then be required to solve a captcha at least as hard as
+    /// `min_difficulty`.
+    Failure {
+        #[serde(default)]
+        min_difficulty: u8,
+    },
 }
 
 /// The pre analysis is an ergonomic mechanism to allow trusted users to skip captcha challenges.
@@ -224,7 +413,7 @@ pub enum PreAnalysisResponse {
         ?hostname = request.hostname,
         pow_jwt,
         pow_decoded,
-        pow_solution = request.proof_of_work.solution,
+        pow_solutions = ?request.proof_of_work.solutions,
         interaction_score,
     )
 )]
@@ -240,7 +429,10 @@ pub async fn process_pre_analysis(
         .ok_or(ChallengeError::InvalidKey)?
         .encoding_key;
 
-    let verified = request.proof_of_work.verify(&crypt_key)?;
+    let verified = request
+        .proof_of_work
+        .verify(&state, addr, &request.site_key, &crypt_key)
+        .await?;
     if !verified {
         return Err(ChallengeError::FailedProofOfWork);
     }
@@ -250,15 +442,25 @@ pub async fn process_pre_analysis(
     Span::current().record("interaction_score", score);
 
     let response = match score {
-        _ => PreAnalysisResponse::Failure,
+        _ => {
+            PreAnalysisResponse::Failure { min_difficulty: selection::difficulty_for_score(score) }
+        }
         // For now, assume pre-analysis always fails
         #[expect(unreachable_patterns)]
-        0f32..0.5 => PreAnalysisResponse::Failure,
+        0f32..0.5 => {
+            PreAnalysisResponse::Failure { min_difficulty: selection::difficulty_for_score(score) }
+        }
         #[expect(unreachable_patterns)]
         0.5..=1. => PreAnalysisResponse::Success {
             response: ChallengeResponse {
                 token: response::encode(
-                    ResponseClaims { score, addr: addr.ip(), host: request.hostname },
+                    ResponseClaims {
+                        score,
+                        addr: addr.ip(),
+                        host: request.hostname,
+                        challenge_type: ChallengeType::PreAnalysis,
+                        action: None,
+                    },
                     &crypt_key,
                 )
                 .context("failed encoding jwt response")?,
@@ -272,9 +474,145 @@ pub async fn process_pre_analysis(
         }
     };
 
+    if let PreAnalysisResponse::Failure { min_difficulty } = response {
+        db::record_min_difficulty(
+            &state.pool,
+            &request.site_key,
+            addr.ip(),
+            min_difficulty.into(),
+        )
+        .await
+        .context("failed to record pre analysis difficulty")?;
+    }
+
     Ok(Json(response))
 }
 
+/// Escalates the difficulty of the challenges served to `addr` after an attempt scoring under
+/// [`selection::PASSING_SCORE`].
+async fn record_attempt(
+    state: &AppState,
+    site_key: &Base64<UrlSafe>,
+    addr: SocketAddr,
+    score: f32,
+) -> Result<(), ChallengeError> {
+    if score < selection::PASSING_SCORE {
+        db::record_failed_attempt(&state.pool, site_key, addr.ip())
+            .await
+            .context("failed to record failed attempt")?;
+    }
+    Ok(())
+}
+
+/// Expected params for get accessibility challenge route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessibilityParams {
+    /// Public site key encoded in base64 url safe alphabet.
+    pub site_key: Base64<UrlSafe>,
+}
+
+/// Response payload of get accessibility challenge route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessibilityChallenge {
+    /// Question to show or read out to the user.
+    pub question: String,
+    /// JWT to send back along with the answer.
+    pub token: String,
+}
+
+/// Generates a question for users who can't solve the interactive challenges, if the console
+/// offers the accessibility alternative. Requests are rate limited per address.
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn get_accessibility_challenge(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<AccessibilityParams>,
+) -> Result<Json<AccessibilityChallenge>, ChallengeError> {
+    let enc_key = authorize_accessibility(&state, addr, &query.site_key).await?;
+
+    let question = Question::random(&mut rand::rng());
+    telemetry::challenge_served("accessibility");
+
+    Ok(Json(AccessibilityChallenge {
+        question: question.prompt,
+        token: sign_answer(&question.answer, addr, &enc_key)?,
+    }))
+}
+
+/// Response payload of get audio challenge route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AudioChallenge {
+    /// WAV clip of the digits to type, encoded in base64.
+    pub audio: String,
+    /// JWT to send back along with the answer.
+    pub token: String,
+}
+
+/// Generates a clip of spoken digits for users who can't see the interactive challenges, answered
+/// through the accessibility process route like text questions. Shares their rate limit.
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn get_audio_challenge(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<AccessibilityParams>,
+) -> Result<Json<AudioChallenge>, ChallengeError> {
+    let enc_key = authorize_accessibility(&state, addr, &query.site_key).await?;
+
+    // Mixing a clip takes a few milliseconds of CPU, keep it off the async runtime.
+    let clip = tokio::task::spawn_blocking(|| AudioClip::random(&mut rand::rng()))
+        .await
+        .context("mixing audio clip")?;
+    telemetry::challenge_served("audio");
+
+    Ok(Json(AudioChallenge {
+        audio: BASE64_STANDARD.encode(clip.wav),
+        token: sign_answer(&clip.digits, addr, &enc_key)?,
+    }))
+}
+
+/// Counts the request against the address rate limit and checks the site key offers the
+/// accessibility alternative, returns its encoding key.
+async fn authorize_accessibility(
+    state: &AppState,
+    addr: SocketAddr,
+    site_key: &Base64<UrlSafe>,
+) -> Result<Base64, ChallengeError> {
+    if !state.accessibility.limiter.check(addr.ip()) {
+        return Err(ChallengeError::TooManyRequests);
+    }
+    let enc_key = db::fetch_api_key_by_site_key(&state.pool, site_key)
+        .await
+        .context("failed to fetch api key by site key while getting accessibility challenge")?
+        .ok_or(ChallengeError::InvalidKey)?
+        .encoding_key;
+    ensure_accessibility_enabled(state, site_key).await?;
+    Ok(enc_key)
+}
+
+fn sign_answer(answer: &str, addr: SocketAddr, enc_key: &Base64) -> Result<String, ChallengeError> {
+    let nonce = rand::random();
+    let claims = QuestionClaims {
+        nonce,
+        addr: addr.ip(),
+        answer_hash: answer_hash(answer, nonce, enc_key),
+    };
+    Ok(tokens::accessibility::encode(claims, enc_key).context("failed encoding jwt question")?)
+}
+
+async fn ensure_accessibility_enabled(
+    state: &AppState,
+    site_key: &Base64<UrlSafe>,
+) -> Result<(), ChallengeError> {
+    let enabled = db::fetch_challenge_customization_by_site_key(&state.pool, site_key)
+        .await
+        .context("failed to fetch challenge customization while checking accessibility")?
+        .is_none_or(|c| c.accessibility_enabled);
+    match enabled {
+        true => Ok(()),
+        false => Err(ChallengeError::AccessibilityDisabled),
+    }
+}
+
 /// Expected payload for acessibility route.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccessibilityRequest {
@@ -285,9 +623,15 @@ pub struct AccessibilityRequest {
     pub hostname: Host,
     /// Proof of work computed by the client.
     pub proof_of_work: ProofOfWork,
+    /// JWT of the question answered.
+    pub question: String,
+    /// Answer typed by the user.
+    pub answer: String,
 }
 
-/// Alternative process for accessibility users. At the moment, just checks proof of work.
+/// Alternative process for accessibility users. Checks the proof of work and the answer to the
+/// question, a right answer gets a token with the configured accessibility score, lower than the
+/// interactive challenges as it gives weaker assurance. Requests are rate limited per address.
 /// TODO: check fingerprint.
 #[instrument(skip(state, request), ret(Debug, level = Level::INFO), err(Debug, level = Level::ERROR),
     fields(
@@ -296,7 +640,7 @@ pub struct AccessibilityRequest {
         ?hostname = request.hostname,
         pow_jwt,
         pow_decoded,
-        solution = request.proof_of_work.solution,
+        solutions = ?request.proof_of_work.solutions,
     )
 )]
 pub async fn process_accessibility_challenge(
@@ -304,20 +648,46 @@ pub async fn process_accessibility_challenge(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(request): Json<AccessibilityRequest>,
 ) -> Result<Json<PreAnalysisResponse>, ChallengeError> {
+    if !state.accessibility.limiter.check(addr.ip()) {
+        return Err(ChallengeError::TooManyRequests);
+    }
     // TODO: look at cookies and other fingerprints
     let crypt_key = db::fetch_api_key_by_site_key(&state.pool, &request.site_key)
         .await
         .context("failed to fetch api key by api secret while processing accessility challenge")?
         .ok_or(ChallengeError::InvalidKey)?
         .encoding_key;
+    ensure_accessibility_enabled(&state, &request.site_key).await?;
 
-    let verified = request.proof_of_work.verify(&crypt_key)?;
+    let question = tokens::accessibility::decode(&request.question, &crypt_key)
+        .map_err(|_| ChallengeError::InvalidQuestion)?;
+    if question.addr != addr.ip() {
+        return Err(ChallengeError::InvalidQuestion);
+    }
+    let verified = request
+        .proof_of_work
+        .verify(&state, addr, &request.site_key, &crypt_key)
+        .await?;
     if !verified {
         return Err(ChallengeError::FailedProofOfWork);
     }
 
+    if !state.answered.answer(question.nonce, QUESTION_TIMEOUT) {
+        return Err(ChallengeError::InvalidQuestion);
+    }
+    if answer_hash(&request.answer, question.nonce, &crypt_key) != question.answer_hash {
+        record_attempt(&state, &request.site_key, addr, 0.).await?;
+        return Ok(Json(PreAnalysisResponse::Failure { min_difficulty: 0 }));
+    }
+
     let token = response::encode(
-        ResponseClaims { score: 1.0, addr: addr.ip(), host: request.hostname },
+        ResponseClaims {
+            score: state.accessibility.score,
+            addr: addr.ip(),
+            host: request.hostname,
+            challenge_type: ChallengeType::Accessibility,
+            action: None,
+        },
         &crypt_key,
     )?;
 
@@ -326,12 +696,372 @@ pub async fn process_accessibility_challenge(
     }))
 }
 
-fn choose_challenge(mut challenges: Vec<DbChallenge>) -> Option<DbChallenge> {
-    match &challenges[..] {
-        [] => None,
-        _ => Some(challenges.swap_remove(rand::rng().random_range(0..challenges.len()))),
-        // _ => Some(challenges.swap_remove(0)),
+/// Expected params for get puzzle challenge route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PuzzleParams {
+    /// Public site key encoded in base64 url safe alphabet.
+    pub site_key: Base64<UrlSafe>,
+}
+
+/// Response payload of get puzzle challenge route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PuzzleChallenge {
+    /// PNG of the picture with the hole, encoded in base64.
+    pub background: String,
+    /// PNG of the piece to slide into the hole, encoded in base64.
+    pub piece: String,
+    /// Top of the piece over the picture, it only slides horizontally. The decoy hole is on the
+    /// same row.
+    pub piece_y: u32,
+    /// JWT to send back along with the answer.
+    pub token: String,
+}
+
+/// Renders a slider puzzle, the fallback for when the interactive challenges can't load. Rendering
+/// takes CPU, so it's rate limited by address like the accessibility questions.
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn get_puzzle_challenge(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<PuzzleParams>,
+) -> Result<Json<PuzzleChallenge>, ChallengeError> {
+    if !state.puzzles.limiter.check(addr.ip()) {
+        return Err(ChallengeError::TooManyRequests);
     }
+    let enc_key = db::fetch_api_key_by_site_key(&state.pool, &query.site_key)
+        .await
+        .context("failed to fetch api key by site key while getting puzzle challenge")?
+        .ok_or(ChallengeError::InvalidKey)?
+        .encoding_key;
+
+    let nonce = rand::random();
+    let puzzle = Puzzle::render(Seed::derive(nonce, &enc_key)).await?;
+    let token = tokens::puzzle::encode(PuzzleClaims { nonce, addr: addr.ip() }, &enc_key)
+        .context("failed encoding jwt puzzle")?;
+    telemetry::challenge_served("puzzle");
+
+    Ok(Json(PuzzleChallenge {
+        background: BASE64_STANDARD.encode(puzzle.background),
+        piece: BASE64_STANDARD.encode(puzzle.piece),
+        piece_y: puzzle.piece_y,
+        token,
+    }))
+}
+
+/// Expected payload for puzzle route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PuzzleRequest {
+    /// Public site key encoded in base64 url safe alphabet.
+    pub site_key: Base64<UrlSafe>,
+    /// The host name of the URL where it was solved.
+    #[serde(with = "crate::serde::host_as_str")]
+    pub hostname: Host,
+    /// Proof of work computed by the client.
+    pub proof_of_work: ProofOfWork,
+    /// JWT of the puzzle solved.
+    pub puzzle: String,
+    /// Left of the piece where the user dropped it.
+    pub answer_x: u32,
+}
+
+/// Checks the proof of work and where the piece was dropped. Each puzzle gets a single answer, a
+/// right one gets a token with the configured puzzle score.
+#[instrument(skip(state, request), ret(Debug, level = Level::INFO), err(Debug, level = Level::ERROR),
+    fields(
+        ?addr,
+        ?site_key = request.site_key,
+        ?hostname = request.hostname,
+        pow_jwt,
+        pow_decoded,
+        solutions = ?request.proof_of_work.solutions,
+    )
+)]
+pub async fn process_puzzle_challenge(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<PuzzleRequest>,
+) -> Result<Json<PreAnalysisResponse>, ChallengeError> {
+    let crypt_key = db::fetch_api_key_by_site_key(&state.pool, &request.site_key)
+        .await
+        .context("failed to fetch api key by site key while processing puzzle challenge")?
+        .ok_or(ChallengeError::InvalidKey)?
+        .encoding_key;
+
+    let claims = tokens::puzzle::decode(&request.puzzle, &crypt_key)
+        .map_err(|_| ChallengeError::InvalidPuzzle)?;
+    if claims.addr != addr.ip() {
+        return Err(ChallengeError::InvalidPuzzle);
+    }
+    let verified = request
+        .proof_of_work
+        .verify(&state, addr, &request.site_key, &crypt_key)
+        .await?;
+    if !verified {
+        return Err(ChallengeError::FailedProofOfWork);
+    }
+
+    if !state.answered.answer(claims.nonce, PUZZLE_TIMEOUT) {
+        return Err(ChallengeError::InvalidPuzzle);
+    }
+    let puzzle = Puzzle::render(Seed::derive(claims.nonce, &crypt_key)).await?;
+    if !state.puzzles.is_solved(&puzzle, request.answer_x) {
+        record_attempt(&state, &request.site_key, addr, 0.).await?;
+        return Ok(Json(PreAnalysisResponse::Failure { min_difficulty: 0 }));
+    }
+
+    let token = response::encode(
+        ResponseClaims {
+            score: state.puzzles.score,
+            addr: addr.ip(),
+            host: request.hostname,
+            challenge_type: ChallengeType::Puzzle,
+            action: None,
+        },
+        &crypt_key,
+    )?;
+
+    Ok(Json(PreAnalysisResponse::Success {
+        response: ChallengeResponse { token },
+    }))
+}
+
+/// Expected params for get challenge instance route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstanceParams {
+    /// Public site key encoded in base64 url safe alphabet.
+    pub site_key: Base64<UrlSafe>,
+    /// URL of the challenge to issue an instance of.
+    pub challenge: String,
+}
+
+/// Response payload of get challenge instance route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChallengeInstance {
+    /// What the challenge renders the instance from.
+    pub data: serde_json::Value,
+    /// JWT to send back along with the answer.
+    pub token: String,
+}
+
+/// Issues an instance of a challenge verified by the server, see [`challenges`]. Puzzles count
+/// against the same rate limit as the puzzle route.
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn get_challenge_instance(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<InstanceParams>,
+) -> Result<Json<ChallengeInstance>, ChallengeError> {
+    let enc_key = db::fetch_api_key_by_site_key(&state.pool, &query.site_key)
+        .await
+        .context("failed to fetch api key by site key while getting challenge instance")?
+        .ok_or(ChallengeError::InvalidKey)?
+        .encoding_key;
+    let (kind, challenge) = registered_challenge(&state, &query.challenge).await?;
+    if kind == ChallengeKind::Puzzle && !state.puzzles.limiter.check(addr.ip()) {
+        return Err(ChallengeError::TooManyRequests);
+    }
+
+    let nonce = rand::random();
+    let instance = challenge
+        .issue(Seed::derive(nonce, &enc_key))
+        .await
+        .context("failed to issue challenge instance")?;
+    let claims = InstanceClaims { challenge: query.challenge, nonce, addr: addr.ip() };
+    let token =
+        tokens::instance::encode(claims, &enc_key).context("failed encoding jwt instance")?;
+    telemetry::challenge_served("instance");
+
+    Ok(Json(ChallengeInstance { data: instance.data, token }))
+}
+
+/// Expected payload for process challenge instance route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstanceRequest {
+    /// Public site key encoded in base64 url safe alphabet.
+    pub site_key: Base64<UrlSafe>,
+    /// The host name of the URL where it was solved.
+    #[serde(with = "crate::serde::host_as_str")]
+    pub hostname: Host,
+    /// Proof of work computed by the client.
+    pub proof_of_work: ProofOfWork,
+    /// JWT of the instance answered.
+    pub instance: String,
+    /// Answer in whatever shape the challenge expects.
+    pub answer: serde_json::Value,
+}
+
+/// Checks the proof of work and has the challenge score the answer to the instance, issued again
+/// from its seed. Each instance gets a single answer, one scoring above 0 gets a token with its
+/// score.
+#[instrument(skip(state, request), ret(Debug, level = Level::INFO), err(Debug, level = Level::ERROR),
+    fields(
+        ?addr,
+        ?site_key = request.site_key,
+        ?hostname = request.hostname,
+        pow_jwt,
+        pow_decoded,
+        solutions = ?request.proof_of_work.solutions,
+    )
+)]
+pub async fn process_challenge_instance(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<InstanceRequest>,
+) -> Result<Json<PreAnalysisResponse>, ChallengeError> {
+    let crypt_key = db::fetch_api_key_by_site_key(&state.pool, &request.site_key)
+        .await
+        .context("failed to fetch api key by site key while processing challenge instance")?
+        .ok_or(ChallengeError::InvalidKey)?
+        .encoding_key;
+
+    let claims = tokens::instance::decode(&request.instance, &crypt_key)
+        .map_err(|_| ChallengeError::InvalidInstance)?;
+    if claims.addr != addr.ip() {
+        return Err(ChallengeError::InvalidInstance);
+    }
+    let verified = request
+        .proof_of_work
+        .verify(&state, addr, &request.site_key, &crypt_key)
+        .await?;
+    if !verified {
+        return Err(ChallengeError::FailedProofOfWork);
+    }
+
+    if !state.answered.answer(claims.nonce, INSTANCE_TIMEOUT) {
+        return Err(ChallengeError::InvalidInstance);
+    }
+    let (kind, challenge) = registered_challenge(&state, &claims.challenge).await?;
+    let instance = challenge
+        .issue(Seed::derive(claims.nonce, &crypt_key))
+        .await
+        .context("failed to issue challenge instance again")?;
+    let Score(score) = challenge
+        .verify(&instance, &request.answer)
+        .await
+        .context("failed to verify challenge answer")?;
+    record_attempt(&state, &request.site_key, addr, score).await?;
+    if score <= 0. {
+        return Ok(Json(PreAnalysisResponse::Failure { min_difficulty: 0 }));
+    }
+
+    let token = response::encode(
+        ResponseClaims {
+            score,
+            addr: addr.ip(),
+            host: request.hostname,
+            challenge_type: kind.challenge_type(),
+            action: None,
+        },
+        &crypt_key,
+    )?;
+
+    Ok(Json(PreAnalysisResponse::Success {
+        response: ChallengeResponse { token },
+    }))
+}
+
+/// The enabled challenge at `url` and its kind, if it's verified by the server.
+async fn registered_challenge(
+    state: &AppState,
+    url: &str,
+) -> Result<(ChallengeKind, Box<dyn challenges::Challenge>), ChallengeError> {
+    let challenge = db::fetch_challenge(&state.pool, url)
+        .await
+        .context("failed to fetch challenge")?
+        .filter(|challenge| challenge.enabled)
+        .ok_or(ChallengeError::NoMatchingChallenge)?;
+    let kind = ChallengeKind::from(challenge.kind.as_str());
+    let registered = challenges::registered(&challenge, &state.puzzles)?
+        .ok_or(ChallengeError::NoMatchingChallenge)?;
+    Ok((kind, registered))
+}
+
+/// Expected payload for invisible route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvisibleRequest {
+    /// Public site key encoded in base64 url safe alphabet.
+    pub site_key: Base64<UrlSafe>,
+    /// The host name of the URL where it was executed.
+    #[serde(with = "crate::serde::host_as_str")]
+    pub hostname: Host,
+    /// Name of the action being protected (e.g. `login`), echoed back on site verification.
+    #[serde(default)]
+    pub action: Option<String>,
+    /// The list of interactions performed on the page before executing.
+    #[serde(default)]
+    pub interactions: Vec<Interaction>,
+    /// Proof of work computed by the client.
+    pub proof_of_work: ProofOfWork,
+}
+
+/// Invisible mode never prompts the user with a challenge. It checks the proof of work, analyses
+/// the interactions collected in the background and responds with a token carrying the raw score,
+/// leaving the decision of what is an acceptable score to the site backend.
+#[instrument(skip(state, request), ret(Debug, level = Level::INFO), err(Debug, level = Level::ERROR),
+    fields(
+        ?addr,
+        %site_key = request.site_key,
+        ?hostname = request.hostname,
+        ?action = request.action,
+        pow_jwt,
+        pow_decoded,
+        pow_solutions = ?request.proof_of_work.solutions,
+        interaction_score,
+    )
+)]
+pub async fn process_invisible(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<InvisibleRequest>,
+) -> Result<Json<ChallengeResponse>, ChallengeError> {
+    let api_key = db::fetch_api_key_by_site_key(&state.pool, &request.site_key)
+        .await
+        .context("failed to fetch api key by site key while processing invisible")?
+        .ok_or(ChallengeError::InvalidKey)?;
+    if !api_key.invisible {
+        return Err(ChallengeError::InvisibleModeDisabled);
+    }
+    if request
+        .action
+        .as_deref()
+        .is_some_and(|a| !is_valid_action(a))
+    {
+        return Err(ChallengeError::InvalidAction);
+    }
+
+    let verified = request
+        .proof_of_work
+        .verify(&state, addr, &request.site_key, &api_key.encoding_key)
+        .await?;
+    if !verified {
+        return Err(ChallengeError::FailedProofOfWork);
+    }
+
+    // TODO: potentially heavy CPU operation - offload to rayon
+    let Score(score) = analysis::interaction::interaction_analysis(&request.interactions);
+    Span::current().record("interaction_score", score);
+
+    Ok(Json(ChallengeResponse {
+        token: response::encode(
+            ResponseClaims {
+                score,
+                addr: addr.ip(),
+                host: request.hostname,
+                challenge_type: ChallengeType::Invisible,
+                action: request.action,
+            },
+            &api_key.encoding_key,
+        )
+        .context("failed encoding jwt response")?,
+    }))
+}
+
+/// Same rules as reCAPTCHA: alphanumeric characters, slashes and underscores, up to 100 characters.
+fn is_valid_action(action: &str) -> bool {
+    (1..=100).contains(&action.len())
+        && action
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '/' || c == '_')
 }
 
 impl TryFrom<DbChallenge> for GetChallenge {
@@ -348,6 +1078,39 @@ impl TryFrom<DbChallenge> for GetChallenge {
             small_width: db_challenge.small_width as u16,
             small_height: db_challenge.small_height as u16,
             logo_url: db_challenge.logo_url,
+            theme: Theme::default(),
+            localization: Localization::default(),
+            attempts: AttemptLimits {
+                max_attempts: db_challenge.max_attempts as u8,
+                moves_per_attempt: db_challenge.moves_per_attempt.map(|moves| moves as u8),
+            },
+            kind: ChallengeKind::from(db_challenge.kind.as_str()),
+            game: None,
         })
     }
 }
+
+impl Default for AttemptLimits {
+    fn default() -> Self {
+        Self { max_attempts: DEFAULT_MAX_ATTEMPTS, moves_per_attempt: None }
+    }
+}
+
+impl From<&DbChallengeCustomization> for Theme {
+    fn from(c: &DbChallengeCustomization) -> Self {
+        Theme {
+            mode: c.theme_mode.as_str().into(),
+            primary_color: c.primary_color.clone(),
+            background_color: c.background_color.clone(),
+            text_color: c.text_color.clone(),
+            corner_radius: c.corner_radius.map(|r| r as u8),
+            font_family: c.font_family.clone(),
+        }
+    }
+}
+
+impl From<DbChallengeCustomization> for Localization {
+    fn from(c: DbChallengeCustomization) -> Self {
+        Localization { locale: c.locale, strings: c.strings.0 }
+    }
+}
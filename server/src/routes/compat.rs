@@ -0,0 +1,319 @@
+//! Site verification routes compatible with other captcha vendors.
+//!
+//! Each route accepts the vendor's request shape, runs [`site_verify`] and answers with the
+//! vendor's response schema and error codes, so migrating only requires changing the verify URL.
+
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{Level, instrument};
+
+use crate::AppState;
+
+use super::{
+    errors::VerificationError,
+    verification::{ErrorCodes, VerificationParams, VerificationResponse, site_verify},
+};
+
+/// Response schema of reCAPTCHA's `/recaptcha/api/siteverify`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecaptchaResponse {
+    pub success: bool,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub challenge_ts: Option<OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(rename = "error-codes", default, skip_serializing_if = "Vec::is_empty")]
+    pub error_codes: Vec<RecaptchaErrorCode>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecaptchaErrorCode {
+    MissingInputSecret,
+    InvalidInputSecret,
+    MissingInputResponse,
+    InvalidInputResponse,
+    BadRequest,
+    TimeoutOrDuplicate,
+}
+
+/// Response schema of hCaptcha's `/siteverify`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HCaptchaResponse {
+    pub success: bool,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub challenge_ts: Option<OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(rename = "error-codes", default, skip_serializing_if = "Vec::is_empty")]
+    pub error_codes: Vec<HCaptchaErrorCode>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HCaptchaErrorCode {
+    MissingInputSecret,
+    InvalidInputSecret,
+    MissingInputResponse,
+    InvalidInputResponse,
+    ExpiredInputResponse,
+    BadRequest,
+    SitekeySecretMismatch,
+}
+
+/// Response schema of Cloudflare Turnstile's `/turnstile/v0/siteverify`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TurnstileResponse {
+    pub success: bool,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub challenge_ts: Option<OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Turnstile always reports error codes, empty on success.
+    #[serde(rename = "error-codes")]
+    pub error_codes: Vec<TurnstileErrorCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdata: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TurnstileErrorCode {
+    MissingInputSecret,
+    InvalidInputSecret,
+    MissingInputResponse,
+    InvalidInputResponse,
+    BadRequest,
+    TimeoutOrDuplicate,
+    InternalError,
+}
+
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn recaptcha_site_verify(
+    state: State<Arc<AppState>>,
+    params: Result<VerificationParams, VerificationError>,
+) -> Result<Json<RecaptchaResponse>, VerificationError> {
+    compat_verify(state, params).await
+}
+
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn hcaptcha_site_verify(
+    state: State<Arc<AppState>>,
+    params: Result<VerificationParams, VerificationError>,
+) -> Result<Json<HCaptchaResponse>, VerificationError> {
+    compat_verify(state, params).await
+}
+
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn turnstile_site_verify(
+    state: State<Arc<AppState>>,
+    params: Result<VerificationParams, VerificationError>,
+) -> Result<Json<TurnstileResponse>, VerificationError> {
+    compat_verify(state, params)
+        .await
+        .or_else(turnstile_internal_error)
+}
+
+/// Turnstile has an error code for unexpected failures, answered instead of our own error shape.
+fn turnstile_internal_error(
+    err: VerificationError,
+) -> Result<Json<TurnstileResponse>, VerificationError> {
+    match err {
+        VerificationError::Unexpected(err) => {
+            tracing::error!(error = ?err, "turnstile verification failed");
+            Ok(Json(TurnstileResponse {
+                success: false,
+                challenge_ts: None,
+                hostname: None,
+                error_codes: vec![TurnstileErrorCode::InternalError],
+                action: None,
+                cdata: None,
+                messages: vec![],
+            }))
+        }
+        other => Err(other),
+    }
+}
+
+/// Runs [`site_verify`] and maps its outcome, failures included, into a vendor response.
+async fn compat_verify<R: From<VerificationResponse>>(
+    state: State<Arc<AppState>>,
+    params: Result<VerificationParams, VerificationError>,
+) -> Result<Json<R>, VerificationError> {
+    let verification = match params {
+        Ok(params) => site_verify(state, params).await.map(|Json(v)| v),
+        Err(err) => Err(err),
+    };
+    match verification {
+        Ok(verification) | Err(VerificationError::UserError(verification)) => {
+            Ok(Json(verification.into()))
+        }
+        Err(VerificationError::BadRequest(_) | VerificationError::BadJsonRequest(_)) => Ok(Json(
+            VerificationResponse::failure(vec![ErrorCodes::BadRequest]).into(),
+        )),
+        Err(err) => Err(err),
+    }
+}
+
+/// Timestamp and hostname are only meaningful when the token could be decoded.
+fn token_details(verification: &VerificationResponse) -> (Option<OffsetDateTime>, Option<String>) {
+    match &verification.hostname {
+        Some(host) => (Some(verification.challenge_ts), Some(host.to_string())),
+        None => (None, None),
+    }
+}
+
+impl From<VerificationResponse> for RecaptchaResponse {
+    fn from(verification: VerificationResponse) -> Self {
+        let (challenge_ts, hostname) = token_details(&verification);
+        Self {
+            success: verification.success,
+            challenge_ts,
+            hostname,
+            score: verification.score,
+            action: verification.action,
+            error_codes: verification
+                .error_codes
+                .into_iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<VerificationResponse> for HCaptchaResponse {
+    fn from(verification: VerificationResponse) -> Self {
+        let (challenge_ts, hostname) = token_details(&verification);
+        Self {
+            success: verification.success,
+            challenge_ts,
+            hostname,
+            score: verification.score,
+            error_codes: verification
+                .error_codes
+                .into_iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<VerificationResponse> for TurnstileResponse {
+    fn from(verification: VerificationResponse) -> Self {
+        let (challenge_ts, hostname) = token_details(&verification);
+        // turnstile reports an empty action and cdata for any decoded token
+        let decoded = hostname.is_some();
+        Self {
+            success: verification.success,
+            challenge_ts,
+            hostname,
+            error_codes: verification
+                .error_codes
+                .into_iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+            action: decoded.then(|| verification.action.unwrap_or_default()),
+            cdata: decoded.then(String::new),
+            messages: vec![],
+        }
+    }
+}
+
+impl From<ErrorCodes> for RecaptchaErrorCode {
+    fn from(code: ErrorCodes) -> Self {
+        match code {
+            ErrorCodes::MissingInputSecret => Self::MissingInputSecret,
+            ErrorCodes::InvalidInputSecret | ErrorCodes::SitekeySecretMismatch => {
+                Self::InvalidInputSecret
+            }
+            ErrorCodes::MissingInputResponse => Self::MissingInputResponse,
+            ErrorCodes::InvalidInputResponse | ErrorCodes::RemoteipMismatch => {
+                Self::InvalidInputResponse
+            }
+            ErrorCodes::BadRequest => Self::BadRequest,
+            ErrorCodes::TimeoutOrDuplicate => Self::TimeoutOrDuplicate,
+        }
+    }
+}
+
+impl From<ErrorCodes> for HCaptchaErrorCode {
+    fn from(code: ErrorCodes) -> Self {
+        match code {
+            ErrorCodes::MissingInputSecret => Self::MissingInputSecret,
+            ErrorCodes::InvalidInputSecret => Self::InvalidInputSecret,
+            ErrorCodes::MissingInputResponse => Self::MissingInputResponse,
+            ErrorCodes::InvalidInputResponse | ErrorCodes::RemoteipMismatch => {
+                Self::InvalidInputResponse
+            }
+            ErrorCodes::BadRequest => Self::BadRequest,
+            ErrorCodes::TimeoutOrDuplicate => Self::ExpiredInputResponse,
+            ErrorCodes::SitekeySecretMismatch => Self::SitekeySecretMismatch,
+        }
+    }
+}
+
+impl From<ErrorCodes> for TurnstileErrorCode {
+    fn from(code: ErrorCodes) -> Self {
+        match code {
+            ErrorCodes::MissingInputSecret => Self::MissingInputSecret,
+            ErrorCodes::InvalidInputSecret | ErrorCodes::SitekeySecretMismatch => {
+                Self::InvalidInputSecret
+            }
+            ErrorCodes::MissingInputResponse => Self::MissingInputResponse,
+            ErrorCodes::InvalidInputResponse | ErrorCodes::RemoteipMismatch => {
+                Self::InvalidInputResponse
+            }
+            ErrorCodes::BadRequest => Self::BadRequest,
+            ErrorCodes::TimeoutOrDuplicate => Self::TimeoutOrDuplicate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn turnstile_reports_internal_errors_in_its_schema() {
+        let Ok(Json(response)) =
+            turnstile_internal_error(VerificationError::Unexpected(anyhow::anyhow!("db down")))
+        else {
+            panic!("expected a turnstile response");
+        };
+
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({ "success": false, "error-codes": ["internal-error"], "messages": [] })
+        );
+    }
+}
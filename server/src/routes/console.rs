@@ -9,23 +9,28 @@ use serde::{Deserialize, Serialize};
 use tracing::{Level, instrument};
 use uuid::Uuid;
 
-use super::{errors::ConsoleError, extractors::User};
+use super::{
+    challenge::{Localization, Theme},
+    errors::ConsoleError,
+    extractors::User,
+};
 use crate::{
     AppState,
+    analysis::proof_of_work::{MAX_SCRYPT_MEMORY_KIB, MIN_SCRYPT_MEMORY_KIB, PowSettings},
     db::{
-        self, DbApiKey, DbChallengeCustomization, DbConsole, DbUpdateApiKey,
-        DbUpdateChallengeCustomization, DbUpdateConsole, RowsAffected,
+        self, DbApiKey, DbChallengeCustomization, DbConsole, DbConsoleChallenge,
+        DbCustomizationProfile, DbLocalization, DbProofOfWork, DbTheme, DbUpdateApiKey,
+        DbUpdateChallengeCustomization, DbUpdateConsole, DbUpdateCustomizationProfile,
+        RowsAffected,
     },
     encodings::{Base64, KEY_SIZE, Standard, UrlSafe},
-    serde::nested_option,
 };
 
-/// Response payload of retrieving a console.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ConsoleResponse {
-    pub id: Uuid,
-    pub label: Option<String>,
-}
+pub use gotcha_types::console::{
+    ApiKeyResponse, ChallengePreferences, ChallengeSelection, ConsoleResponse,
+    CreateConsoleRequest, CreateCustomizationProfile, CustomizationProfile, UpdateApiKeyRequest,
+    UpdateChallengePreferences, UpdateConsoleRequest, UpdateCustomizationProfile,
+};
 
 /// Gets all consoles associated with user given by the token in the "Authorization" header.
 #[instrument(skip_all, ret(Debug, level = Level::INFO), err(Debug, level = Level::ERROR))]
@@ -42,13 +47,6 @@ pub async fn get_consoles(
     Ok(Json(consoles))
 }
 
-/// Expected payload for create console route.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateConsoleRequest {
-    /// Label.
-    pub label: String,
-}
-
 /// Creates a console associated to the user given by the token in the "Authorization" header
 /// with the label provided in the payload.
 #[instrument(skip(state, user_id), ret(level = Level::INFO))]
@@ -69,13 +67,6 @@ pub async fn create_console(
     Ok(Json(ConsoleResponse { id, label: Some(request.label) }))
 }
 
-/// Expected payload for update console route.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateConsoleRequest {
-    // Label to be updated. `None` means "don't change".
-    pub label: Option<String>,
-}
-
 /// Updates console by id given in the path with the non-`None` fields in the payload.
 #[instrument(skip(state), err(Debug, level = Level::ERROR))]
 pub async fn update_console(
@@ -106,17 +97,6 @@ pub async fn delete_console(
     }
 }
 
-/// Response payload of retrieving an api key.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ApiKeyResponse {
-    /// Public site key encoded in base64 url safe alphabet.
-    pub site_key: Base64<UrlSafe>,
-    /// Secret site key encoded in base64 standard alphabet.
-    pub secret: Base64,
-    /// Label. Can be absent.
-    pub label: Option<String>,
-}
-
 /// Gets api keys for a console id given in the path.
 #[instrument(skip(state), ret(level = Level::INFO), err(Debug, level = Level::ERROR))]
 pub async fn get_api_keys(
@@ -153,12 +133,14 @@ pub async fn gen_api_key(
             Err(err) => return Err(err),
         };
     };
-    Ok(Json(ApiKeyResponse { site_key, secret, label: None }))
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateApiKeyRequest {
-    pub label: Option<String>,
+    Ok(Json(ApiKeyResponse {
+        site_key,
+        secret,
+        label: None,
+        invisible: false,
+        score_threshold: 0.5,
+        customization_profile_id: None,
+    }))
 }
 
 #[instrument(skip(state), err(Debug, level = Level::ERROR))]
@@ -167,7 +149,20 @@ pub async fn update_api_key(
     Path((console_id, site_key)): Path<(Uuid, String)>,
     Json(request): Json<UpdateApiKeyRequest>,
 ) -> Result<(), ConsoleError> {
-    let update = DbUpdateApiKey { label: request.label.as_deref() };
+    if request
+        .score_threshold
+        .is_some_and(|t| !(0.0..=1.0).contains(&t))
+    {
+        return Err(ConsoleError::InvalidInput {
+            what: "score_threshold out of range [0:1]".into(),
+        });
+    }
+    let update = DbUpdateApiKey {
+        label: request.label.as_deref(),
+        invisible: request.invisible,
+        score_threshold: request.score_threshold,
+        customization_profile_id: request.customization_profile_id,
+    };
     match db::update_api_key(&state.pool, &site_key, &console_id, update)
         .await
         .with_context(|| {
@@ -202,51 +197,34 @@ pub async fn revoke_api_key(
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-pub struct ChallengePreferences {
-    pub width: u16,
-    pub height: u16,
-    pub small_width: u16,
-    pub small_height: u16,
-    pub logo_url: Option<String>,
-}
-
-impl Default for ChallengePreferences {
-    fn default() -> Self {
-        Self {
-            width: 360,
-            height: 500,
-            small_width: 360,
-            small_height: 500,
-            logo_url: None,
-        }
-    }
-}
-
 #[instrument(skip(state), ret(Debug, level = Level::DEBUG), err(Debug, level = Level::ERROR))]
 pub async fn get_challenge_preferences(
     State(state): State<Arc<AppState>>,
     Path(console_id): Path<Uuid>,
 ) -> Result<Json<ChallengePreferences>, ConsoleError> {
-    let challenge_preferences = db::fetch_challenge_customization(&state.pool, &console_id)
+    let mut challenge_preferences: ChallengePreferences =
+        db::fetch_challenge_customization(&state.pool, &console_id)
+            .await?
+            .map(Into::into)
+            .unwrap_or_default();
+    challenge_preferences.challenges = db::fetch_console_challenges(&state.pool, &console_id)
         .await?
+        .into_iter()
         .map(Into::into)
-        .unwrap_or_default();
+        .collect();
     Ok(Json(challenge_preferences))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateChallengePreferences {
-    #[serde(default)]
-    pub width: Option<u16>,
-    #[serde(default)]
-    pub height: Option<u16>,
-    #[serde(default)]
-    pub small_width: Option<u16>,
-    #[serde(default)]
-    pub small_height: Option<u16>,
-    #[serde(default, deserialize_with = "nested_option::deserialize")]
-    pub logo_url: Option<Option<String>>,
+/// Most string overrides a console can set.
+pub const MAX_LOCALIZED_STRINGS: usize = 100;
+/// Longest string override.
+pub const MAX_LOCALIZED_STRING_LEN: usize = 256;
+
+fn validate_weight(weight: Option<u16>) -> Result<Option<i16>, ConsoleError> {
+    weight
+        .map(TryInto::try_into)
+        .transpose()
+        .map_err(|_| ConsoleError::InvalidInput { what: "weight out of range [0:32,767]".into() })
 }
 
 fn validate_update_dimension(
@@ -261,14 +239,109 @@ fn validate_update_dimension(
         })
 }
 
+fn validate_theme(theme: &Theme) -> Result<(), ConsoleError> {
+    let colors = [
+        ("primary_color", &theme.primary_color),
+        ("background_color", &theme.background_color),
+        ("text_color", &theme.text_color),
+    ];
+    for (input_name, color) in colors {
+        if color.as_deref().is_some_and(|c| !is_hex_color(c)) {
+            return Err(ConsoleError::InvalidInput {
+                what: format!("{input_name} must be a hex color like #7c3aed"),
+            });
+        }
+    }
+    if theme.corner_radius.is_some_and(|r| r > 64) {
+        return Err(ConsoleError::InvalidInput {
+            what: "corner_radius out of range [0:64]".into(),
+        });
+    }
+    // the font family ends up in css, keep it to plain names
+    if theme.font_family.as_deref().is_some_and(|f| {
+        f.len() > 64
+            || !f
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_' | ','))
+    }) {
+        return Err(ConsoleError::InvalidInput {
+            what: "font_family is not a valid font name".into(),
+        });
+    }
+    Ok(())
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn validate_localization(localization: &Localization) -> Result<(), ConsoleError> {
+    if localization.locale.as_deref().is_some_and(|l| {
+        l.is_empty() || l.len() > 35 || !l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    }) {
+        return Err(ConsoleError::InvalidInput {
+            what: "locale is not a BCP 47 language tag".into(),
+        });
+    }
+    if localization.strings.len() > MAX_LOCALIZED_STRINGS {
+        return Err(ConsoleError::InvalidInput {
+            what: format!("at most {MAX_LOCALIZED_STRINGS} strings can be overridden"),
+        });
+    }
+    if localization
+        .strings
+        .iter()
+        .any(|(key, value)| key.len() > 64 || value.len() > MAX_LOCALIZED_STRING_LEN)
+    {
+        return Err(ConsoleError::InvalidInput {
+            what: format!("string overrides are limited to {MAX_LOCALIZED_STRING_LEN} bytes"),
+        });
+    }
+    Ok(())
+}
+
 #[instrument(skip(state), ret(Debug, level = Level::DEBUG), err(Debug, level = Level::ERROR))]
 pub async fn update_challenge_preferences(
     State(state): State<Arc<AppState>>,
     Path(console_id): Path<Uuid>,
     Json(update): Json<UpdateChallengePreferences>,
 ) -> Result<(), ConsoleError> {
+    let challenges = update
+        .challenges
+        .unwrap_or_default()
+        .into_iter()
+        .map(|challenge| {
+            Ok(DbConsoleChallenge {
+                weight: validate_weight(challenge.weight)?,
+                challenge_url: challenge.url,
+                enabled: challenge.enabled,
+            })
+        })
+        .collect::<Result<Vec<_>, ConsoleError>>()?;
+    if let Some(theme) = &update.theme {
+        validate_theme(theme)?;
+    }
+    if let Some(localization) = &update.localization {
+        validate_localization(localization)?;
+    }
+    if update.proof_of_work.is_some_and(|pow| !pow.is_valid()) {
+        return Err(ConsoleError::InvalidInput {
+            what: format!(
+                "proof of work memory_kib must be a power of two in \
+                 [{MIN_SCRYPT_MEMORY_KIB}:{MAX_SCRYPT_MEMORY_KIB}]"
+            ),
+        });
+    }
+
+    let mut txn = state
+        .pool
+        .begin()
+        .await
+        .context("db could not begin transaction")?;
     let res = db::update_challenge_customization(
-        &state.pool,
+        &mut *txn,
         &console_id,
         &DbUpdateChallengeCustomization {
             width: validate_update_dimension("width", update.width)?,
@@ -276,9 +349,37 @@ pub async fn update_challenge_preferences(
             small_width: validate_update_dimension("small_width", update.small_width)?,
             small_height: validate_update_dimension("small_height", update.small_height)?,
             logo_url: update.logo_url.as_ref().map(|l| l.as_deref()),
+            exclude_webgl_on_low_end: update.exclude_webgl_on_low_end,
+            escalate_difficulty: update.escalate_difficulty,
+            accessibility_enabled: update.accessibility_enabled,
+            theme: update.theme.as_ref().map(|theme| DbTheme {
+                mode: theme.mode.as_str(),
+                primary_color: theme.primary_color.as_deref(),
+                background_color: theme.background_color.as_deref(),
+                text_color: theme.text_color.as_deref(),
+                corner_radius: theme.corner_radius.map(Into::into),
+                font_family: theme.font_family.as_deref(),
+            }),
+            localization: update
+                .localization
+                .as_ref()
+                .map(|localization| DbLocalization {
+                    locale: localization.locale.as_deref(),
+                    strings: &localization.strings,
+                }),
+            proof_of_work: update.proof_of_work.as_ref().map(|pow| DbProofOfWork {
+                algorithm: pow.algorithm.as_str(),
+                memory_kib: pow.memory_kib as i32,
+            }),
         },
     )
     .await?;
+    for challenge in &challenges {
+        db::upsert_console_challenge(&mut *txn, &console_id, challenge).await?;
+    }
+    txn.commit()
+        .await
+        .context("db could not commit transaction")?;
 
     match res {
         RowsAffected(0) => Err(ConsoleError::NotFound {
@@ -288,6 +389,74 @@ pub async fn update_challenge_preferences(
     }
 }
 
+#[instrument(skip(state), ret(Debug, level = Level::DEBUG), err(Debug, level = Level::ERROR))]
+pub async fn get_customization_profiles(
+    State(state): State<Arc<AppState>>,
+    Path(console_id): Path<Uuid>,
+) -> Result<Json<Vec<CustomizationProfile>>, ConsoleError> {
+    let profiles = db::fetch_customization_profiles(&state.pool, &console_id)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Ok(Json(profiles))
+}
+
+#[instrument(skip(state), ret(Debug, level = Level::DEBUG), err(Debug, level = Level::ERROR))]
+pub async fn create_customization_profile(
+    State(state): State<Arc<AppState>>,
+    Path(console_id): Path<Uuid>,
+    Json(request): Json<CreateCustomizationProfile>,
+) -> Result<Json<CustomizationProfile>, ConsoleError> {
+    let profile = DbCustomizationProfile {
+        id: Uuid::new_v4(),
+        label: request.label,
+        width: validate_update_dimension("width", request.width)?,
+        height: validate_update_dimension("height", request.height)?,
+        small_width: validate_update_dimension("small_width", request.small_width)?,
+        small_height: validate_update_dimension("small_height", request.small_height)?,
+        logo_url: request.logo_url,
+    };
+    db::insert_customization_profile(&state.pool, &console_id, &profile).await?;
+    Ok(Json(profile.into()))
+}
+
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn update_customization_profile(
+    State(state): State<Arc<AppState>>,
+    Path((console_id, profile_id)): Path<(Uuid, Uuid)>,
+    Json(update): Json<UpdateCustomizationProfile>,
+) -> Result<(), ConsoleError> {
+    let update = DbUpdateCustomizationProfile {
+        label: update.label.as_deref(),
+        width: validate_update_dimension("width", update.width)?,
+        height: validate_update_dimension("height", update.height)?,
+        small_width: validate_update_dimension("small_width", update.small_width)?,
+        small_height: validate_update_dimension("small_height", update.small_height)?,
+        logo_url: update.logo_url.as_ref().map(|l| l.as_deref()),
+    };
+    match db::update_customization_profile(&state.pool, &console_id, &profile_id, &update).await? {
+        RowsAffected(0) => Err(ConsoleError::NotFound {
+            what: format!("customization profile {profile_id} for console with id {console_id}"),
+        }),
+        RowsAffected(_) => Ok(()),
+    }
+}
+
+/// Deletes a customization profile, site keys using it fall back to the console preferences.
+#[instrument(skip(state), err(Debug, level = Level::ERROR))]
+pub async fn delete_customization_profile(
+    State(state): State<Arc<AppState>>,
+    Path((console_id, profile_id)): Path<(Uuid, Uuid)>,
+) -> Result<(), ConsoleError> {
+    match db::delete_customization_profile(&state.pool, &console_id, &profile_id).await? {
+        RowsAffected(0) => Err(ConsoleError::NotFound {
+            what: format!("customization profile {profile_id} for console with id {console_id}"),
+        }),
+        RowsAffected(_) => Ok(()),
+    }
+}
+
 impl From<DbConsole> for ConsoleResponse {
     fn from(c: DbConsole) -> Self {
         ConsoleResponse { id: c.id, label: c.label }
@@ -296,7 +465,14 @@ impl From<DbConsole> for ConsoleResponse {
 
 impl From<DbApiKey> for ApiKeyResponse {
     fn from(k: DbApiKey) -> Self {
-        ApiKeyResponse { site_key: k.site_key, secret: k.secret, label: k.label }
+        ApiKeyResponse {
+            site_key: k.site_key,
+            secret: k.secret,
+            label: k.label,
+            invisible: k.invisible,
+            score_threshold: k.score_threshold,
+            customization_profile_id: k.customization_profile_id,
+        }
     }
 }
 
@@ -307,7 +483,38 @@ impl From<DbChallengeCustomization> for ChallengePreferences {
             width: c.width as u16,
             small_width: c.small_width as u16,
             small_height: c.small_height as u16,
-            logo_url: c.logo_url,
+            logo_url: c.logo_url.clone(),
+            exclude_webgl_on_low_end: c.exclude_webgl_on_low_end,
+            escalate_difficulty: c.escalate_difficulty,
+            accessibility_enabled: c.accessibility_enabled,
+            challenges: vec![],
+            theme: Theme::from(&c),
+            proof_of_work: PowSettings::from(&c),
+            localization: Localization::from(c),
+        }
+    }
+}
+
+impl From<DbConsoleChallenge> for ChallengeSelection {
+    fn from(c: DbConsoleChallenge) -> Self {
+        ChallengeSelection {
+            url: c.challenge_url,
+            enabled: c.enabled,
+            weight: c.weight.map(|w| w as u16),
+        }
+    }
+}
+
+impl From<DbCustomizationProfile> for CustomizationProfile {
+    fn from(p: DbCustomizationProfile) -> Self {
+        CustomizationProfile {
+            id: p.id,
+            label: p.label,
+            width: p.width.map(|w| w as u16),
+            height: p.height.map(|h| h as u16),
+            small_width: p.small_width.map(|w| w as u16),
+            small_height: p.small_height.map(|h| h as u16),
+            logo_url: p.logo_url,
         }
     }
 }
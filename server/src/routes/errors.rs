@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    extract::rejection::FormRejection,
+    extract::rejection::{FormRejection, JsonRejection},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
@@ -21,6 +21,20 @@ pub enum ChallengeError {
     FailedProofOfWork,
     #[error("No matching challenge")]
     NoMatchingChallenge,
+    #[error("Invisible mode is not enabled for this key")]
+    InvisibleModeDisabled,
+    #[error("Invalid action: only alphanumeric characters, slashes and underscores are allowed")]
+    InvalidAction,
+    #[error("Accessibility challenge is not enabled for this console")]
+    AccessibilityDisabled,
+    #[error("Invalid, expired or already answered accessibility question")]
+    InvalidQuestion,
+    #[error("Too many requests")]
+    TooManyRequests,
+    #[error("Invalid, expired or already answered puzzle")]
+    InvalidPuzzle,
+    #[error("Invalid, expired or already answered challenge instance")]
+    InvalidInstance,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -43,6 +57,24 @@ impl IntoResponse for ChallengeError {
             ChallengeError::NoMatchingChallenge => {
                 (StatusCode::NOT_FOUND, self.to_string()).into_response()
             }
+            ChallengeError::InvisibleModeDisabled => {
+                (StatusCode::FORBIDDEN, self.to_string()).into_response()
+            }
+            ChallengeError::InvalidAction => {
+                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+            }
+            ChallengeError::AccessibilityDisabled => {
+                (StatusCode::FORBIDDEN, self.to_string()).into_response()
+            }
+            ChallengeError::InvalidQuestion => {
+                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+            }
+            ChallengeError::TooManyRequests => {
+                (StatusCode::TOO_MANY_REQUESTS, self.to_string()).into_response()
+            }
+            ChallengeError::InvalidPuzzle | ChallengeError::InvalidInstance => {
+                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+            }
         }
     }
 }
@@ -61,6 +93,8 @@ pub enum ConsoleError {
     Forbidden,
     #[error("Duplicate")]
     Duplicate,
+    #[error("{what} already exists")]
+    AlreadyExists { what: String },
     #[error("Invalid input: {what}")]
     InvalidInput { what: String },
     #[error(transparent)]
@@ -80,6 +114,9 @@ impl IntoResponse for ConsoleError {
                 (StatusCode::NOT_FOUND, self.to_string()).into_response()
             }
             ConsoleError::Forbidden => StatusCode::FORBIDDEN.into_response(),
+            ConsoleError::AlreadyExists { .. } => {
+                (StatusCode::CONFLICT, self.to_string()).into_response()
+            }
             ConsoleError::InvalidInput { .. } => {
                 (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()).into_response()
             }
@@ -95,6 +132,26 @@ impl From<db::Error> for ConsoleError {
             {
                 ConsoleError::Forbidden
             }
+            db::Error::Constraint { source, kind: ConstraintKind::ForeignKey }
+                if source.constraint() == Some("console_challenge_challenge_url_fkey") =>
+            {
+                ConsoleError::NotFound { what: "challenge".into() }
+            }
+            db::Error::Constraint { source, kind: ConstraintKind::ForeignKey }
+                if source.constraint() == Some("api_key_customization_profile_id_fkey") =>
+            {
+                ConsoleError::NotFound { what: "customization profile".into() }
+            }
+            db::Error::Constraint { source, kind: ConstraintKind::UniqueKey }
+                if source.constraint() == Some("customization_profile_label_unique") =>
+            {
+                ConsoleError::AlreadyExists { what: "Customization profile label".into() }
+            }
+            db::Error::Constraint { source, kind: ConstraintKind::ValueRange } => {
+                ConsoleError::InvalidInput {
+                    what: format!("{} violated", source.constraint().unwrap_or("range")),
+                }
+            }
             db::Error::Constraint {
                 source,
                 kind: ConstraintKind::PrimaryKey | ConstraintKind::UniqueKey,
@@ -113,8 +170,14 @@ impl From<db::Error> for ConsoleError {
 pub enum AdminError {
     #[error("{what} resource already exists")]
     NotUnique { what: String },
-    #[error("Dimensions out of range: width and height must be greater than 0")]
+    #[error("Dimensions out of range: width and height must be between 1 and 32,767")]
     InvalidDimensions,
+    #[error("Selection out of range: weight must be at most 32,767 and difficulty between 1 and 5")]
+    InvalidSelection,
+    #[error(
+        "Attempts out of range: max attempts must be between 1 and 10 and moves per attempt between 1 and 100"
+    )]
+    InvalidAttempts,
     #[error("Could not parse URL")]
     InvalidUrl,
     #[error("Challenge not found: url('{0}')")]
@@ -137,7 +200,9 @@ impl IntoResponse for AdminError {
             AdminError::NotUnique { what: _ } => {
                 (StatusCode::CONFLICT, self.to_string()).into_response()
             }
-            AdminError::InvalidDimensions => {
+            AdminError::InvalidDimensions
+            | AdminError::InvalidSelection
+            | AdminError::InvalidAttempts => {
                 (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()).into_response()
             }
             AdminError::InvalidUrl => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
@@ -152,6 +217,22 @@ impl IntoResponse for AdminError {
 impl From<db::Error> for AdminError {
     fn from(db_err: db::Error) -> Self {
         match db_err {
+            db::Error::Constraint { source, kind: ConstraintKind::ValueRange }
+                if matches!(
+                    source.constraint(),
+                    Some("challenge_weight_range" | "challenge_difficulty_range")
+                ) =>
+            {
+                AdminError::InvalidSelection
+            }
+            db::Error::Constraint { source, kind: ConstraintKind::ValueRange }
+                if matches!(
+                    source.constraint(),
+                    Some("challenge_max_attempts_range" | "challenge_moves_per_attempt_range")
+                ) =>
+            {
+                AdminError::InvalidAttempts
+            }
             db::Error::Constraint { kind: ConstraintKind::ValueRange, .. } => {
                 AdminError::InvalidDimensions
             }
@@ -172,6 +253,8 @@ pub enum VerificationError {
     #[error(transparent)]
     BadRequest(#[from] FormRejection),
     #[error(transparent)]
+    BadJsonRequest(#[from] JsonRejection),
+    #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
 
@@ -185,7 +268,7 @@ impl IntoResponse for VerificationError {
                 other => Some(other.into_response()),
             }),
             VerificationError::UserError(verification) => Json(verification).into_response(),
-            VerificationError::BadRequest(_) => {
+            VerificationError::BadRequest(_) | VerificationError::BadJsonRequest(_) => {
                 Json(VerificationResponse::failure(vec![ErrorCodes::BadRequest])).into_response()
             }
         }
@@ -1,69 +1,69 @@
-use std::{collections::HashMap, fmt::Display, net::IpAddr, str::FromStr, sync::Arc};
+use std::{collections::HashMap, net::IpAddr, str::FromStr, sync::Arc};
 
 use anyhow::Context;
-use axum::{Form, Json, extract::State};
-use axum_extra::extract::WithRejection;
+use axum::{
+    Form, Json,
+    extract::{FromRequest, Request, State},
+    http::header::CONTENT_TYPE,
+};
 use jsonwebtoken::errors::ErrorKind;
 use secrecy::{ExposeSecret, Secret};
-use serde::{Deserialize, Serialize};
-use thiserror::Error;
-use time::OffsetDateTime;
 use tracing::{Level, instrument};
-use url::Host;
 
-use crate::{AppState, db, encodings::Base64, tokens::response};
+use crate::{AppState, db, encodings::Base64, telemetry, tokens::response};
 
 use super::errors::VerificationError;
 
+pub use gotcha_types::verification::{ErrorCodes, VerificationResponse};
+
 #[derive(Debug)]
 pub struct VerificationRequest {
     secret: Secret<Base64>,
     response: String,
     remoteip: Option<IpAddr>,
+    sitekey: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Error)]
-pub struct VerificationResponse {
-    pub success: bool,
-    #[serde(with = "time::serde::iso8601")]
-    pub challenge_ts: OffsetDateTime,
-    #[serde(with = "crate::serde::option_host_as_str")]
-    pub hostname: Option<Host>,
-    #[serde(rename = "error-codes", skip_serializing_if = "Option::is_none")]
-    pub error_codes: Option<Vec<ErrorCodes>>,
-}
-
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum ErrorCodes {
-    MissingInputSecret,
-    InvalidInputSecret,
-    MissingInputResponse,
-    InvalidInputResponse,
-    BadRequest,
-    TimeoutOrDuplicate,
-}
+/// Raw siteverify parameters, read from either an url encoded form or a json body.
+#[derive(Debug)]
+pub struct VerificationParams(HashMap<String, String>);
 
 #[instrument(skip(state), ret(Debug, level = Level::INFO), err(Debug, level = Level::ERROR))]
 pub async fn site_verify(
     State(state): State<Arc<AppState>>,
-    WithRejection(Form(verification), _): WithRejection<
-        Form<HashMap<String, String>>,
-        VerificationError,
-    >,
+    VerificationParams(verification): VerificationParams,
+) -> Result<Json<VerificationResponse>, VerificationError> {
+    let response = verify(&state, verification).await;
+    match &response {
+        Ok(Json(response)) | Err(VerificationError::UserError(response)) => {
+            telemetry::site_verified(Some(response))
+        }
+        Err(_) => telemetry::site_verified(None),
+    }
+    response
+}
+
+async fn verify(
+    state: &AppState,
+    verification: HashMap<String, String>,
 ) -> Result<Json<VerificationResponse>, VerificationError> {
     let verification: Result<VerificationRequest, Vec<ErrorCodes>> = verification.try_into();
     let verification = verification.map_err(VerificationResponse::failure)?;
 
-    let enc_key = db::fetch_api_key_by_secret(&state.pool, verification.secret.expose_secret())
+    let api_key = db::fetch_api_key_by_secret(&state.pool, verification.secret.expose_secret())
         .await
         .context("failed to fetch encoding key bey api secret while verifying challenge")?
         .ok_or(VerificationResponse::failure(vec![
             ErrorCodes::InvalidInputSecret,
-        ]))?
-        .encoding_key;
+        ]))?;
+    if verification
+        .sitekey
+        .is_some_and(|sitekey| sitekey != api_key.site_key.as_str())
+    {
+        return Err(VerificationResponse::failure(vec![ErrorCodes::SitekeySecretMismatch]).into());
+    }
 
-    let claims = response::decode(&verification.response, &enc_key)
+    let claims = response::decode(&verification.response, &api_key.encoding_key)
         .map_err(|err| match err.into_kind() {
             ErrorKind::ExpiredSignature => ErrorCodes::TimeoutOrDuplicate,
             _ => ErrorCodes::InvalidInputResponse,
@@ -74,25 +74,20 @@ pub async fn site_verify(
         .remoteip
         .is_none_or(|solver| solver == claims.other.addr);
 
+    // in invisible mode the site backend applies its own threshold to the score
+    let score_check = api_key.invisible || claims.other.score >= api_key.score_threshold;
+
     Ok(Json(VerificationResponse {
-        success: claims.other.score >= 0.5 && solver_check,
+        success: score_check && solver_check,
         challenge_ts: *claims.iat(),
         hostname: Some(claims.other.host),
-        error_codes: None,
+        score: Some(claims.other.score),
+        challenge_type: Some(claims.other.challenge_type),
+        action: claims.other.action,
+        error_codes: (!solver_check).then(|| vec![ErrorCodes::RemoteipMismatch]),
     }))
 }
 
-impl VerificationResponse {
-    pub fn failure(errors: Vec<ErrorCodes>) -> Self {
-        Self {
-            success: false,
-            challenge_ts: OffsetDateTime::UNIX_EPOCH,
-            hostname: None,
-            error_codes: Some(errors),
-        }
-    }
-}
-
 impl TryFrom<HashMap<String, String>> for VerificationRequest {
     type Error = Vec<ErrorCodes>;
 
@@ -109,7 +104,8 @@ impl TryFrom<HashMap<String, String>> for VerificationRequest {
             }
             Some(Ok(secret)) => Some(secret),
         };
-        if !form.contains_key("response") {
+        let response = form.remove("response");
+        if response.is_none() {
             errors.push(ErrorCodes::MissingInputResponse);
         }
         let remoteip = match form.remove("remoteip").as_deref().map(IpAddr::from_str) {
@@ -120,32 +116,53 @@ impl TryFrom<HashMap<String, String>> for VerificationRequest {
             }
             Some(Ok(r)) => Some(r),
         };
+        let sitekey = form.remove("sitekey");
+        // in strict mode any parameter we don't know about is a malformed request
+        match form.remove("strict").as_deref() {
+            None | Some("false") => {}
+            Some("true") if form.is_empty() => {}
+            Some(_) => errors.push(ErrorCodes::BadRequest),
+        }
         if !errors.is_empty() {
+            errors.dedup();
             return Err(errors);
         }
 
         Ok(VerificationRequest {
             secret: Secret::new(secret_b64.expect("validated before")),
-            response: form
-                .remove("response")
-                .expect("checked if it contains key before"),
+            response: response.expect("validated before"),
             remoteip,
+            sitekey,
         })
     }
 }
 
-impl Display for VerificationResponse {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(
-            f,
-            "verification: challenge {}, loaded at {} in `{:?}` - {:?}",
-            match self.success {
-                true => "solved successfully",
-                false => "failed",
-            },
-            self.challenge_ts,
-            self.hostname,
-            self.error_codes
-        )
+impl<S: Send + Sync> FromRequest<S> for VerificationParams {
+    type Rejection = VerificationError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("application/json"));
+
+        if is_json {
+            let Json(params) =
+                Json::<HashMap<String, serde_json::Value>>::from_request(req, state).await?;
+            // null fields are absent rather than the string "null"
+            let params = params
+                .into_iter()
+                .filter_map(|(key, value)| match value {
+                    serde_json::Value::Null => None,
+                    serde_json::Value::String(value) => Some((key, value)),
+                    other => Some((key, other.to_string())),
+                })
+                .collect();
+            Ok(Self(params))
+        } else {
+            let Form(params) = Form::from_request(req, state).await?;
+            Ok(Self(params))
+        }
     }
 }
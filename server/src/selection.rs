@@ -0,0 +1,300 @@
+//! Selection of the challenge served to a user. Challenges are filtered by what the device can run
+//! and by the difficulty the request calls for, then one is drawn at random proportionally to its
+//! weight.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{DbChallenge, DbChallengeCustomization, DbEscalation};
+
+/// Hardest difficulty a challenge can have.
+pub const MAX_DIFFICULTY: u8 = 5;
+/// Score under which an attempt counts as failed and escalates the difficulty, the default score
+/// threshold of api keys.
+pub const PASSING_SCORE: f32 = 0.5;
+
+/// Class of device requesting a challenge, as reported by the widget.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeviceClass {
+    #[default]
+    Desktop,
+    Mobile,
+    /// Mobile device with little memory or few cores, struggling with WebGL-heavy challenges.
+    LowEndMobile,
+}
+
+/// Console owner preferences on how challenges are picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectionPolicy {
+    /// Skip challenges requiring WebGL on [`DeviceClass::LowEndMobile`].
+    pub exclude_webgl_on_low_end: bool,
+    /// Serve harder challenges after failed attempts or a bad pre analysis.
+    pub escalate_difficulty: bool,
+}
+
+/// What is known about the client asking for a challenge. Only the device comes from the client,
+/// the escalation is recorded by the server per site key and address.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SelectionContext {
+    /// A hint of what the device can run, it never lowers the difficulty.
+    pub device: DeviceClass,
+    /// Challenges failed within the last hour.
+    pub failed_attempts: u8,
+    /// Lowest difficulty recommended by a failed pre analysis.
+    pub min_difficulty: u8,
+}
+
+impl Default for SelectionPolicy {
+    fn default() -> Self {
+        Self { exclude_webgl_on_low_end: true, escalate_difficulty: true }
+    }
+}
+
+impl From<&DbChallengeCustomization> for SelectionPolicy {
+    fn from(customization: &DbChallengeCustomization) -> Self {
+        Self {
+            exclude_webgl_on_low_end: customization.exclude_webgl_on_low_end,
+            escalate_difficulty: customization.escalate_difficulty,
+        }
+    }
+}
+
+impl From<&DbEscalation> for SelectionContext {
+    fn from(escalation: &DbEscalation) -> Self {
+        Self {
+            device: DeviceClass::default(),
+            failed_attempts: escalation.failed_attempts.clamp(0, u8::MAX.into()) as u8,
+            min_difficulty: escalation.min_difficulty.clamp(0, MAX_DIFFICULTY.into()) as u8,
+        }
+    }
+}
+
+impl SelectionContext {
+    /// Difficulty the served challenge should have at least, every failed attempt raises it by one.
+    pub fn target_difficulty(&self) -> u8 {
+        self.min_difficulty
+            .max(1)
+            .saturating_add(self.failed_attempts)
+            .min(MAX_DIFFICULTY)
+    }
+}
+
+/// Maps a pre analysis score to the lowest difficulty of the challenge to serve next.
+pub fn difficulty_for_score(score: f32) -> u8 {
+    match score {
+        s if s < 0.2 => 3,
+        s if s < 0.4 => 2,
+        _ => 1,
+    }
+}
+
+/// Chooses a challenge according to the policy, never serving disabled or unhealthy ones. Filters
+/// that would leave nothing to serve are relaxed: when no challenge is hard enough the hardest ones
+/// are served, and a low-end device still gets a WebGL challenge if none of those runs without.
+/// The device is filtered on last so it can't lower the difficulty.
+pub fn choose_challenge(
+    challenges: Vec<DbChallenge>,
+    policy: &SelectionPolicy,
+    ctx: &SelectionContext,
+    rng: &mut impl Rng,
+) -> Option<DbChallenge> {
+    let mut candidates: Vec<_> = challenges
+        .into_iter()
+        .filter(|c| c.weight > 0 && c.enabled && c.healthy)
+        .collect();
+
+    if policy.escalate_difficulty {
+        let target = ctx.target_difficulty() as i16;
+        let hardest = candidates.iter().map(|c| c.difficulty).max()?;
+        let min_difficulty = target.min(hardest);
+        candidates.retain(|c| c.difficulty >= min_difficulty);
+    }
+
+    if policy.exclude_webgl_on_low_end
+        && ctx.device == DeviceClass::LowEndMobile
+        && candidates.iter().any(|c| !c.requires_webgl)
+    {
+        candidates.retain(|c| !c.requires_webgl);
+    }
+
+    let total: u32 = candidates.iter().map(|c| c.weight as u32).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut draw = rng.random_range(0..total);
+    let idx = candidates.iter().position(|c| {
+        let weight = c.weight as u32;
+        if draw < weight {
+            return true;
+        }
+        draw -= weight;
+        false
+    })?;
+    Some(candidates.swap_remove(idx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(url: &str, weight: i16, difficulty: i16, requires_webgl: bool) -> DbChallenge {
+        DbChallenge { weight, difficulty, requires_webgl, ..DbChallenge::new(url.into()) }
+    }
+
+    fn choose(
+        challenges: Vec<DbChallenge>,
+        policy: SelectionPolicy,
+        ctx: SelectionContext,
+    ) -> Option<String> {
+        choose_challenge(challenges, &policy, &ctx, &mut rand::rng()).map(|c| c.url)
+    }
+
+    #[test]
+    fn no_challenges() {
+        let chosen = choose(
+            vec![],
+            SelectionPolicy::default(),
+            SelectionContext::default(),
+        );
+        assert_eq!(chosen, None);
+    }
+
+    #[test]
+    fn zero_weight_never_chosen() {
+        for _ in 0..100 {
+            let chosen = choose(
+                vec![challenge("a", 0, 1, false), challenge("b", 1, 1, false)],
+                SelectionPolicy::default(),
+                SelectionContext::default(),
+            );
+            assert_eq!(chosen.as_deref(), Some("b"));
+        }
+    }
+
+    #[test]
+    fn weighted_draw_follows_weights() {
+        let mut heavy = 0;
+        for _ in 0..1000 {
+            let chosen = choose(
+                vec![challenge("a", 9, 1, false), challenge("b", 1, 1, false)],
+                SelectionPolicy::default(),
+                SelectionContext::default(),
+            );
+            if chosen.as_deref() == Some("a") {
+                heavy += 1;
+            }
+        }
+        assert!(
+            (800..=980).contains(&heavy),
+            "chose heavier challenge {heavy} times"
+        );
+    }
+
+    #[test]
+    fn unhealthy_never_chosen() {
+        let broken = DbChallenge { healthy: false, ..challenge("broken", 9, 1, false) };
+        let chosen = choose(
+            vec![broken],
+            SelectionPolicy::default(),
+            SelectionContext::default(),
+        );
+        assert_eq!(chosen, None);
+        for _ in 0..100 {
+            let broken = DbChallenge { healthy: false, ..challenge("broken", 9, 1, false) };
+            let chosen = choose(
+                vec![broken, challenge("b", 1, 1, false)],
+                SelectionPolicy::default(),
+                SelectionContext::default(),
+            );
+            assert_eq!(chosen.as_deref(), Some("b"));
+        }
+    }
+
+    #[test]
+    fn disabled_never_chosen() {
+        let disabled = DbChallenge { enabled: false, ..challenge("disabled", 9, 1, false) };
+        let chosen = choose(
+            vec![disabled],
+            SelectionPolicy::default(),
+            SelectionContext::default(),
+        );
+        assert_eq!(chosen, None);
+    }
+
+    #[test]
+    fn low_end_mobile_skips_webgl() {
+        let ctx = SelectionContext { device: DeviceClass::LowEndMobile, ..Default::default() };
+        for _ in 0..100 {
+            let chosen = choose(
+                vec![
+                    challenge("cup-stack", 5, 1, true),
+                    challenge("b", 1, 1, false),
+                ],
+                SelectionPolicy::default(),
+                ctx,
+            );
+            assert_eq!(chosen.as_deref(), Some("b"));
+        }
+        let chosen = choose(
+            vec![challenge("cup-stack", 5, 1, true)],
+            SelectionPolicy::default(),
+            ctx,
+        );
+        assert_eq!(chosen.as_deref(), Some("cup-stack"));
+    }
+
+    #[test]
+    fn device_never_lowers_difficulty() {
+        let ctx = SelectionContext {
+            device: DeviceClass::LowEndMobile,
+            failed_attempts: 2,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let chosen = choose(
+                vec![
+                    challenge("easy", 5, 1, false),
+                    challenge("cup-stack", 1, 3, true),
+                ],
+                SelectionPolicy::default(),
+                ctx,
+            );
+            assert_eq!(chosen.as_deref(), Some("cup-stack"));
+        }
+    }
+
+    #[test]
+    fn failed_attempts_escalate_difficulty() {
+        let challenges = || {
+            vec![
+                challenge("easy", 1, 1, false),
+                challenge("medium", 1, 2, false),
+                challenge("hard", 1, 4, false),
+            ]
+        };
+        let ctx = SelectionContext { failed_attempts: 1, ..Default::default() };
+        for _ in 0..100 {
+            let chosen = choose(challenges(), SelectionPolicy::default(), ctx);
+            assert_ne!(chosen.as_deref(), Some("easy"));
+        }
+        // nothing is hard enough, serve the hardest
+        let ctx = SelectionContext { failed_attempts: 10, ..Default::default() };
+        let chosen = choose(challenges(), SelectionPolicy::default(), ctx);
+        assert_eq!(chosen.as_deref(), Some("hard"));
+        // escalation disabled by the console owner
+        let policy = SelectionPolicy { escalate_difficulty: false, ..Default::default() };
+        let served_easy = (0..100)
+            .filter_map(|_| choose(challenges(), policy, ctx))
+            .any(|url| url == "easy");
+        assert!(served_easy);
+    }
+
+    #[test]
+    fn target_difficulty_is_capped() {
+        let ctx =
+            SelectionContext { failed_attempts: u8::MAX, min_difficulty: 3, ..Default::default() };
+        assert_eq!(ctx.target_difficulty(), MAX_DIFFICULTY);
+        assert_eq!(SelectionContext::default().target_difficulty(), 1);
+    }
+}
@@ -25,6 +25,8 @@ pub struct TestContext {
 struct InnerContext {
     test_id: Uuid,
     addr: SocketAddr,
+    /// Of the metrics listener, always served to the tests.
+    metrics_addr: SocketAddr,
     shutdown_signal: Sender<()>,
     pool: PgPool,
 }
@@ -60,6 +62,13 @@ impl TestContext {
         let test_id = Uuid::new_v4();
         populate_demo(&pool, &test_id).await?;
 
+        let metrics_listener =
+            tokio::net::TcpListener::bind(format!("{}:0", app_conf.host)).await?;
+        let metrics_addr = metrics_listener.local_addr()?;
+        tokio::spawn(
+            axum::serve(metrics_listener, crate::telemetry::router(pool.clone())).into_future(),
+        );
+
         let app_pool = pool.clone();
         let _join_handle = tokio::spawn(async move {
             axum::serve(
@@ -71,7 +80,9 @@ impl TestContext {
             .unwrap();
         });
 
-        Ok(Self { inner: Arc::new(InnerContext { test_id, addr, shutdown_signal, pool }) })
+        Ok(Self {
+            inner: Arc::new(InnerContext { test_id, addr, metrics_addr, shutdown_signal, pool }),
+        })
     }
 
     pub async fn teardown(self) -> anyhow::Result<()> {
@@ -91,6 +102,10 @@ impl TestContext {
         self.inner.addr.port()
     }
 
+    pub fn metrics_port(&self) -> u16 {
+        self.inner.metrics_addr.port()
+    }
+
     pub fn pool(&self) -> &PgPool {
         &self.inner.pool
     }
@@ -5,8 +5,12 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use url::Url;
 
+pub mod accessibility;
 pub mod auth;
+pub mod game;
+pub mod instance;
 pub mod pow_challenge;
+pub mod puzzle;
 pub mod response;
 
 #[derive(Debug, Serialize, Deserialize)]
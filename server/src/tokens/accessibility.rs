@@ -0,0 +1,44 @@
+use std::{net::IpAddr, time::Duration};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::encodings::Base64;
+
+use super::TimeClaims;
+
+pub static JWT_QUESTION_ALGORITHM: Algorithm = Algorithm::HS256;
+/// Time to read and answer a question.
+pub const QUESTION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Accessibility question handed to a client, answerable only from the address that asked for it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuestionClaims {
+    pub nonce: u64,
+    pub addr: IpAddr,
+    /// See [`crate::accessibility::answer_hash`].
+    pub answer_hash: String,
+}
+
+pub fn encode(
+    claims: QuestionClaims,
+    enc_key: &Base64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    jsonwebtoken::encode(
+        &Header::new(JWT_QUESTION_ALGORITHM),
+        &TimeClaims::with_timeout(QUESTION_TIMEOUT, claims),
+        &EncodingKey::from_base64_secret(enc_key.as_str())?,
+    )
+}
+
+pub fn decode(jwt: &str, dec_key: &Base64) -> Result<QuestionClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::new(JWT_QUESTION_ALGORITHM);
+    TimeClaims::<QuestionClaims>::build_validation(&mut validation);
+
+    jsonwebtoken::decode::<TimeClaims<QuestionClaims>>(
+        jwt,
+        &DecodingKey::from_base64_secret(dec_key.as_str())?,
+        &validation,
+    )
+    .map(|tok| tok.claims.other)
+}
@@ -0,0 +1,42 @@
+use std::{net::IpAddr, time::Duration};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::encodings::Base64;
+
+use super::TimeClaims;
+
+pub static JWT_GAME_ALGORITHM: Algorithm = Algorithm::HS256;
+/// Time to play a game challenge and report how it was played.
+pub const GAME_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Seed a game challenge is generated with, its trace is only scored when played on it, once and
+/// from the address that was served the challenge.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameClaims {
+    /// URL of the challenge served.
+    pub challenge: String,
+    pub seed: u64,
+    pub addr: IpAddr,
+}
+
+pub fn encode(claims: GameClaims, enc_key: &Base64) -> Result<String, jsonwebtoken::errors::Error> {
+    jsonwebtoken::encode(
+        &Header::new(JWT_GAME_ALGORITHM),
+        &TimeClaims::with_timeout(GAME_TIMEOUT, claims),
+        &EncodingKey::from_base64_secret(enc_key.as_str())?,
+    )
+}
+
+pub fn decode(jwt: &str, dec_key: &Base64) -> Result<GameClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::new(JWT_GAME_ALGORITHM);
+    TimeClaims::<GameClaims>::build_validation(&mut validation);
+
+    jsonwebtoken::decode::<TimeClaims<GameClaims>>(
+        jwt,
+        &DecodingKey::from_base64_secret(dec_key.as_str())?,
+        &validation,
+    )
+    .map(|tok| tok.claims.other)
+}
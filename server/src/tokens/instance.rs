@@ -0,0 +1,46 @@
+use std::{net::IpAddr, time::Duration};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::encodings::Base64;
+
+use super::TimeClaims;
+
+pub static JWT_INSTANCE_ALGORITHM: Algorithm = Algorithm::HS256;
+/// Time to answer an instance.
+pub const INSTANCE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Instance of a registered challenge handed to a client, answerable once and only from the
+/// address that asked for it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstanceClaims {
+    /// URL of the challenge issuing the instance.
+    pub challenge: String,
+    /// See [`crate::challenges::Seed::derive`].
+    pub nonce: u64,
+    pub addr: IpAddr,
+}
+
+pub fn encode(
+    claims: InstanceClaims,
+    enc_key: &Base64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    jsonwebtoken::encode(
+        &Header::new(JWT_INSTANCE_ALGORITHM),
+        &TimeClaims::with_timeout(INSTANCE_TIMEOUT, claims),
+        &EncodingKey::from_base64_secret(enc_key.as_str())?,
+    )
+}
+
+pub fn decode(jwt: &str, dec_key: &Base64) -> Result<InstanceClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::new(JWT_INSTANCE_ALGORITHM);
+    TimeClaims::<InstanceClaims>::build_validation(&mut validation);
+
+    jsonwebtoken::decode::<TimeClaims<InstanceClaims>>(
+        jwt,
+        &DecodingKey::from_base64_secret(dec_key.as_str())?,
+        &validation,
+    )
+    .map(|tok| tok.claims.other)
+}
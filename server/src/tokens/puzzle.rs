@@ -0,0 +1,43 @@
+use std::{net::IpAddr, time::Duration};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::encodings::Base64;
+
+use super::TimeClaims;
+
+pub static JWT_PUZZLE_ALGORITHM: Algorithm = Algorithm::HS256;
+/// Time to solve a puzzle.
+pub const PUZZLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Slider puzzle handed to a client, answerable once and only from the address that asked for it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PuzzleClaims {
+    /// See [`crate::challenges::Seed::derive`].
+    pub nonce: u64,
+    pub addr: IpAddr,
+}
+
+pub fn encode(
+    claims: PuzzleClaims,
+    enc_key: &Base64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    jsonwebtoken::encode(
+        &Header::new(JWT_PUZZLE_ALGORITHM),
+        &TimeClaims::with_timeout(PUZZLE_TIMEOUT, claims),
+        &EncodingKey::from_base64_secret(enc_key.as_str())?,
+    )
+}
+
+pub fn decode(jwt: &str, dec_key: &Base64) -> Result<PuzzleClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::new(JWT_PUZZLE_ALGORITHM);
+    TimeClaims::<PuzzleClaims>::build_validation(&mut validation);
+
+    jsonwebtoken::decode::<TimeClaims<PuzzleClaims>>(
+        jwt,
+        &DecodingKey::from_base64_secret(dec_key.as_str())?,
+        &validation,
+    )
+    .map(|tok| tok.claims.other)
+}
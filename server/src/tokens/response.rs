@@ -3,10 +3,12 @@ use serde::{Deserialize, Serialize};
 use std::{net::IpAddr, time::Duration};
 use url::Host;
 
-use crate::encodings::Base64;
+use crate::{encodings::Base64, telemetry};
 
 use super::TimeClaims;
 
+pub use gotcha_types::verification::ChallengeType;
+
 pub static JWT_RESPONSE_ALGORITHM: Algorithm = Algorithm::HS256;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,12 +17,18 @@ pub struct ResponseClaims {
     pub addr: IpAddr,
     #[serde(with = "crate::serde::host_as_str")]
     pub host: Host,
+    /// Which path issued the token.
+    pub challenge_type: ChallengeType,
+    /// Name of the action the token was issued for. Only present in invisible mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
 }
 
 pub fn encode(
     response_claims: ResponseClaims,
     enc_key: &Base64,
 ) -> Result<String, jsonwebtoken::errors::Error> {
+    telemetry::token_issued(response_claims.challenge_type, response_claims.score);
     jsonwebtoken::encode(
         &Header::new(JWT_RESPONSE_ALGORITHM),
         &TimeClaims::new(response_claims),
@@ -33,6 +41,7 @@ pub fn encode_with_timeout(
     enc_key: &Base64,
     timeout: Duration,
 ) -> Result<String, jsonwebtoken::errors::Error> {
+    telemetry::token_issued(response_claims.challenge_type, response_claims.score);
     jsonwebtoken::encode(
         &Header::new(JWT_RESPONSE_ALGORITHM),
         &TimeClaims::with_timeout(timeout, response_claims),
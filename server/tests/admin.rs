@@ -1,6 +1,11 @@
 use gotcha_server::{
     HTTP_CLIENT,
-    routes::admin::{AddChallenge, DeleteChallenge},
+    challenges::ChallengeKind,
+    db::{self, DbChallenge},
+    health::{self, HealthCheckConfig},
+    routes::admin::{
+        self, AddChallenge, AddedChallenge, ChallengePage, DeleteChallenge, UpdateChallenge,
+    },
     test_helpers,
 };
 use gotcha_server_macros::integration_test;
@@ -15,7 +20,23 @@ async fn add_challenge_successful(server: TestContext) -> anyhow::Result<()> {
     let response = HTTP_CLIENT
         .post(format!("http://localhost:{port}/api/admin/challenge"))
         .bearer_auth(test_helpers::auth_jwt().await)
-        .json(&AddChallenge { url: url.clone(), width: 50, height: 50 })
+        .json(&AddChallenge {
+            url: url.clone(),
+            width: 50,
+            height: 50,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
+        })
         .send()
         .await?;
     assert_eq!(response.status(), StatusCode::OK);
@@ -37,6 +58,18 @@ async fn add_challenge_bad_url(server: TestContext) -> anyhow::Result<()> {
             url: "bad_url::gotcha-integration.test.com/index.html".into(),
             width: 50,
             height: 50,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
         })
         .send()
         .await?;
@@ -45,6 +78,56 @@ async fn add_challenge_bad_url(server: TestContext) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[integration_test]
+async fn add_remote_challenge_gets_secret(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let url = format!(
+        "https://gotcha-integration.test.com/remote.html?nonce={}",
+        server.test_id()
+    );
+    let add = |verify_url: Option<&str>| AddChallenge {
+        url: url.clone(),
+        width: 50,
+        height: 50,
+        small_width: None,
+        small_height: None,
+        label: None,
+        logo_url: None,
+        weight: None,
+        difficulty: None,
+        requires_webgl: false,
+        max_attempts: None,
+        moves_per_attempt: None,
+        traced: false,
+        kind: ChallengeKind::Remote,
+        verify_url: verify_url.map(Into::into),
+    };
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{port}/api/admin/challenge"))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&add(None))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{port}/api/admin/challenge"))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&add(Some("https://gotcha-integration.test.com/verify")))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let added: AddedChallenge = response.json().await?;
+
+    let challenge = db::fetch_challenge(server.pool(), &url).await?.unwrap();
+    assert_eq!(challenge.kind, "remote");
+    assert_eq!(challenge.verify_secret, added.verify_secret);
+    assert!(added.verify_secret.is_some());
+
+    Ok(())
+}
+
 #[integration_test]
 async fn add_challenge_negative_dimensions(server: TestContext) -> anyhow::Result<()> {
     let port = server.port();
@@ -75,6 +158,18 @@ async fn add_challenge_zero_dimensions(server: TestContext) -> anyhow::Result<()
             url: "https://gotcha-integration.test.com/index.html".into(),
             width: 50,
             height: 0,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
         })
         .send()
         .await?;
@@ -93,7 +188,23 @@ async fn add_challenge_already_exists(server: TestContext) -> anyhow::Result<()>
     let response = HTTP_CLIENT
         .post(format!("http://localhost:{port}/api/admin/challenge"))
         .bearer_auth(test_helpers::auth_jwt().await)
-        .json(&AddChallenge { url: url.clone(), width: 50, height: 50 })
+        .json(&AddChallenge {
+            url: url.clone(),
+            width: 50,
+            height: 50,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
+        })
         .send()
         .await?;
     assert_eq!(response.status(), StatusCode::OK);
@@ -101,7 +212,23 @@ async fn add_challenge_already_exists(server: TestContext) -> anyhow::Result<()>
     let response = HTTP_CLIENT
         .post(format!("http://localhost:{port}/api/admin/challenge"))
         .header("Authorization", format!("Bearer {auth_key}"))
-        .json(&AddChallenge { url: url.clone(), width: 50, height: 50 })
+        .json(&AddChallenge {
+            url: url.clone(),
+            width: 50,
+            height: 50,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
+        })
         .send()
         .await?;
     assert_eq!(response.status(), StatusCode::CONFLICT);
@@ -119,7 +246,23 @@ async fn remove_challenge_successful(server: TestContext) -> anyhow::Result<()>
     let response = HTTP_CLIENT
         .post(format!("http://localhost:{port}/api/admin/challenge"))
         .bearer_auth(test_helpers::auth_jwt().await)
-        .json(&AddChallenge { url: url.clone(), width: 50, height: 50 })
+        .json(&AddChallenge {
+            url: url.clone(),
+            width: 50,
+            height: 50,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
+        })
         .send()
         .await?;
     assert_eq!(response.status(), StatusCode::OK);
@@ -163,6 +306,18 @@ async fn challenge_endpoint_missing_auth_key(server: TestContext) -> anyhow::Res
             url: format!("https://gotcha-integration.test.com/index.html?nonce={nonce}"),
             width: 50,
             height: 50,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
         })
         .send()
         .await?;
@@ -183,6 +338,18 @@ async fn challenge_endpoint_wrong_auth_key(server: TestContext) -> anyhow::Resul
             url: format!("https://gotcha-integration.test.com/index.html?nonce={nonce}"),
             width: 50,
             height: 50,
+            small_width: None,
+            small_height: None,
+            label: None,
+            logo_url: None,
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
         })
         .send()
         .await?;
@@ -190,3 +357,229 @@ async fn challenge_endpoint_wrong_auth_key(server: TestContext) -> anyhow::Resul
 
     Ok(())
 }
+
+#[integration_test]
+async fn list_challenges_with_health(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let url = format!("http://127.0.0.1:9/index.html?nonce={}", server.test_id());
+    db::insert_challenge(server.pool(), &DbChallenge::new(url.clone())).await?;
+    let config = HealthCheckConfig { failure_threshold: 1, ..Default::default() };
+    health::check_challenge(server.pool(), &url, &config).await?;
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/admin/challenge?limit={}",
+            admin::MAX_PAGE_LIMIT
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let ChallengePage { challenges, total, .. } = response.json().await?;
+    assert!(total >= 1);
+    let challenge = challenges
+        .iter()
+        .find(|c| c.url == url)
+        .expect("challenge should be listed");
+    let health = challenge.health.as_ref().expect("challenge was checked");
+    assert!(!health.healthy);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn list_challenges_paginated(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let nonce = server.test_id();
+    for i in 0..3 {
+        let url = format!("https://gotcha-integration.test.com/{i}/index.html?nonce={nonce}");
+        db::insert_challenge(server.pool(), &DbChallenge::new(url)).await?;
+    }
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/admin/challenge?offset=1&limit=2"
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let page: ChallengePage = response.json().await?;
+    assert_eq!(page.challenges.len(), 2);
+    assert_eq!((page.offset, page.limit), (1, 2));
+    assert!(page.total >= 3);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn add_challenge_small_dimensions(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let nonce = server.test_id();
+    let url = format!("https://gotcha-integration.test.com/index.html?nonce={nonce}");
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{port}/api/admin/challenge"))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&AddChallenge {
+            url: url.clone(),
+            width: 400,
+            height: 600,
+            small_width: Some(300),
+            small_height: Some(450),
+            label: Some("Cup Stack".into()),
+            logo_url: Some("https://gotcha-integration.test.com/logo.png".into()),
+            weight: None,
+            difficulty: None,
+            requires_webgl: false,
+            max_attempts: None,
+            moves_per_attempt: None,
+            traced: false,
+            kind: ChallengeKind::Widget,
+            verify_url: None,
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let challenges = server.db_challenges().await;
+    let challenge = challenges
+        .iter()
+        .find(|c| c.url == url)
+        .expect("challenge should be added");
+    assert_eq!((challenge.width, challenge.height), (400, 600));
+    assert_eq!((challenge.small_width, challenge.small_height), (300, 450));
+    assert_eq!(challenge.label.as_deref(), Some("Cup Stack"));
+
+    Ok(())
+}
+
+#[integration_test]
+async fn update_challenge_successful(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let nonce = server.test_id();
+    let url = format!("https://gotcha-integration.test.com/index.html?nonce={nonce}");
+    db::insert_challenge(server.pool(), &DbChallenge::new(url.clone())).await?;
+
+    let response = HTTP_CLIENT
+        .patch(format!("http://localhost:{port}/api/admin/challenge"))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&UpdateChallenge {
+            url: url.clone(),
+            label: Some(Some("Constellation".into())),
+            small_width: Some(200),
+            enabled: Some(false),
+            ..Default::default()
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let challenges = server.db_challenges().await;
+    let challenge = challenges
+        .iter()
+        .find(|c| c.url == url)
+        .expect("challenge should exist");
+    assert_eq!(challenge.label.as_deref(), Some("Constellation"));
+    assert_eq!(challenge.small_width, 200);
+    assert_eq!(challenge.width, DbChallenge::new(url.clone()).width);
+    assert!(!challenge.enabled);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn update_challenge_invalid_dimensions(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let nonce = server.test_id();
+    let url = format!("https://gotcha-integration.test.com/index.html?nonce={nonce}");
+    db::insert_challenge(server.pool(), &DbChallenge::new(url.clone())).await?;
+
+    for update in [
+        UpdateChallenge { url: url.clone(), width: Some(0), ..Default::default() },
+        UpdateChallenge { url: url.clone(), small_height: Some(40_000), ..Default::default() },
+    ] {
+        let response = HTTP_CLIENT
+            .patch(format!("http://localhost:{port}/api/admin/challenge"))
+            .bearer_auth(test_helpers::auth_jwt().await)
+            .json(&update)
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    Ok(())
+}
+
+#[integration_test]
+async fn update_challenge_attempt_limits(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let nonce = server.test_id();
+    let url = format!("https://gotcha-integration.test.com/index.html?nonce={nonce}");
+    db::insert_challenge(server.pool(), &DbChallenge::new(url.clone())).await?;
+
+    let response = HTTP_CLIENT
+        .patch(format!("http://localhost:{port}/api/admin/challenge"))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&UpdateChallenge {
+            url: url.clone(),
+            max_attempts: Some(5),
+            moves_per_attempt: Some(Some(4)),
+            ..Default::default()
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let challenges = server.db_challenges().await;
+    let challenge = challenges
+        .iter()
+        .find(|c| c.url == url)
+        .expect("challenge should exist");
+    assert_eq!(challenge.max_attempts, 5);
+    assert_eq!(challenge.moves_per_attempt, Some(4));
+
+    for update in [
+        UpdateChallenge { url: url.clone(), max_attempts: Some(0), ..Default::default() },
+        UpdateChallenge { url: url.clone(), max_attempts: Some(11), ..Default::default() },
+        UpdateChallenge {
+            url: url.clone(),
+            moves_per_attempt: Some(Some(0)),
+            ..Default::default()
+        },
+    ] {
+        let response = HTTP_CLIENT
+            .patch(format!("http://localhost:{port}/api/admin/challenge"))
+            .bearer_auth(test_helpers::auth_jwt().await)
+            .json(&update)
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    Ok(())
+}
+
+#[integration_test]
+async fn update_challenge_not_found(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+
+    let response = HTTP_CLIENT
+        .patch(format!("http://localhost:{port}/api/admin/challenge"))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&UpdateChallenge {
+            url: format!(
+                "https://gotcha-integration.test.com/missing.html?nonce={}",
+                server.test_id()
+            ),
+            enabled: Some(false),
+            ..Default::default()
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
@@ -1,12 +1,36 @@
+use base64::prelude::*;
 use gotcha_server::{
-    HTTP_CLIENT,
-    routes::challenge::{
-        AccessibilityRequest, ChallengeResponse, ChallengeResults, GetChallenge, PowResponse,
-        PreAnalysisRequest, ProofOfWork,
+    HTTP_CLIENT, accessibility,
+    analysis::{
+        self,
+        game::{AttemptTrace, GameResult, MoveTrace, TracePoint},
+        proof_of_work::{PowAlgorithm, PowChallenge, SCRYPT_DIFFICULTY, SCRYPT_PUZZLES},
+    },
+    challenges::{
+        Seed, audio,
+        puzzle::{self, Puzzle},
+        remote::{self, CallbackRequest, IssueResponse, VerifyResponse},
+    },
+    db::{
+        self, DbChallenge, DbConsoleChallenge, DbCustomizationProfile, DbEscalation,
+        DbLocalization, DbProofOfWork, DbTheme, DbUpdateApiKey, DbUpdateChallengeCustomization,
+    },
+    health::{self, HealthCheckConfig},
+    routes::{
+        challenge::{
+            AccessibilityChallenge, AccessibilityRequest, AudioChallenge, ChallengeInstance,
+            ChallengeResponse, ChallengeResults, GetChallenge, InstanceRequest, InvisibleRequest,
+            PowResponse, PreAnalysisRequest, PreAnalysisResponse, ProofOfWork, PuzzleChallenge,
+            PuzzleRequest, ThemeMode,
+        },
+        verification::VerificationResponse,
     },
     tokens::{
-        TimeClaims,
-        response::{JWT_RESPONSE_ALGORITHM, ResponseClaims},
+        self, TimeClaims,
+        accessibility::QuestionClaims,
+        game::GameClaims,
+        pow_challenge,
+        response::{ChallengeType, JWT_RESPONSE_ALGORITHM, ResponseClaims},
     },
 };
 use gotcha_server_macros::integration_test;
@@ -43,6 +67,233 @@ async fn get_challenge_fails(server: TestContext) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[integration_test]
+async fn disabled_challenge_is_not_served(server: TestContext) -> anyhow::Result<()> {
+    let pool = server.pool();
+    let site_key = server.db_api_site_key().await;
+    let url = format!(
+        "https://gotcha-integration.test.com/index.html?nonce={}",
+        server.test_id()
+    );
+    db::insert_challenge(pool, &DbChallenge::new(url.clone())).await?;
+
+    let challenges = db::fetch_challenges_with_customization(pool, &site_key).await?;
+    assert!(challenges.iter().any(|c| c.url == url));
+
+    db::upsert_console_challenge(
+        pool,
+        &server.db_console().await,
+        &DbConsoleChallenge { challenge_url: url.clone(), enabled: false, weight: None },
+    )
+    .await?;
+    let challenges = db::fetch_challenges_with_customization(pool, &site_key).await?;
+    assert!(!challenges.iter().any(|c| c.url == url));
+
+    Ok(())
+}
+
+#[integration_test]
+async fn customization_resolves_profile_then_console(server: TestContext) -> anyhow::Result<()> {
+    let pool = server.pool();
+    let console_id = server.db_console().await;
+    let site_key = server.db_api_site_key().await;
+    let url = format!(
+        "https://gotcha-integration.test.com/index.html?nonce={}",
+        server.test_id()
+    );
+    db::insert_challenge(
+        pool,
+        &DbChallenge {
+            logo_url: Some("challenge.png".into()),
+            ..DbChallenge::new(url.clone())
+        },
+    )
+    .await?;
+    db::update_challenge_customization(
+        pool,
+        &console_id,
+        &DbUpdateChallengeCustomization {
+            width: Some(300),
+            height: Some(400),
+            small_width: None,
+            small_height: None,
+            logo_url: None,
+            exclude_webgl_on_low_end: None,
+            escalate_difficulty: None,
+            accessibility_enabled: None,
+            theme: None,
+            localization: None,
+            proof_of_work: None,
+        },
+    )
+    .await?;
+    let profile = DbCustomizationProfile {
+        id: uuid::Uuid::new_v4(),
+        label: "checkout".into(),
+        width: Some(420),
+        height: None,
+        small_width: None,
+        small_height: None,
+        logo_url: Some("checkout.png".into()),
+    };
+    db::insert_customization_profile(pool, &console_id, &profile).await?;
+    db::update_api_key(
+        pool,
+        site_key.as_str(),
+        &console_id,
+        DbUpdateApiKey {
+            label: None,
+            invisible: None,
+            score_threshold: None,
+            customization_profile_id: Some(Some(profile.id)),
+        },
+    )
+    .await?;
+
+    let challenges = db::fetch_challenges_with_customization(pool, &site_key).await?;
+    let challenge = challenges
+        .iter()
+        .find(|c| c.url == url)
+        .expect("challenge is served");
+    assert_eq!(challenge.width, 420);
+    assert_eq!(challenge.height, 400);
+    assert_eq!(challenge.logo_url.as_deref(), Some("checkout.png"));
+
+    Ok(())
+}
+
+#[integration_test]
+async fn get_challenge_with_theme_and_localization(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let pool = server.pool();
+    let site_key = server.db_api_site_key().await;
+    let url = format!(
+        "https://gotcha-integration.test.com/index.html?nonce={}",
+        server.test_id()
+    );
+    db::insert_challenge(pool, &DbChallenge::new(url)).await?;
+    let strings = [("start".to_string(), "Klicken zum Starten!".to_string())].into();
+    db::update_challenge_customization(
+        pool,
+        &server.db_console().await,
+        &DbUpdateChallengeCustomization {
+            width: None,
+            height: None,
+            small_width: None,
+            small_height: None,
+            logo_url: None,
+            exclude_webgl_on_low_end: None,
+            escalate_difficulty: None,
+            accessibility_enabled: None,
+            theme: Some(DbTheme {
+                mode: "dark",
+                primary_color: Some("#7c3aed"),
+                background_color: None,
+                text_color: None,
+                corner_radius: Some(8),
+                font_family: Some("Inter"),
+            }),
+            localization: Some(DbLocalization { locale: Some("de"), strings: &strings }),
+            proof_of_work: None,
+        },
+    )
+    .await?;
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge?site_key={site_key}"
+        ))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let challenge: GetChallenge = response.json().await?;
+    assert_eq!(challenge.theme.mode, ThemeMode::Dark);
+    assert_eq!(challenge.theme.primary_color.as_deref(), Some("#7c3aed"));
+    assert_eq!(challenge.theme.corner_radius, Some(8));
+    assert_eq!(challenge.localization.locale.as_deref(), Some("de"));
+    assert_eq!(challenge.localization.strings, strings);
+
+    Ok(())
+}
+
+/// Serves a stub challenge host and returns its port.
+async fn serve_stub_challenges() -> anyhow::Result<u16> {
+    let app = axum::Router::new().route("/ok/index.html", axum::routing::get(|| async { "ok" }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    Ok(port)
+}
+
+#[integration_test]
+async fn unhealthy_challenge_is_not_served(server: TestContext) -> anyhow::Result<()> {
+    let pool = server.pool();
+    let site_key = server.db_api_site_key().await;
+    let stub_port = serve_stub_challenges().await?;
+    let nonce = server.test_id();
+    let healthy = format!("http://127.0.0.1:{stub_port}/ok/index.html?nonce={nonce}");
+    let broken = format!("http://127.0.0.1:{stub_port}/broken/index.html?nonce={nonce}");
+    // outweighs every other challenge, it would be served most of the time if selectable
+    let heaviest = |url: &str| DbChallenge { weight: i16::MAX, ..DbChallenge::new(url.into()) };
+    db::insert_challenge(pool, &heaviest(&healthy)).await?;
+    db::insert_challenge(pool, &heaviest(&broken)).await?;
+    let config = HealthCheckConfig { failure_threshold: 2, ..Default::default() };
+
+    let health = health::check_challenge(pool, &healthy, &config).await?;
+    assert!(health.healthy);
+    assert_eq!(health.status, Some(200));
+    assert!(health.latency_ms.is_some());
+
+    // a single failure is tolerated
+    let health = health::check_challenge(pool, &broken, &config).await?;
+    assert!(health.healthy);
+    assert_eq!(health.status, Some(404));
+    assert_eq!(health.consecutive_failures, 1);
+    let health = health::check_challenge(pool, &broken, &config).await?;
+    assert!(!health.healthy);
+    assert_eq!(health.consecutive_failures, 2);
+
+    let challenges = db::fetch_challenges_with_customization(pool, &site_key).await?;
+    assert!(challenges.iter().any(|c| c.url == healthy && c.healthy));
+    assert!(challenges.iter().any(|c| c.url == broken && !c.healthy));
+
+    let mut served = Vec::new();
+    for _ in 0..20 {
+        let response = HTTP_CLIENT
+            .get(format!(
+                "http://localhost:{}/api/challenge?site_key={site_key}",
+                server.port()
+            ))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        served.push(response.json::<GetChallenge>().await?.url.to_string());
+    }
+    // leaves the selection of other tests unaffected
+    db::delete_challenge(pool, &healthy).await?;
+    db::delete_challenge(pool, &broken).await?;
+    assert!(!served.contains(&broken), "{served:?}");
+    assert!(served.contains(&healthy), "{served:?}");
+
+    Ok(())
+}
+
+#[integration_test]
+async fn unreachable_challenge_is_unhealthy(server: TestContext) -> anyhow::Result<()> {
+    let pool = server.pool();
+    // nothing listens on the discard port
+    let url = format!("http://127.0.0.1:9/index.html?nonce={}", server.test_id());
+    db::insert_challenge(pool, &DbChallenge::new(url.clone())).await?;
+    let config = HealthCheckConfig { failure_threshold: 1, ..Default::default() };
+
+    let health = health::check_challenge(pool, &url, &config).await?;
+    assert!(!health.healthy);
+    assert_eq!(health.status, None);
+    assert!(health.error.is_some());
+
+    Ok(())
+}
+
 // This test overtime gets more meaningless and untestable
 #[integration_test]
 async fn process_successful_challenge(server: TestContext) -> anyhow::Result<()> {
@@ -58,6 +309,9 @@ async fn process_successful_challenge(server: TestContext) -> anyhow::Result<()>
             hostname: Host::parse("website-integration.test.com")?,
             challenge: Url::parse("https://gotcha-integration.test.com/im-not-a-robot/index.html")?,
             interactions: vec![],
+            attempts: None,
+            game: None,
+            seed_token: None,
         })
         .send()
         .await?;
@@ -90,6 +344,9 @@ async fn process_failed_challenge(server: TestContext) -> anyhow::Result<()> {
             hostname: Host::parse("website-integration.test.com")?,
             challenge: Url::parse("https://gotcha-integration.test.com/im-not-a-robot/index.html")?,
             interactions: vec![],
+            attempts: None,
+            game: None,
+            seed_token: None,
         })
         .send()
         .await?;
@@ -108,6 +365,292 @@ async fn process_failed_challenge(server: TestContext) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[integration_test]
+async fn failures_escalate_on_the_server(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let pool = server.pool();
+    let site_key = server.db_api_site_key().await;
+    let addr = [127, 0, 0, 1].into();
+    assert_eq!(db::fetch_escalation(pool, &site_key, addr).await?, None);
+
+    for _ in 0..2 {
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/challenge/process"))
+            .json(&ChallengeResults {
+                success: false,
+                site_key: site_key.clone(),
+                hostname: Host::parse("website-integration.test.com")?,
+                challenge: Url::parse(
+                    "https://gotcha-integration.test.com/im-not-a-robot/index.html",
+                )?,
+                interactions: vec![],
+                attempts: None,
+                game: None,
+                seed_token: None,
+            })
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    // a pre analysis can raise the difficulty but not lower it
+    db::record_min_difficulty(pool, &site_key, addr, 3).await?;
+    db::record_min_difficulty(pool, &site_key, addr, 1).await?;
+
+    assert_eq!(
+        db::fetch_escalation(pool, &site_key, addr).await?,
+        Some(DbEscalation { failed_attempts: 2, min_difficulty: 3 })
+    );
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_challenge_scores_attempts(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+    let url = format!(
+        "https://gotcha-integration.test.com/index.html?nonce={}",
+        server.test_id()
+    );
+    db::insert_challenge(
+        server.pool(),
+        &DbChallenge { max_attempts: 5, ..DbChallenge::new(url.clone()) },
+    )
+    .await?;
+
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{port}/api/challenge/process"))
+        .json(&ChallengeResults {
+            success: true,
+            site_key,
+            hostname: Host::parse("website-integration.test.com")?,
+            challenge: Url::parse(&url)?,
+            interactions: vec![],
+            attempts: Some(3),
+            game: Some(played_game(1)),
+            seed_token: Some(seed_token(&enc_key, &url, 1)?),
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let ChallengeResponse { token } = response.json().await?;
+    let token_data = jsonwebtoken::decode::<TimeClaims<ResponseClaims>>(
+        &token,
+        &DecodingKey::from_base64_secret(enc_key.as_str())?,
+        &Validation::new(JWT_RESPONSE_ALGORITHM),
+    )?;
+    assert_eq!(token_data.claims.other.score, 0.75);
+
+    // leaving the attempts out scores as the last one allowed
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{port}/api/challenge/process"))
+        .json(&ChallengeResults {
+            success: true,
+            site_key: server.db_api_site_key().await,
+            hostname: Host::parse("website-integration.test.com")?,
+            challenge: Url::parse(&url)?,
+            interactions: vec![],
+            attempts: None,
+            game: Some(played_game(2)),
+            seed_token: Some(seed_token(&enc_key, &url, 2)?),
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let ChallengeResponse { token } = response.json().await?;
+    let token_data = jsonwebtoken::decode::<TimeClaims<ResponseClaims>>(
+        &token,
+        &DecodingKey::from_base64_secret(enc_key.as_str())?,
+        &Validation::new(JWT_RESPONSE_ALGORITHM),
+    )?;
+    assert_eq!(token_data.claims.other.score, 0.5);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn untraced_challenges_pass_without_a_game(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let pool = server.pool();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    let mut scores = Vec::new();
+    for traced in [false, true] {
+        let url = format!(
+            "https://gotcha-integration.test.com/index.html?traced={traced}&nonce={}",
+            server.test_id()
+        );
+        db::insert_challenge(
+            pool,
+            &DbChallenge { traced, ..DbChallenge::new(url.clone()) },
+        )
+        .await?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/challenge/process"))
+            .json(&ChallengeResults {
+                success: true,
+                site_key: site_key.clone(),
+                hostname: Host::parse("website-integration.test.com")?,
+                challenge: Url::parse(&url)?,
+                interactions: vec![],
+                attempts: Some(1),
+                game: None,
+                seed_token: None,
+            })
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let ChallengeResponse { token } = response.json().await?;
+        let token_data = jsonwebtoken::decode::<TimeClaims<ResponseClaims>>(
+            &token,
+            &DecodingKey::from_base64_secret(enc_key.as_str())?,
+            &Validation::new(JWT_RESPONSE_ALGORITHM),
+        )?;
+        scores.push(token_data.claims.other.score);
+
+        if !traced {
+            // passing, so it doesn't escalate the next challenges
+            assert_eq!(
+                db::fetch_escalation(pool, &site_key, [127, 0, 0, 1].into()).await?,
+                None
+            );
+        }
+    }
+    assert_eq!(scores, [1., analysis::game::UNTRACED_SCORE]);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_challenge_scores_game_traces(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    // a scripted throw, dragged in a perfectly straight line
+    let url = "https://gotcha-integration.test.com/cup-stack/index.html";
+    let response = HTTP_CLIENT
+        .post(format!("http://localhost:{port}/api/challenge/process"))
+        .json(&serde_json::json!({
+            "success": true,
+            "site_key": site_key,
+            "hostname": "website-integration.test.com",
+            "challenge": url,
+            "attempts": 1,
+            "seed_token": seed_token(&enc_key, url, 7)?,
+            "game": {
+                "solve_time_ms": 3200,
+                "seed": 7,
+                "traces": [{
+                    "success": true,
+                    "moves": [{
+                        "path": [
+                            { "x": 100.0, "y": 300.0, "t_ms": 0 },
+                            { "x": 100.0, "y": 250.0, "t_ms": 16 },
+                            { "x": 100.0, "y": 200.0, "t_ms": 32 }
+                        ],
+                        "impulse": [0.0, 0.01, -0.04]
+                    }]
+                }]
+            }
+        }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let ChallengeResponse { token } = response.json().await?;
+    let token_data = jsonwebtoken::decode::<TimeClaims<ResponseClaims>>(
+        &token,
+        &DecodingKey::from_base64_secret(enc_key.as_str())?,
+        &Validation::new(JWT_RESPONSE_ALGORITHM),
+    )?;
+    assert_eq!(token_data.claims.other.score, 0.);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn games_are_scored_once_on_the_served_seed(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge?site_key={site_key}"
+        ))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let challenge: GetChallenge = response.json().await?;
+    let game = challenge
+        .game
+        .expect("a seed is issued with a valid site key");
+
+    let mut scores = Vec::new();
+    for seed in [game.seed + 1, game.seed, game.seed] {
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/challenge/process"))
+            .json(&ChallengeResults {
+                success: true,
+                site_key: site_key.clone(),
+                hostname: Host::parse("website-integration.test.com")?,
+                challenge: challenge.url.clone(),
+                interactions: vec![],
+                attempts: Some(1),
+                game: Some(played_game(seed)),
+                seed_token: Some(game.token.clone()),
+            })
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let ChallengeResponse { token } = response.json().await?;
+        let token_data = jsonwebtoken::decode::<TimeClaims<ResponseClaims>>(
+            &token,
+            &DecodingKey::from_base64_secret(enc_key.as_str())?,
+            &Validation::new(JWT_RESPONSE_ALGORITHM),
+        )?;
+        scores.push(token_data.claims.other.score);
+    }
+    // another seed, then the served one, then the served one replayed
+    assert_eq!(scores, [0., 1., 0.]);
+
+    Ok(())
+}
+
+/// A game played like a human would on `seed`, a single attempt with a wobbly throw.
+fn played_game(seed: u64) -> GameResult {
+    let path = [(100., 300.), (102., 280.), (99., 250.), (105., 200.)]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (x, y))| TracePoint { x, y, t_ms: i as u32 * 16 })
+        .collect();
+    GameResult {
+        solve_time_ms: 3_200,
+        seed: Some(seed),
+        traces: vec![AttemptTrace {
+            success: true,
+            moves: vec![MoveTrace { path, impulse: Some([0., 0.01, -0.04]), keys: vec![] }],
+        }],
+    }
+}
+
+/// Token of `seed` as served with the challenge at `url` to the tests.
+fn seed_token(
+    enc_key: &gotcha_server::encodings::Base64,
+    url: &str,
+    seed: u64,
+) -> anyhow::Result<String> {
+    let claims = GameClaims { challenge: url.into(), seed, addr: [127, 0, 0, 1].into() };
+    Ok(tokens::game::encode(claims, enc_key)?)
+}
+
 #[integration_test]
 async fn process_challenge_with_invalid_secret(server: TestContext) -> anyhow::Result<()> {
     let port = server.port();
@@ -143,7 +686,7 @@ async fn process_pre_analysis_fails_on_invalid_proof_of_work(
             site_key,
             hostname: Host::parse("website-integration.test.com")?,
             interactions: vec![],
-            proof_of_work: ProofOfWork { challenge: "".into(), solution: 0 },
+            proof_of_work: ProofOfWork { challenge: "".into(), solutions: vec![0] },
         })
         .send()
         .await?;
@@ -176,7 +719,7 @@ async fn process_pre_analysis_fails_on_proof_of_work_failed(
             site_key,
             hostname: Host::parse("website-integration.test.com")?,
             interactions: vec![],
-            proof_of_work: ProofOfWork { challenge: pow.token, solution: 0 },
+            proof_of_work: ProofOfWork { challenge: pow.token, solutions: vec![0] },
         })
         .send()
         .await?;
@@ -185,6 +728,34 @@ async fn process_pre_analysis_fails_on_proof_of_work_failed(
     Ok(())
 }
 
+#[integration_test]
+async fn process_pre_analysis_accepts_a_single_solution(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    // as sent by widgets from before sub-puzzles, solving a single one
+    let challenge = PowChallenge::random(2);
+    let solution = challenge.solve()[0];
+    let token = pow_challenge::encode(challenge, &enc_key)?;
+
+    let response = HTTP_CLIENT
+        .post(format!(
+            "http://localhost:{port}/api/challenge/process-pre-analysis"
+        ))
+        .json(&serde_json::json!({
+            "site_key": site_key,
+            "hostname": "website-integration.test.com",
+            "interactions": [],
+            "proof_of_work": { "challenge": token, "solution": solution },
+        }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    Ok(())
+}
+
 #[integration_test]
 async fn process_accessibility_fails_on_invalid_proof_of_work(
     server: TestContext,
@@ -199,7 +770,9 @@ async fn process_accessibility_fails_on_invalid_proof_of_work(
         .json(&AccessibilityRequest {
             site_key,
             hostname: Host::parse("website-integration.test.com")?,
-            proof_of_work: ProofOfWork { challenge: "".into(), solution: 0 },
+            proof_of_work: ProofOfWork { challenge: "".into(), solutions: vec![0] },
+            question: "".into(),
+            answer: "".into(),
         })
         .send()
         .await?;
@@ -231,7 +804,9 @@ async fn process_accessibility_fails_on_proof_of_work_failed(
         .json(&AccessibilityRequest {
             site_key,
             hostname: Host::parse("website-integration.test.com")?,
-            proof_of_work: ProofOfWork { challenge: pow.token, solution: 0 },
+            proof_of_work: ProofOfWork { challenge: pow.token, solutions: vec![0] },
+            question: "".into(),
+            answer: "".into(),
         })
         .send()
         .await?;
@@ -241,34 +816,825 @@ async fn process_accessibility_fails_on_proof_of_work_failed(
 }
 
 #[integration_test]
-async fn get_proof_of_work_challenge(server: TestContext) -> anyhow::Result<()> {
+async fn get_accessibility_challenge(server: TestContext) -> anyhow::Result<()> {
     let port = server.port();
     let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
 
     let response = HTTP_CLIENT
         .get(format!(
-            "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+            "http://localhost:{port}/api/challenge/accessibility?site_key={site_key}"
         ))
         .send()
         .await?;
     assert_eq!(response.status(), StatusCode::OK);
 
-    let response: PowResponse = response.json().await?;
-    assert!(!response.token.is_empty());
+    let challenge: AccessibilityChallenge = response.json().await?;
+    assert!(challenge.question.ends_with('?'));
+    let claims = tokens::accessibility::decode(&challenge.token, &enc_key)?;
+    assert!(claims.addr.is_loopback());
 
     Ok(())
 }
 
 #[integration_test]
-async fn get_proof_of_work_challenge_no_site_key(server: TestContext) -> anyhow::Result<()> {
+async fn get_audio_challenge(server: TestContext) -> anyhow::Result<()> {
     let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
 
     let response = HTTP_CLIENT
         .get(format!(
-            "http://localhost:{port}/api/challenge/proof-of-work"
+            "http://localhost:{port}/api/challenge/audio?site_key={site_key}"
         ))
         .send()
         .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let challenge: AudioChallenge = response.json().await?;
+    let wav = BASE64_STANDARD.decode(challenge.audio)?;
+    assert!(!audio::decode_wav(&wav)?.is_empty());
+    let claims = tokens::accessibility::decode(&challenge.token, &enc_key)?;
+    assert!(claims.addr.is_loopback());
+
+    Ok(())
+}
+
+/// Answers a question issued with a known answer, as the answer of a served one can't be read.
+async fn answer_accessibility(
+    server: &gotcha_server::test_helpers::TestContext,
+    answer: &str,
+) -> anyhow::Result<reqwest::Response> {
+    let question = known_question(server).await?;
+    answer_question(server, &question, answer).await
+}
+
+/// Token of a question answered with "21".
+async fn known_question(
+    server: &gotcha_server::test_helpers::TestContext,
+) -> anyhow::Result<String> {
+    let enc_key = server.db_enconding_key().await;
+    let nonce = rand::random();
+    Ok(tokens::accessibility::encode(
+        QuestionClaims {
+            nonce,
+            addr: "127.0.0.1".parse()?,
+            answer_hash: accessibility::answer_hash("21", nonce, &enc_key),
+        },
+        &enc_key,
+    )?)
+}
+
+async fn answer_question(
+    server: &gotcha_server::test_helpers::TestContext,
+    question: &str,
+    answer: &str,
+) -> anyhow::Result<reqwest::Response> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    let pow: PowResponse = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let solutions = pow_challenge::decode(&pow.token, enc_key.as_str())?.solve();
+
+    Ok(HTTP_CLIENT
+        .post(format!(
+            "http://127.0.0.1:{port}/api/challenge/process-accessibility"
+        ))
+        .json(&AccessibilityRequest {
+            site_key,
+            hostname: Host::parse("website-integration.test.com")?,
+            proof_of_work: ProofOfWork { challenge: pow.token, solutions },
+            question: question.into(),
+            answer: answer.into(),
+        })
+        .send()
+        .await?)
+}
+
+#[integration_test]
+async fn process_accessibility_right_answer_scores_lower(
+    server: TestContext,
+) -> anyhow::Result<()> {
+    let enc_key = server.db_enconding_key().await;
+
+    let response = answer_accessibility(&server, "Twenty-one").await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let PreAnalysisResponse::Success { response } = response.json().await? else {
+        panic!("expected the right answer to succeed");
+    };
+    let claims = tokens::response::decode(&response.token, &enc_key)?;
+    assert_eq!(claims.other.score, 0.4);
+    assert_eq!(claims.other.challenge_type, ChallengeType::Accessibility);
+
+    // below the threshold of a default site key
+    let verification: VerificationResponse = HTTP_CLIENT
+        .post(format!("http://localhost:{}/api/siteverify", server.port()))
+        .form(&[
+            ("secret", server.db_api_secret().await.as_str()),
+            ("response", &response.token),
+        ])
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert!(!verification.success);
+    assert_eq!(verification.score, Some(0.4));
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_accessibility_wrong_answer_fails(server: TestContext) -> anyhow::Result<()> {
+    let response = answer_accessibility(&server, "22").await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response: PreAnalysisResponse = response.json().await?;
+    assert!(matches!(response, PreAnalysisResponse::Failure { .. }));
+
+    Ok(())
+}
+
+#[integration_test]
+async fn accessibility_questions_are_answered_once(server: TestContext) -> anyhow::Result<()> {
+    let question = known_question(&server).await?;
+
+    let response = answer_question(&server, &question, "22").await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: PreAnalysisResponse = response.json().await?;
+    assert!(matches!(response, PreAnalysisResponse::Failure { .. }));
+
+    // the right answer is too late once the question was answered
+    let response = answer_question(&server, &question, "21").await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn accessibility_fails_when_disabled(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    db::update_challenge_customization(
+        server.pool(),
+        &server.db_console().await,
+        &DbUpdateChallengeCustomization {
+            width: None,
+            height: None,
+            small_width: None,
+            small_height: None,
+            logo_url: None,
+            exclude_webgl_on_low_end: None,
+            escalate_difficulty: None,
+            accessibility_enabled: Some(false),
+            theme: None,
+            localization: None,
+            proof_of_work: None,
+        },
+    )
+    .await?;
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/accessibility?site_key={site_key}"
+        ))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = answer_accessibility(&server, "21").await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn accessibility_is_rate_limited(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+
+    let mut statuses = vec![];
+    for _ in 0..11 {
+        let response = HTTP_CLIENT
+            .get(format!(
+                "http://localhost:{port}/api/challenge/accessibility?site_key={site_key}"
+            ))
+            .send()
+            .await?;
+        statuses.push(response.status());
+    }
+    assert!(statuses[..10].iter().all(|s| *s == StatusCode::OK));
+    assert_eq!(statuses[10], StatusCode::TOO_MANY_REQUESTS);
+
+    Ok(())
+}
+
+/// Fetches a puzzle and works out where its piece fits, as the server does from the nonce.
+async fn fetch_puzzle(
+    server: &gotcha_server::test_helpers::TestContext,
+) -> anyhow::Result<(PuzzleChallenge, u32)> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    let challenge: PuzzleChallenge = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/puzzle?site_key={site_key}"
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let claims = tokens::puzzle::decode(&challenge.token, &enc_key)?;
+    let target_x = Puzzle::generate(Seed::derive(claims.nonce, &enc_key)).target_x;
+
+    Ok((challenge, target_x))
+}
+
+async fn answer_puzzle(
+    server: &gotcha_server::test_helpers::TestContext,
+    puzzle: &str,
+    answer_x: u32,
+) -> anyhow::Result<reqwest::Response> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    let pow: PowResponse = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let solutions = pow_challenge::decode(&pow.token, enc_key.as_str())?.solve();
+
+    Ok(HTTP_CLIENT
+        .post(format!(
+            "http://127.0.0.1:{port}/api/challenge/process-puzzle"
+        ))
+        .json(&PuzzleRequest {
+            site_key,
+            hostname: Host::parse("website-integration.test.com")?,
+            proof_of_work: ProofOfWork { challenge: pow.token, solutions },
+            puzzle: puzzle.into(),
+            answer_x,
+        })
+        .send()
+        .await?)
+}
+
+#[integration_test]
+async fn get_puzzle_challenge(server: TestContext) -> anyhow::Result<()> {
+    let (challenge, _) = fetch_puzzle(&server).await?;
+
+    let background = image::load_from_memory(&BASE64_STANDARD.decode(challenge.background)?)?;
+    assert_eq!(background.width(), puzzle::WIDTH);
+    assert_eq!(background.height(), puzzle::HEIGHT);
+    assert!(challenge.piece_y + puzzle::PIECE_SIZE <= puzzle::HEIGHT);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn puzzles_are_rate_limited(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+
+    let mut statuses = vec![];
+    for _ in 0..21 {
+        let response = HTTP_CLIENT
+            .get(format!(
+                "http://localhost:{port}/api/challenge/puzzle?site_key={site_key}"
+            ))
+            .send()
+            .await?;
+        statuses.push(response.status());
+    }
+    assert!(statuses[..20].iter().all(|s| *s == StatusCode::OK));
+    assert_eq!(statuses[20], StatusCode::TOO_MANY_REQUESTS);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_puzzle_solved_once(server: TestContext) -> anyhow::Result<()> {
+    let enc_key = server.db_enconding_key().await;
+    let (challenge, target_x) = fetch_puzzle(&server).await?;
+
+    let response = answer_puzzle(&server, &challenge.token, target_x + 2).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let PreAnalysisResponse::Success { response } = response.json().await? else {
+        panic!("expected the piece in its hole to succeed");
+    };
+    let claims = tokens::response::decode(&response.token, &enc_key)?;
+    assert_eq!(claims.other.score, 0.4);
+    assert_eq!(claims.other.challenge_type, ChallengeType::Puzzle);
+
+    let response = answer_puzzle(&server, &challenge.token, target_x).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_puzzle_misplaced_piece_fails(server: TestContext) -> anyhow::Result<()> {
+    let (challenge, target_x) = fetch_puzzle(&server).await?;
+
+    let response = answer_puzzle(&server, &challenge.token, target_x - 20).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: PreAnalysisResponse = response.json().await?;
+    assert!(matches!(response, PreAnalysisResponse::Failure { .. }));
+
+    Ok(())
+}
+
+/// Serves a remote challenge asking for the first 4 characters of its seed, checking the callbacks
+/// are signed with `secret`. Responds with its verify URL.
+async fn spawn_remote_challenge(secret: &'static str) -> anyhow::Result<String> {
+    use axum::{Json, http::HeaderMap, routing::post};
+
+    let callback = async move |headers: HeaderMap, body: String| {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+        };
+        let timestamp: i64 = header(remote::TIMESTAMP_HEADER).parse().unwrap_or_default();
+        if header(remote::SIGNATURE_HEADER) != remote::sign(secret, timestamp, &body) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(Json(match serde_json::from_str(&body).unwrap() {
+            CallbackRequest::Issue { .. } => {
+                serde_json::to_value(IssueResponse { data: "type the seed".into() }).unwrap()
+            }
+            CallbackRequest::Verify { seed, answer, .. } => {
+                let score = match answer.as_str() == Some(&seed[..4]) {
+                    true => 0.9,
+                    false => 0.,
+                };
+                serde_json::to_value(VerifyResponse { score }).unwrap()
+            }
+        }))
+    };
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(
+        axum::serve(
+            listener,
+            axum::Router::new().route("/verify", post(callback)),
+        )
+        .into_future(),
+    );
+    Ok(format!("http://{addr}/verify"))
+}
+
+async fn insert_registered_challenge(
+    server: &gotcha_server::test_helpers::TestContext,
+    kind: &str,
+    verify_url: Option<String>,
+    verify_secret: Option<&str>,
+) -> anyhow::Result<String> {
+    let url = format!(
+        "https://gotcha-integration.test.com/{kind}.html?nonce={}",
+        server.test_id()
+    );
+    db::insert_challenge(
+        server.pool(),
+        &DbChallenge {
+            kind: kind.into(),
+            verify_url,
+            verify_secret: verify_secret.map(Into::into),
+            ..DbChallenge::new(url.clone())
+        },
+    )
+    .await?;
+    Ok(url)
+}
+
+async fn fetch_instance(
+    server: &gotcha_server::test_helpers::TestContext,
+    challenge: &str,
+) -> anyhow::Result<reqwest::Response> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+
+    Ok(HTTP_CLIENT
+        .get(format!("http://localhost:{port}/api/challenge/instance"))
+        .query(&[("site_key", site_key.as_str()), ("challenge", challenge)])
+        .send()
+        .await?)
+}
+
+async fn answer_instance(
+    server: &gotcha_server::test_helpers::TestContext,
+    instance: &str,
+    answer: serde_json::Value,
+) -> anyhow::Result<reqwest::Response> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+
+    let pow: PowResponse = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let solutions = pow_challenge::decode(&pow.token, enc_key.as_str())?.solve();
+
+    Ok(HTTP_CLIENT
+        .post(format!(
+            "http://127.0.0.1:{port}/api/challenge/process-instance"
+        ))
+        .json(&InstanceRequest {
+            site_key,
+            hostname: Host::parse("website-integration.test.com")?,
+            proof_of_work: ProofOfWork { challenge: pow.token, solutions },
+            instance: instance.into(),
+            answer,
+        })
+        .send()
+        .await?)
+}
+
+#[integration_test]
+async fn process_remote_instance_answered_once(server: TestContext) -> anyhow::Result<()> {
+    const SECRET: &str = "remote-secret";
+    let enc_key = server.db_enconding_key().await;
+    let verify_url = spawn_remote_challenge(SECRET).await?;
+    let url =
+        insert_registered_challenge(&server, "remote", Some(verify_url), Some(SECRET)).await?;
+
+    let response = fetch_instance(&server, &url).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let instance: ChallengeInstance = response.json().await?;
+    assert_eq!(instance.data, "type the seed");
+    let claims = tokens::instance::decode(&instance.token, &enc_key)?;
+    let seed = Seed::derive(claims.nonce, &enc_key).to_hex();
+
+    let response = answer_instance(&server, &instance.token, seed[..4].into()).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let PreAnalysisResponse::Success { response } = response.json().await? else {
+        panic!("expected the right answer to succeed");
+    };
+    let claims = tokens::response::decode(&response.token, &enc_key)?;
+    assert_eq!(claims.other.score, 0.9);
+    assert_eq!(claims.other.challenge_type, ChallengeType::Remote);
+
+    let response = answer_instance(&server, &instance.token, seed[..4].into()).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_remote_instance_wrong_answer_fails(server: TestContext) -> anyhow::Result<()> {
+    const SECRET: &str = "remote-secret";
+    let verify_url = spawn_remote_challenge(SECRET).await?;
+    let url =
+        insert_registered_challenge(&server, "remote", Some(verify_url), Some(SECRET)).await?;
+
+    let instance: ChallengeInstance = fetch_instance(&server, &url).await?.json().await?;
+    let response = answer_instance(&server, &instance.token, "nope".into()).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: PreAnalysisResponse = response.json().await?;
+    assert!(matches!(response, PreAnalysisResponse::Failure { .. }));
+
+    Ok(())
+}
+
+#[integration_test]
+async fn remote_instance_with_wrong_secret_fails(server: TestContext) -> anyhow::Result<()> {
+    let verify_url = spawn_remote_challenge("remote-secret").await?;
+    let url =
+        insert_registered_challenge(&server, "remote", Some(verify_url), Some("other")).await?;
+
+    let response = fetch_instance(&server, &url).await?;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_puzzle_instance(server: TestContext) -> anyhow::Result<()> {
+    let enc_key = server.db_enconding_key().await;
+    let url = insert_registered_challenge(&server, "puzzle", None, None).await?;
+
+    let instance: ChallengeInstance = fetch_instance(&server, &url).await?.json().await?;
+    let data: puzzle::PuzzleData = serde_json::from_value(instance.data)?;
+    assert!(data.piece_y > 0);
+    let claims = tokens::instance::decode(&instance.token, &enc_key)?;
+    let target_x = Puzzle::generate(Seed::derive(claims.nonce, &enc_key)).target_x;
+
+    let response = answer_instance(
+        &server,
+        &instance.token,
+        serde_json::json!({ "x": target_x }),
+    )
+    .await?;
+    let PreAnalysisResponse::Success { response } = response.json().await? else {
+        panic!("expected the piece in its hole to succeed");
+    };
+    let claims = tokens::response::decode(&response.token, &enc_key)?;
+    assert_eq!(claims.other.score, 0.4);
+    assert_eq!(claims.other.challenge_type, ChallengeType::Puzzle);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn puzzle_instances_are_rate_limited(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let url = insert_registered_challenge(&server, "puzzle", None, None).await?;
+
+    let mut statuses = vec![];
+    for _ in 0..20 {
+        statuses.push(fetch_instance(&server, &url).await?.status());
+    }
+    assert!(statuses.iter().all(|s| *s == StatusCode::OK));
+
+    // the puzzle route shares the limit
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/puzzle?site_key={site_key}"
+        ))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let response = fetch_instance(&server, &url).await?;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn widget_challenges_have_no_instances(server: TestContext) -> anyhow::Result<()> {
+    let url = insert_registered_challenge(&server, "widget", None, None).await?;
+
+    let response = fetch_instance(&server, &url).await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn get_proof_of_work_challenge(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+        ))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response: PowResponse = response.json().await?;
+    assert!(!response.token.is_empty());
+
+    Ok(())
+}
+
+/// Switches the console of the test site key to scrypt proofs of work of 1 MiB.
+async fn use_scrypt(server: &gotcha_server::test_helpers::TestContext) -> anyhow::Result<()> {
+    db::update_challenge_customization(
+        server.pool(),
+        &server.db_console().await,
+        &DbUpdateChallengeCustomization {
+            width: None,
+            height: None,
+            small_width: None,
+            small_height: None,
+            logo_url: None,
+            exclude_webgl_on_low_end: None,
+            escalate_difficulty: None,
+            accessibility_enabled: None,
+            theme: None,
+            localization: None,
+            proof_of_work: Some(DbProofOfWork { algorithm: "scrypt", memory_kib: 1024 }),
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+#[integration_test]
+async fn proof_of_work_follows_console_algorithm(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+    use_scrypt(&server).await?;
+
+    let pow: PowResponse = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let challenge = pow_challenge::decode(&pow.token, enc_key.as_str())?;
+    assert_eq!(
+        challenge.algorithm,
+        PowAlgorithm::Scrypt { log_n: 10, r: 8 }
+    );
+    assert_eq!(challenge.difficulty, SCRYPT_DIFFICULTY);
+    assert_eq!(challenge.puzzles, SCRYPT_PUZZLES);
+
+    // solved with scrypt by the helper, a misplaced piece fails after the proof of work
+    let (puzzle, target_x) = fetch_puzzle(&server).await?;
+    let response = answer_puzzle(&server, &puzzle.token, target_x + 30).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn memory_hard_proofs_of_work_are_rate_limited(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+    use_scrypt(&server).await?;
+
+    let mut statuses = vec![];
+    for request in 0..36 {
+        let pow: PowResponse = HTTP_CLIENT
+            .get(format!(
+                "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+            ))
+            .send()
+            .await?
+            .json()
+            .await?;
+        let challenge = pow_challenge::decode(&pow.token, enc_key.as_str())?;
+        // the first few are missing a solution, they're turned down before hashing anything
+        let solutions = match request {
+            0..5 => vec![0],
+            _ => {
+                let wrong = (0..)
+                    .find(|&s| !challenge.verify_solution(&vec![s; SCRYPT_PUZZLES as usize]))
+                    .unwrap();
+                vec![wrong; SCRYPT_PUZZLES as usize]
+            }
+        };
+        let response = HTTP_CLIENT
+            .post(format!(
+                "http://localhost:{port}/api/challenge/process-pre-analysis"
+            ))
+            .json(&PreAnalysisRequest {
+                site_key: site_key.clone(),
+                hostname: Host::parse("website-integration.test.com")?,
+                interactions: vec![],
+                proof_of_work: ProofOfWork { challenge: pow.token, solutions },
+            })
+            .send()
+            .await?;
+        statuses.push(response.status());
+    }
+    assert!(statuses[..35].iter().all(|s| *s == StatusCode::BAD_REQUEST));
+    assert_eq!(statuses[35], StatusCode::TOO_MANY_REQUESTS);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn get_proof_of_work_challenge_no_site_key(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/proof-of-work"
+        ))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_invisible_successful(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    let enc_key = server.db_enconding_key().await;
+    db::update_api_key(
+        server.pool(),
+        site_key.as_str(),
+        &server.db_console().await,
+        DbUpdateApiKey {
+            label: None,
+            invisible: Some(true),
+            score_threshold: None,
+            customization_profile_id: None,
+        },
+    )
+    .await?;
+
+    let pow: PowResponse = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/challenge/proof-of-work?site_key={site_key}"
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let solutions = pow_challenge::decode(&pow.token, enc_key.as_str())?.solve();
+
+    let response = HTTP_CLIENT
+        .post(format!(
+            "http://localhost:{port}/api/challenge/process-invisible"
+        ))
+        .json(&InvisibleRequest {
+            site_key,
+            hostname: Host::parse("website-integration.test.com")?,
+            action: Some("login".into()),
+            interactions: vec![],
+            proof_of_work: ProofOfWork { challenge: pow.token, solutions },
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let ChallengeResponse { token } = response.json().await?;
+    let token_data = jsonwebtoken::decode::<TimeClaims<ResponseClaims>>(
+        &token,
+        &DecodingKey::from_base64_secret(enc_key.as_str())?,
+        &Validation::new(JWT_RESPONSE_ALGORITHM),
+    )?;
+    assert_eq!(token_data.claims.other.action.as_deref(), Some("login"));
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_invisible_fails_when_disabled(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+
+    let response = HTTP_CLIENT
+        .post(format!(
+            "http://localhost:{port}/api/challenge/process-invisible"
+        ))
+        .json(&InvisibleRequest {
+            site_key,
+            hostname: Host::parse("website-integration.test.com")?,
+            action: None,
+            interactions: vec![],
+            proof_of_work: ProofOfWork { challenge: "".into(), solutions: vec![0] },
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn process_invisible_fails_on_invalid_action(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let site_key = server.db_api_site_key().await;
+    db::update_api_key(
+        server.pool(),
+        site_key.as_str(),
+        &server.db_console().await,
+        DbUpdateApiKey {
+            label: None,
+            invisible: Some(true),
+            score_threshold: None,
+            customization_profile_id: None,
+        },
+    )
+    .await?;
+
+    let response = HTTP_CLIENT
+        .post(format!(
+            "http://localhost:{port}/api/challenge/process-invisible"
+        ))
+        .json(&InvisibleRequest {
+            site_key,
+            hostname: Host::parse("website-integration.test.com")?,
+            action: Some("log in!".into()),
+            interactions: vec![],
+            proof_of_work: ProofOfWork { challenge: "".into(), solutions: vec![0] },
+        })
+        .send()
+        .await?;
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
     Ok(())
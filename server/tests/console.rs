@@ -1,10 +1,15 @@
 use gotcha_server::{
     HTTP_CLIENT,
-    db::{self, DbChallengeCustomization, RowsAffected},
+    analysis::proof_of_work::{PowAlgorithmKind, PowSettings},
+    db::{self, DbChallenge, DbChallengeCustomization, RowsAffected},
     encodings::{Base64, KEY_SIZE, UrlSafe},
-    routes::console::{
-        ApiKeyResponse, ChallengePreferences, ConsoleResponse, CreateConsoleRequest,
-        UpdateApiKeyRequest, UpdateConsoleRequest,
+    routes::{
+        challenge::ThemeMode,
+        console::{
+            ApiKeyResponse, ChallengePreferences, ChallengeSelection, ConsoleResponse,
+            CreateConsoleRequest, CreateCustomizationProfile, CustomizationProfile,
+            UpdateApiKeyRequest, UpdateConsoleRequest, UpdateCustomizationProfile,
+        },
     },
     test_helpers,
 };
@@ -248,7 +253,12 @@ async fn update_api_key(server: TestContext) -> anyhow::Result<()> {
             "http://localhost:{port}/api/console/{console_id}/api-key/{site_key}"
         ))
         .bearer_auth(test_helpers::auth_jwt().await)
-        .json(&UpdateApiKeyRequest { label: Some("updated".into()) })
+        .json(&UpdateApiKeyRequest {
+            label: Some("updated".into()),
+            invisible: None,
+            score_threshold: None,
+            customization_profile_id: None,
+        })
         .send()
         .await?;
     assert_eq!(response.status(), StatusCode::OK);
@@ -300,7 +310,12 @@ async fn update_forbidden_api_key(server: TestContext) -> anyhow::Result<()> {
             "http://localhost:{port}/api/console/{console_id}/api-key/{site_key}"
         ))
         .bearer_auth(test_helpers::auth_jwt().await)
-        .json(&UpdateApiKeyRequest { label: Some("updated".into()) })
+        .json(&UpdateApiKeyRequest {
+            label: Some("updated".into()),
+            invisible: None,
+            score_threshold: None,
+            customization_profile_id: None,
+        })
         .send()
         .await?;
     assert_eq!(response.status(), StatusCode::FORBIDDEN);
@@ -406,6 +421,76 @@ async fn update_all_challenge_preferences(server: TestContext) -> anyhow::Result
     Ok(())
 }
 
+#[integration_test]
+async fn update_challenge_selection_preferences(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let pool = server.pool();
+    let console_id = server.db_console().await;
+    let url = format!(
+        "https://gotcha-integration.test.com/index.html?nonce={}",
+        server.test_id()
+    );
+    db::insert_challenge(pool, &DbChallenge::new(url.clone())).await?;
+
+    let response = HTTP_CLIENT
+        .patch(format!(
+            "http://localhost:{port}/api/console/{console_id}/challenge-preferences"
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&serde_json::json!({
+            "escalate_difficulty": false,
+            "accessibility_enabled": false,
+            "challenges": [{ "url": url, "enabled": false, "weight": 3 }],
+            "proof_of_work": { "algorithm": "scrypt", "memory_kib": 2048 },
+        }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = HTTP_CLIENT
+        .get(format!(
+            "http://localhost:{port}/api/console/{console_id}/challenge-preferences"
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let preferences: ChallengePreferences = response.json().await?;
+    assert!(preferences.exclude_webgl_on_low_end);
+    assert!(!preferences.escalate_difficulty);
+    assert!(!preferences.accessibility_enabled);
+    assert_eq!(
+        preferences.proof_of_work,
+        PowSettings { algorithm: PowAlgorithmKind::Scrypt, memory_kib: 2048 }
+    );
+    assert_eq!(
+        preferences.challenges,
+        vec![ChallengeSelection { url, enabled: false, weight: Some(3) }]
+    );
+
+    Ok(())
+}
+
+#[integration_test]
+async fn update_challenge_selection_not_found(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let console_id = server.db_console().await;
+
+    let response = HTTP_CLIENT
+        .patch(format!(
+            "http://localhost:{port}/api/console/{console_id}/challenge-preferences"
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&serde_json::json!({
+            "challenges": [{ "url": "https://not-a-challenge.test.com", "enabled": false }],
+        }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
 #[integration_test]
 async fn update_partially_challenge_preferences_1(server: TestContext) -> anyhow::Result<()> {
     let port = server.port();
@@ -510,3 +595,205 @@ async fn create_api_key_on_another_console(port: u16) -> anyhow::Result<(Uuid, B
 
     Ok((console_id, site_key))
 }
+
+#[integration_test]
+async fn customization_profile_lifecycle(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let console_id = server.db_console().await;
+    let site_key = server.db_api_site_key().await;
+    let profiles_url =
+        format!("http://localhost:{port}/api/console/{console_id}/customization-profile");
+
+    let response = HTTP_CLIENT
+        .post(&profiles_url)
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&CreateCustomizationProfile {
+            label: "checkout".into(),
+            width: Some(420),
+            logo_url: Some("https://checkout.test.com/logo.png".into()),
+            ..Default::default()
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let profile: CustomizationProfile = response.json().await?;
+    assert_eq!(profile.width, Some(420));
+    assert_eq!(profile.height, None);
+
+    let response = HTTP_CLIENT
+        .patch(format!("{profiles_url}/{}", profile.id))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&UpdateCustomizationProfile { height: Some(640), ..Default::default() })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = HTTP_CLIENT
+        .patch(format!(
+            "http://localhost:{port}/api/console/{console_id}/api-key/{site_key}"
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&UpdateApiKeyRequest {
+            label: None,
+            invisible: None,
+            score_threshold: None,
+            customization_profile_id: Some(Some(profile.id)),
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let profiles: Vec<CustomizationProfile> = HTTP_CLIENT
+        .get(&profiles_url)
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0].height, Some(640));
+    let key = db::fetch_api_key_by_site_key(server.pool(), &site_key)
+        .await?
+        .expect("site key exists");
+    assert_eq!(key.customization_profile_id, Some(profile.id));
+
+    // deleting the profile detaches it from the site key
+    let response = HTTP_CLIENT
+        .delete(format!("{profiles_url}/{}", profile.id))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let key = db::fetch_api_key_by_site_key(server.pool(), &site_key)
+        .await?
+        .expect("site key exists");
+    assert_eq!(key.customization_profile_id, None);
+
+    Ok(())
+}
+
+#[integration_test]
+async fn customization_profile_duplicate_label(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let console_id = server.db_console().await;
+    let profiles_url =
+        format!("http://localhost:{port}/api/console/{console_id}/customization-profile");
+
+    for expected in [StatusCode::OK, StatusCode::CONFLICT] {
+        let response = HTTP_CLIENT
+            .post(&profiles_url)
+            .bearer_auth(test_helpers::auth_jwt().await)
+            .json(&CreateCustomizationProfile { label: "marketing".into(), ..Default::default() })
+            .send()
+            .await?;
+        assert_eq!(response.status(), expected);
+    }
+
+    Ok(())
+}
+
+#[integration_test]
+async fn attach_customization_profile_of_another_console(
+    server: TestContext,
+) -> anyhow::Result<()> {
+    let port = server.port();
+    let pool = server.pool();
+    let console_id = server.db_console().await;
+    let site_key = server.db_api_site_key().await;
+
+    let other_console = post_console(port).await?;
+    let profile: CustomizationProfile = HTTP_CLIENT
+        .post(format!(
+            "http://localhost:{port}/api/console/{}/customization-profile",
+            other_console.id
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&CreateCustomizationProfile { label: "other".into(), ..Default::default() })
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    let response = HTTP_CLIENT
+        .patch(format!(
+            "http://localhost:{port}/api/console/{console_id}/api-key/{site_key}"
+        ))
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&UpdateApiKeyRequest {
+            label: None,
+            invisible: None,
+            score_threshold: None,
+            customization_profile_id: Some(Some(profile.id)),
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    db::delete_console(pool, &other_console.id).await?;
+
+    Ok(())
+}
+
+#[integration_test]
+async fn update_theme_and_localization(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let console_id = server.db_console().await;
+    let url = format!("http://localhost:{port}/api/console/{console_id}/challenge-preferences");
+
+    let response = HTTP_CLIENT
+        .patch(&url)
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .json(&serde_json::json!({
+            "theme": { "mode": "dark", "primary_color": "#7c3aed", "corner_radius": 12 },
+            "localization": { "locale": "fr", "strings": { "start": "Cliquez pour commencer !" } },
+        }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let preferences: ChallengePreferences = HTTP_CLIENT
+        .get(&url)
+        .bearer_auth(test_helpers::auth_jwt().await)
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(preferences.theme.mode, ThemeMode::Dark);
+    assert_eq!(preferences.theme.primary_color.as_deref(), Some("#7c3aed"));
+    assert_eq!(preferences.theme.corner_radius, Some(12));
+    assert_eq!(preferences.localization.locale.as_deref(), Some("fr"));
+    assert_eq!(
+        preferences
+            .localization
+            .strings
+            .get("start")
+            .map(String::as_str),
+        Some("Cliquez pour commencer !")
+    );
+
+    Ok(())
+}
+
+#[integration_test]
+async fn update_theme_invalid(server: TestContext) -> anyhow::Result<()> {
+    let port = server.port();
+    let console_id = server.db_console().await;
+
+    for theme in [
+        serde_json::json!({ "primary_color": "red" }),
+        serde_json::json!({ "corner_radius": 100 }),
+        serde_json::json!({ "font_family": "x; background: url(evil)" }),
+    ] {
+        let response = HTTP_CLIENT
+            .patch(format!(
+                "http://localhost:{port}/api/console/{console_id}/challenge-preferences"
+            ))
+            .bearer_auth(test_helpers::auth_jwt().await)
+            .json(&serde_json::json!({ "theme": theme }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    Ok(())
+}
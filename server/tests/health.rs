@@ -0,0 +1,130 @@
+mod health {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use axum::{Json, Router, routing::get};
+    use gotcha_server::{HTTP_CLIENT, health};
+    use gotcha_server_macros::integration_test;
+    use reqwest::StatusCode;
+    use serde_json::{Value, json};
+
+    #[integration_test]
+    async fn server_is_live(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+
+        let response = HTTP_CLIENT
+            .get(format!("http://localhost:{port}/healthz"))
+            .send()
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json::<Value>().await?, json!({ "status": "ok" }));
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn readiness_is_ok_when_every_check_passes(server: TestContext) -> anyhow::Result<()> {
+        let (auth_origin, _) = serve_jwks().await?;
+        let port = serve(health::router(
+            server.pool().clone(),
+            auth_origin,
+            Some(env!("CARGO_MANIFEST_DIR").into()),
+        ))
+        .await?;
+
+        let response = HTTP_CLIENT
+            .get(format!("http://localhost:{port}/readyz"))
+            .send()
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let report = response.json::<Value>().await?;
+        assert_eq!(report["status"], "ok", "{report}");
+        for check in ["database", "migrations", "jwks", "serve_dir"] {
+            assert_eq!(report["checks"][check]["status"], "ok", "{check}: {report}");
+            assert!(report["checks"][check]["latency_ms"].is_u64());
+        }
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn readiness_fails_when_a_check_fails(server: TestContext) -> anyhow::Result<()> {
+        // nothing listens on port 1
+        let port = serve(health::router(
+            server.pool().clone(),
+            "http://127.0.0.1:1".into(),
+            Some("/nonexistent".into()),
+        ))
+        .await?;
+
+        let response = HTTP_CLIENT
+            .get(format!("http://localhost:{port}/readyz"))
+            .send()
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let report = response.json::<Value>().await?;
+        assert_eq!(report["status"], "fail", "{report}");
+        assert_eq!(report["checks"]["database"]["status"], "ok", "{report}");
+        for check in ["jwks", "serve_dir"] {
+            assert_eq!(
+                report["checks"][check]["status"], "fail",
+                "{check}: {report}"
+            );
+            assert_eq!(
+                report["checks"][check].get("error"),
+                None,
+                "{check}: {report}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn readiness_is_checked_once_every_few_seconds(
+        server: TestContext,
+    ) -> anyhow::Result<()> {
+        let (auth_origin, fetches) = serve_jwks().await?;
+        let port = serve(health::router(server.pool().clone(), auth_origin, None)).await?;
+
+        for _ in 0..3 {
+            let response = HTTP_CLIENT
+                .get(format!("http://localhost:{port}/readyz"))
+                .send()
+                .await?;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(fetches.load(Ordering::Relaxed), 1);
+
+        Ok(())
+    }
+
+    /// Serves `router` on an ephemeral port.
+    async fn serve(router: Router) -> anyhow::Result<u16> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        tokio::spawn(axum::serve(listener, router).into_future());
+        Ok(port)
+    }
+
+    /// Serves a key set like the auth provider's, returns its origin and how many times the keys
+    /// were fetched.
+    async fn serve_jwks() -> anyhow::Result<(String, Arc<AtomicUsize>)> {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&fetches);
+        let router = Router::new().route(
+            "/.well-known/jwks.json",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::Relaxed);
+                Json(json!({ "keys": [{ "kty": "oct", "k": "c2VjcmV0", "alg": "HS256" }] }))
+            }),
+        );
+        let port = serve(router).await?;
+        Ok((format!("http://127.0.0.1:{port}"), fetches))
+    }
+}
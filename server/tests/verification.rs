@@ -3,11 +3,13 @@ mod verify_site {
 
     use gotcha_server::{
         HTTP_CLIENT,
+        db::{self, DbUpdateApiKey},
         routes::verification::{ErrorCodes, VerificationResponse},
-        tokens::response::{self, ResponseClaims},
+        tokens::response::{self, ChallengeType, ResponseClaims},
     };
     use gotcha_server_macros::integration_test;
     use reqwest::StatusCode;
+    use serde_json::json;
     use url::Host;
 
     #[integration_test]
@@ -21,6 +23,8 @@ mod verify_site {
                 score: 0.75,
                 addr: [127, 0, 0, 1].into(),
                 host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
             },
             &enc_key,
         )?;
@@ -39,6 +43,45 @@ mod verify_site {
         Ok(())
     }
 
+    #[integration_test]
+    async fn outcomes_are_exported_as_metrics(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .form(&[("secret", secret.as_str()), ("response", "invalid")])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = HTTP_CLIENT
+            .get(format!("http://localhost:{port}/metrics"))
+            .send()
+            .await?;
+        assert_eq!(
+            response.status(),
+            StatusCode::NOT_FOUND,
+            "not served publicly"
+        );
+
+        let metrics_port = server.metrics_port();
+        let response = HTTP_CLIENT
+            .get(format!("http://localhost:{metrics_port}/metrics"))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let metrics = response.text().await?;
+        assert!(metrics.contains(r#"gotcha_siteverify_total{outcome="invalid-input-response"}"#));
+        assert!(metrics.contains(
+            r#"gotcha_http_requests_total{method="POST",route="/api/siteverify",status="200"}"#
+        ));
+        assert!(metrics.contains("gotcha_http_request_duration_seconds_bucket"));
+        assert!(metrics.contains("gotcha_db_pool_connections"));
+
+        Ok(())
+    }
+
     #[integration_test]
     async fn sucessful_challenge_with_remoteip(server: TestContext) -> anyhow::Result<()> {
         let port = server.port();
@@ -47,7 +90,13 @@ mod verify_site {
         let addr = [127, 0, 0, 1].into();
 
         let token = response::encode(
-            ResponseClaims { score: 0.75, addr, host: Host::parse("gotcha-integration.test.com")? },
+            ResponseClaims {
+                score: 0.75,
+                addr,
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
             &enc_key,
         )?;
 
@@ -80,6 +129,8 @@ mod verify_site {
                 score: 0.75,
                 addr: [127, 0, 0, 1].into(),
                 host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
             },
             &enc_key,
         )?;
@@ -98,7 +149,10 @@ mod verify_site {
 
         let verification: VerificationResponse = response.json().await?;
         assert!(!verification.success);
-        assert_eq!(verification.error_codes, None);
+        assert_eq!(
+            verification.error_codes,
+            Some(vec![ErrorCodes::RemoteipMismatch])
+        );
 
         Ok(())
     }
@@ -114,6 +168,8 @@ mod verify_site {
                 score: 0.3,
                 addr: [127, 0, 0, 1].into(),
                 host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
             },
             &enc_key,
         )?;
@@ -132,6 +188,100 @@ mod verify_site {
         Ok(())
     }
 
+    #[integration_test]
+    async fn invisible_mode_reports_score_and_action(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+        db::update_api_key(
+            server.pool(),
+            server.db_api_site_key().await.as_str(),
+            &server.db_console().await,
+            DbUpdateApiKey {
+                label: None,
+                invisible: Some(true),
+                score_threshold: None,
+                customization_profile_id: None,
+            },
+        )
+        .await?;
+
+        let token = response::encode(
+            ResponseClaims {
+                score: 0.3,
+                addr: [127, 0, 0, 1].into(),
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Invisible,
+                action: Some("login".into()),
+            },
+            &enc_key,
+        )?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .form(&[("secret", secret.as_str()), ("response", &token)])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: VerificationResponse = response.json().await?;
+        assert!(verification.success);
+        assert_eq!(verification.score, Some(0.3));
+        assert_eq!(verification.action.as_deref(), Some("login"));
+        assert_eq!(verification.challenge_type, Some(ChallengeType::Invisible));
+        assert_eq!(verification.error_codes, None);
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn score_below_key_threshold(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+        db::update_api_key(
+            server.pool(),
+            server.db_api_site_key().await.as_str(),
+            &server.db_console().await,
+            DbUpdateApiKey {
+                label: None,
+                invisible: None,
+                score_threshold: Some(0.8),
+                customization_profile_id: None,
+            },
+        )
+        .await?;
+
+        let token = response::encode(
+            ResponseClaims {
+                score: 0.75,
+                addr: [127, 0, 0, 1].into(),
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
+            &enc_key,
+        )?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .form(&[("secret", secret.as_str()), ("response", &token)])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: VerificationResponse = response.json().await?;
+        assert!(!verification.success);
+        assert_eq!(verification.score, Some(0.75));
+        assert_eq!(
+            verification.challenge_type,
+            Some(ChallengeType::Interactive)
+        );
+        assert_eq!(verification.error_codes, None);
+
+        Ok(())
+    }
+
     #[integration_test]
     async fn missing_secret(server: TestContext) -> anyhow::Result<()> {
         let port = server.port();
@@ -142,6 +292,8 @@ mod verify_site {
                 score: 1.,
                 addr: [127, 0, 0, 1].into(),
                 host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
             },
             &enc_key,
         )?;
@@ -230,6 +382,8 @@ mod verify_site {
                 score: 1.,
                 addr: [127, 0, 0, 1].into(),
                 host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
             },
             &enc_key,
         )?;
@@ -271,6 +425,8 @@ mod verify_site {
                 score: 0.75,
                 addr: [127, 0, 0, 1].into(),
                 host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
             },
             &enc_key,
         )?;
@@ -322,6 +478,189 @@ mod verify_site {
         Ok(())
     }
 
+    #[integration_test]
+    async fn json_body(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let site_key = server.db_api_site_key().await;
+        let enc_key = server.db_enconding_key().await;
+
+        let token = response::encode(
+            ResponseClaims {
+                score: 0.75,
+                addr: [127, 0, 0, 1].into(),
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
+            &enc_key,
+        )?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .json(&json!({
+                "secret": secret.as_str(),
+                "response": token,
+                "sitekey": site_key.as_str(),
+                "strict": true,
+            }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: VerificationResponse = response.json().await?;
+        assert!(verification.success);
+        assert_eq!(verification.error_codes, None);
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn json_body_null_fields_are_absent(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+
+        let token = response::encode(
+            ResponseClaims {
+                score: 0.75,
+                addr: [127, 0, 0, 1].into(),
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
+            &enc_key,
+        )?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .json(&json!({
+                "secret": secret.as_str(),
+                "response": token,
+                "remoteip": null,
+                "sitekey": null,
+                "strict": true,
+            }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: VerificationResponse = response.json().await?;
+        assert!(verification.success);
+        assert_eq!(verification.error_codes, None);
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn sitekey_mismatch(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+
+        let token = response::encode(
+            ResponseClaims {
+                score: 0.75,
+                addr: [127, 0, 0, 1].into(),
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
+            &enc_key,
+        )?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .form(&[
+                ("secret", secret.as_str()),
+                ("response", &token),
+                ("sitekey", "not-the-site-key"),
+            ])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: VerificationResponse = response.json().await?;
+        assert!(!verification.success);
+        assert_eq!(
+            verification.error_codes,
+            Some(vec![ErrorCodes::SitekeySecretMismatch])
+        );
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn unknown_params_ignored(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+
+        let token = response::encode(
+            ResponseClaims {
+                score: 0.75,
+                addr: [127, 0, 0, 1].into(),
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
+            &enc_key,
+        )?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .form(&[
+                ("secret", secret.as_str()),
+                ("response", &token),
+                ("idempotency_key", "1"),
+            ])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: VerificationResponse = response.json().await?;
+        assert!(verification.success);
+        assert_eq!(verification.error_codes, None);
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn strict_rejects_unknown_params(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+
+        let token = response::encode(
+            ResponseClaims {
+                score: 0.75,
+                addr: [127, 0, 0, 1].into(),
+                host: Host::parse("gotcha-integration.test.com")?,
+                challenge_type: ChallengeType::Interactive,
+                action: None,
+            },
+            &enc_key,
+        )?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/siteverify"))
+            .json(&json!({
+                "secret": secret.as_str(),
+                "response": token,
+                "strict": true,
+                "idempotency_key": 1,
+            }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: VerificationResponse = response.json().await?;
+        assert!(!verification.success);
+        assert_eq!(verification.error_codes, Some(vec![ErrorCodes::BadRequest]));
+
+        Ok(())
+    }
+
     #[integration_test]
     async fn duplicate(_server: TestContext) -> anyhow::Result<()> {
         // TODO
@@ -349,6 +688,8 @@ mod verify_site {
                     score: 1.,
                     addr: [127, 0, 0, 1].into(),
                     host: Host::parse("gotcha-integration.test.com")?,
+                    challenge_type: ChallengeType::Interactive,
+                    action: None,
                 },
                 &enc_key,
                 Duration::from_secs(0),
@@ -418,6 +759,8 @@ mod verify_site {
                     score: 1.,
                     addr: [127, 0, 0, 1].into(),
                     host: Host::parse("gotcha-integration.test.com")?,
+                    challenge_type: ChallengeType::Interactive,
+                    action: None,
                 }),
                 &EncodingKey::from_base64_secret(
                     "bXktd3Jvbmctc2VjcmV0", /* `my-wrong-secret` in base64 */
@@ -455,6 +798,8 @@ mod verify_site {
                     score: 1.,
                     addr: [127, 0, 0, 1].into(),
                     host: Host::parse("gotcha-integration.test.com")?,
+                    challenge_type: ChallengeType::Interactive,
+                    action: None,
                 }),
                 &EncodingKey::from_base64_secret(enc_key.as_str())?,
             )?;
@@ -505,3 +850,189 @@ mod verify_site {
         }
     }
 }
+
+mod compat {
+    use std::time::Duration;
+
+    use gotcha_server::{
+        HTTP_CLIENT,
+        routes::compat::{
+            HCaptchaErrorCode, HCaptchaResponse, RecaptchaErrorCode, RecaptchaResponse,
+            TurnstileErrorCode, TurnstileResponse,
+        },
+        tokens::response::{self, ChallengeType, ResponseClaims},
+    };
+    use gotcha_server_macros::integration_test;
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use url::Host;
+
+    fn claims() -> anyhow::Result<ResponseClaims> {
+        Ok(ResponseClaims {
+            score: 0.75,
+            addr: [127, 0, 0, 1].into(),
+            host: Host::parse("gotcha-integration.test.com")?,
+            challenge_type: ChallengeType::Interactive,
+            action: None,
+        })
+    }
+
+    #[integration_test]
+    async fn recaptcha_successful(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+        let token = response::encode(claims()?, &enc_key)?;
+
+        let response = HTTP_CLIENT
+            .post(format!(
+                "http://localhost:{port}/api/recaptcha/api/siteverify"
+            ))
+            .form(&[("secret", secret.as_str()), ("response", &token)])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let raw: Value = response.json().await?;
+        assert_eq!(raw.get("challenge_type"), None);
+        assert_eq!(raw.get("error-codes"), None);
+        let verification: RecaptchaResponse = serde_json::from_value(raw)?;
+        assert!(verification.success);
+        assert!(verification.challenge_ts.is_some());
+        assert_eq!(
+            verification.hostname.as_deref(),
+            Some("gotcha-integration.test.com")
+        );
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn recaptcha_missing_secret(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let enc_key = server.db_enconding_key().await;
+        let token = response::encode(claims()?, &enc_key)?;
+
+        let response = HTTP_CLIENT
+            .post(format!(
+                "http://localhost:{port}/api/recaptcha/api/siteverify"
+            ))
+            .form(&[("response", &token)])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let raw: Value = response.json().await?;
+        assert_eq!(raw.get("challenge_ts"), None);
+        let verification: RecaptchaResponse = serde_json::from_value(raw)?;
+        assert!(!verification.success);
+        assert_eq!(
+            verification.error_codes,
+            vec![RecaptchaErrorCode::MissingInputSecret]
+        );
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn hcaptcha_expired_response(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+        let token = response::encode_with_timeout(claims()?, &enc_key, Duration::from_secs(0))?;
+        // expired by 1 second
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/hcaptcha/siteverify"))
+            .form(&[("secret", secret.as_str()), ("response", &token)])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: HCaptchaResponse = response.json().await?;
+        assert!(!verification.success);
+        assert_eq!(
+            verification.error_codes,
+            vec![HCaptchaErrorCode::ExpiredInputResponse]
+        );
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn hcaptcha_sitekey_mismatch(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+        let token = response::encode(claims()?, &enc_key)?;
+
+        let response = HTTP_CLIENT
+            .post(format!("http://localhost:{port}/api/hcaptcha/siteverify"))
+            .form(&[
+                ("secret", secret.as_str()),
+                ("response", &token),
+                ("sitekey", "not-the-site-key"),
+            ])
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: HCaptchaResponse = response.json().await?;
+        assert!(!verification.success);
+        assert_eq!(
+            verification.error_codes,
+            vec![HCaptchaErrorCode::SitekeySecretMismatch]
+        );
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn turnstile_successful(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+        let secret = server.db_api_secret().await;
+        let enc_key = server.db_enconding_key().await;
+        let token = response::encode(claims()?, &enc_key)?;
+
+        let response = HTTP_CLIENT
+            .post(format!(
+                "http://localhost:{port}/api/turnstile/v0/siteverify"
+            ))
+            .json(&serde_json::json!({ "secret": secret.as_str(), "response": token }))
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: TurnstileResponse = response.json().await?;
+        assert!(verification.success);
+        assert_eq!(verification.error_codes, vec![]);
+        assert_eq!(verification.action.as_deref(), Some(""));
+        assert_eq!(verification.cdata.as_deref(), Some(""));
+
+        Ok(())
+    }
+
+    #[integration_test]
+    async fn turnstile_bad_request(server: TestContext) -> anyhow::Result<()> {
+        let port = server.port();
+
+        let response = HTTP_CLIENT
+            .post(format!(
+                "http://localhost:{port}/api/turnstile/v0/siteverify"
+            ))
+            .body("secret=api_key&response=response_token")
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let verification: TurnstileResponse = response.json().await?;
+        assert!(!verification.success);
+        assert_eq!(
+            verification.error_codes,
+            vec![TurnstileErrorCode::BadRequest]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+//! Payloads of the `/api/console` routes.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    encodings::{Base64, UrlSafe},
+    serde::nested_option,
+};
+
+/// Response payload of retrieving a console.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsoleResponse {
+    pub id: Uuid,
+    pub label: Option<String>,
+}
+
+/// Expected payload for create console route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateConsoleRequest {
+    /// Label.
+    pub label: String,
+}
+
+/// Expected payload for update console route.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateConsoleRequest {
+    // Label to be updated. `None` means "don't change".
+    pub label: Option<String>,
+}
+
+/// Response payload of retrieving an api key.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyResponse {
+    /// Public site key encoded in base64 url safe alphabet.
+    pub site_key: Base64<UrlSafe>,
+    /// Secret site key encoded in base64 standard alphabet.
+    pub secret: Base64,
+    /// Label. Can be absent.
+    pub label: Option<String>,
+    /// Wether the key runs in invisible mode, responding with a score instead of a challenge.
+    pub invisible: bool,
+    /// Minimum score a response needs for site verification to succeed.
+    pub score_threshold: f32,
+    /// Customization profile overriding the console challenge preferences.
+    #[serde(default)]
+    pub customization_profile_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateApiKeyRequest {
+    pub label: Option<String>,
+    #[serde(default)]
+    pub invisible: Option<bool>,
+    #[serde(default)]
+    pub score_threshold: Option<f32>,
+    /// Profile of the same console to attach, `null` detaches the current one.
+    #[serde(
+        default,
+        deserialize_with = "nested_option::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub customization_profile_id: Option<Option<Uuid>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChallengePreferences {
+    pub width: u16,
+    pub height: u16,
+    pub small_width: u16,
+    pub small_height: u16,
+    pub logo_url: Option<String>,
+    /// Skip challenges requiring WebGL on low-end mobile devices.
+    pub exclude_webgl_on_low_end: bool,
+    /// Serve harder challenges after failed attempts or a bad pre analysis.
+    pub escalate_difficulty: bool,
+    /// Offer the accessibility question to users who can't play the interactive challenges.
+    pub accessibility_enabled: bool,
+    /// Challenges with console specific settings, any other is enabled with its default weight.
+    #[serde(default)]
+    pub challenges: Vec<ChallengeSelection>,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default)]
+    pub localization: Localization,
+    /// Algorithm of the proof of work asked of the widgets.
+    #[serde(default)]
+    pub proof_of_work: PowSettings,
+}
+
+/// Console specific selection settings of a challenge.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChallengeSelection {
+    /// Challenge public URL.
+    pub url: String,
+    /// Wether the challenge can be served.
+    pub enabled: bool,
+    /// Relative chance of being served, `None` keeps the challenge default.
+    #[serde(default)]
+    pub weight: Option<u16>,
+}
+
+impl Default for ChallengePreferences {
+    fn default() -> Self {
+        Self {
+            width: 360,
+            height: 500,
+            small_width: 360,
+            small_height: 500,
+            logo_url: None,
+            exclude_webgl_on_low_end: true,
+            escalate_difficulty: true,
+            accessibility_enabled: true,
+            challenges: vec![],
+            theme: Theme::default(),
+            localization: Localization::default(),
+            proof_of_work: PowSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateChallengePreferences {
+    #[serde(default)]
+    pub width: Option<u16>,
+    #[serde(default)]
+    pub height: Option<u16>,
+    #[serde(default)]
+    pub small_width: Option<u16>,
+    #[serde(default)]
+    pub small_height: Option<u16>,
+    #[serde(
+        default,
+        deserialize_with = "nested_option::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub logo_url: Option<Option<String>>,
+    #[serde(default)]
+    pub exclude_webgl_on_low_end: Option<bool>,
+    #[serde(default)]
+    pub escalate_difficulty: Option<bool>,
+    #[serde(default)]
+    pub accessibility_enabled: Option<bool>,
+    /// Challenges to enable, disable or reweight, challenges not listed are left unchanged.
+    #[serde(default)]
+    pub challenges: Option<Vec<ChallengeSelection>>,
+    /// Replaces the whole theme.
+    #[serde(default)]
+    pub theme: Option<Theme>,
+    /// Replaces the locale and every string override.
+    #[serde(default)]
+    pub localization: Option<Localization>,
+    /// Replaces the proof of work algorithm and its memory cost.
+    #[serde(default)]
+    pub proof_of_work: Option<PowSettings>,
+}
+
+/// Named challenge customization attached to site keys, `None` fields fall back to the console
+/// challenge preferences.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CustomizationProfile {
+    pub id: Uuid,
+    pub label: String,
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    pub small_width: Option<u16>,
+    pub small_height: Option<u16>,
+    pub logo_url: Option<String>,
+}
+
+/// Expected payload for create customization profile route.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreateCustomizationProfile {
+    /// Label, unique within the console.
+    pub label: String,
+    #[serde(default)]
+    pub width: Option<u16>,
+    #[serde(default)]
+    pub height: Option<u16>,
+    #[serde(default)]
+    pub small_width: Option<u16>,
+    #[serde(default)]
+    pub small_height: Option<u16>,
+    #[serde(default)]
+    pub logo_url: Option<String>,
+}
+
+/// Expected payload for update customization profile route, absent fields are left unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateCustomizationProfile {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub width: Option<u16>,
+    #[serde(default)]
+    pub height: Option<u16>,
+    #[serde(default)]
+    pub small_width: Option<u16>,
+    #[serde(default)]
+    pub small_height: Option<u16>,
+    #[serde(
+        default,
+        deserialize_with = "nested_option::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub logo_url: Option<Option<String>>,
+}
+
+/// Color scheme of the widgets, `auto` follows the user's preference.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    #[default]
+    Auto,
+}
+
+/// Look of the widgets, `None` fields keep the widget defaults.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Theme {
+    #[serde(default)]
+    pub mode: ThemeMode,
+    /// Hex color, e.g. `#7c3aed`.
+    #[serde(default)]
+    pub primary_color: Option<String>,
+    #[serde(default)]
+    pub background_color: Option<String>,
+    #[serde(default)]
+    pub text_color: Option<String>,
+    /// Corner radius in pixels, at most 64.
+    #[serde(default)]
+    pub corner_radius: Option<u8>,
+    #[serde(default)]
+    pub font_family: Option<String>,
+}
+
+/// Language of the widgets.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Localization {
+    /// BCP 47 language tag, e.g. `fr-CA`. Widgets use the user's language when `None`.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Replacements of the widgets strings by key, e.g. `"start": "Cliquez pour commencer !"`.
+    #[serde(default)]
+    pub strings: BTreeMap<String, String>,
+}
+
+impl ThemeMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThemeMode::Light => "light",
+            ThemeMode::Dark => "dark",
+            ThemeMode::Auto => "auto",
+        }
+    }
+}
+
+impl From<&str> for ThemeMode {
+    fn from(mode: &str) -> Self {
+        match mode {
+            "light" => ThemeMode::Light,
+            "dark" => ThemeMode::Dark,
+            _ => ThemeMode::Auto,
+        }
+    }
+}
+
+/// Bounds of the memory cost of scrypt, kept low enough for phones and for verifying on the
+/// request path.
+pub const MIN_SCRYPT_MEMORY_KIB: u32 = 1024;
+
+pub const MAX_SCRYPT_MEMORY_KIB: u32 = 16384;
+
+/// Proof of work settings of a console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowSettings {
+    #[serde(default)]
+    pub algorithm: PowAlgorithmKind,
+    /// Memory cost of each hash of memory-hard algorithms, a power of two from
+    /// [`MIN_SCRYPT_MEMORY_KIB`] to [`MAX_SCRYPT_MEMORY_KIB`].
+    #[serde(default = "PowSettings::default_memory_kib")]
+    pub memory_kib: u32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowAlgorithmKind {
+    #[default]
+    Sha256,
+    Scrypt,
+}
+
+impl PowAlgorithmKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PowAlgorithmKind::Sha256 => "sha256",
+            PowAlgorithmKind::Scrypt => "scrypt",
+        }
+    }
+}
+
+impl From<&str> for PowAlgorithmKind {
+    fn from(kind: &str) -> Self {
+        match kind {
+            "scrypt" => PowAlgorithmKind::Scrypt,
+            _ => PowAlgorithmKind::Sha256,
+        }
+    }
+}
+
+impl PowSettings {
+    fn default_memory_kib() -> u32 {
+        4096
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.memory_kib.is_power_of_two()
+            && (MIN_SCRYPT_MEMORY_KIB..=MAX_SCRYPT_MEMORY_KIB).contains(&self.memory_kib)
+    }
+}
+
+impl Default for PowSettings {
+    fn default() -> Self {
+        Self {
+            algorithm: PowAlgorithmKind::default(),
+            memory_kib: Self::default_memory_kib(),
+        }
+    }
+}
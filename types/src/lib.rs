@@ -0,0 +1,7 @@
+//! Request and response types of the gotcha server API, shared by the server and its clients
+//! without pulling in the server itself.
+
+pub mod console;
+pub mod encodings;
+pub mod serde;
+pub mod verification;
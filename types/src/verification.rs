@@ -0,0 +1,115 @@
+//! The `/api/siteverify` response.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::OffsetDateTime;
+use url::Host;
+
+/// Outcome of the verification of a response token.
+#[derive(Debug, Clone, Serialize, Deserialize, Error)]
+pub struct VerificationResponse {
+    pub success: bool,
+    #[serde(with = "time::serde::iso8601")]
+    pub challenge_ts: OffsetDateTime,
+    #[serde(with = "crate::serde::option_host_as_str")]
+    pub hostname: Option<Host>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge_type: Option<ChallengeType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(rename = "error-codes", skip_serializing_if = "Option::is_none")]
+    pub error_codes: Option<Vec<ErrorCodes>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCodes {
+    MissingInputSecret,
+    InvalidInputSecret,
+    MissingInputResponse,
+    InvalidInputResponse,
+    BadRequest,
+    TimeoutOrDuplicate,
+    RemoteipMismatch,
+    SitekeySecretMismatch,
+}
+
+impl ErrorCodes {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCodes::MissingInputSecret => "missing-input-secret",
+            ErrorCodes::InvalidInputSecret => "invalid-input-secret",
+            ErrorCodes::MissingInputResponse => "missing-input-response",
+            ErrorCodes::InvalidInputResponse => "invalid-input-response",
+            ErrorCodes::BadRequest => "bad-request",
+            ErrorCodes::TimeoutOrDuplicate => "timeout-or-duplicate",
+            ErrorCodes::RemoteipMismatch => "remoteip-mismatch",
+            ErrorCodes::SitekeySecretMismatch => "sitekey-secret-mismatch",
+        }
+    }
+}
+
+impl VerificationResponse {
+    pub fn failure(errors: Vec<ErrorCodes>) -> Self {
+        Self {
+            success: false,
+            challenge_ts: OffsetDateTime::UNIX_EPOCH,
+            hostname: None,
+            score: None,
+            challenge_type: None,
+            action: None,
+            error_codes: Some(errors),
+        }
+    }
+}
+
+impl Display for VerificationResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "verification: challenge {}, loaded at {} in `{:?}` - {:?}",
+            match self.success {
+                true => "solved successfully",
+                false => "failed",
+            },
+            self.challenge_ts,
+            self.hostname,
+            self.error_codes
+        )
+    }
+}
+
+/// The path taken by the user to get a response token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChallengeType {
+    /// Trusted by the pre analysis without solving a challenge.
+    PreAnalysis,
+    /// Solved an interactive challenge.
+    Interactive,
+    /// Took the accessibility alternative.
+    Accessibility,
+    /// Solved the slider puzzle rendered by the server.
+    Puzzle,
+    /// Scored in the background by invisible mode.
+    Invisible,
+    /// Solved a remote challenge, scored by its verify URL.
+    Remote,
+}
+
+impl ChallengeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeType::PreAnalysis => "pre-analysis",
+            ChallengeType::Interactive => "interactive",
+            ChallengeType::Accessibility => "accessibility",
+            ChallengeType::Puzzle => "puzzle",
+            ChallengeType::Invisible => "invisible",
+            ChallengeType::Remote => "remote",
+        }
+    }
+}
@@ -0,0 +1,187 @@
+import { createEffect, createResource, createSignal, Show } from "solid-js";
+import {
+  fetchAccessibilityChallenge,
+  fetchAudioChallenge,
+  processAccessibility,
+} from "../server";
+import { solveProofOfWork } from "./im-not-a-robot";
+import CloseSvg from "./icons/close";
+import Logo from "./logo";
+import Modal from "./modal";
+
+type AccessibilityQuestionProps = {
+  open: boolean;
+  sitekey: string;
+  onComplete: (response: string) => void;
+  onError: () => void;
+  onClose: () => void;
+};
+
+type Question = {
+  question: string;
+  token: string;
+  /** Data URL of the clip to play, for audio questions. */
+  audio?: string;
+};
+
+async function fetchQuestion(source: {
+  sitekey: string;
+  audio: boolean;
+}): Promise<Question | null> {
+  if (!source.audio) return fetchAccessibilityChallenge(source.sitekey);
+
+  const challenge = await fetchAudioChallenge(source.sitekey);
+  return (
+    challenge && {
+      question: "Type the digits you hear.",
+      token: challenge.token,
+      audio: `data:audio/wav;base64,${challenge.audio}`,
+    }
+  );
+}
+
+/**
+ * Text or audio question for users who can't solve the interactive challenges, e.g. with a screen
+ * reader.
+ */
+export default function AccessibilityQuestion(props: AccessibilityQuestionProps) {
+  const [inputRef, setInputRef] = createSignal<HTMLInputElement>();
+  const [answer, setAnswer] = createSignal("");
+  const [wrongAnswer, setWrongAnswer] = createSignal(false);
+  const [submitting, setSubmitting] = createSignal(false);
+  const [audio, setAudio] = createSignal(false);
+  // only fetched while open, questions are rate limited
+  const [challengeRes, challengeActions] = createResource(
+    () => props.open && { sitekey: props.sitekey, audio: audio() },
+    fetchQuestion,
+  );
+
+  createEffect(() => {
+    if (props.open && challengeRes()) inputRef()?.focus();
+  });
+
+  const onSubmit = async (event: SubmitEvent) => {
+    event.preventDefault();
+    const challenge = challengeRes();
+    if (!challenge || submitting()) return;
+
+    setSubmitting(true);
+    try {
+      const pow = await solveProofOfWork(props.sitekey);
+      const response =
+        pow &&
+        (await processAccessibility(
+          props.sitekey,
+          pow,
+          challenge.token,
+          answer(),
+        ));
+      if (!response) {
+        props.onError();
+      } else if (response.result === "success") {
+        props.onComplete(response.response.token);
+      } else {
+        setWrongAnswer(true);
+        setAnswer("");
+        await challengeActions.refetch();
+      }
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  const onClose = () => {
+    props.onClose();
+    setWrongAnswer(false);
+    setAnswer("");
+    setAudio(false);
+  };
+
+  const toggleAudio = () => {
+    setWrongAnswer(false);
+    setAnswer("");
+    setAudio(!audio());
+  };
+
+  return (
+    <Modal open={props.open} onClose={onClose}>
+      <form
+        class="bg-gray-50 dark:bg-gray-700 border-2 border-gray-400 dark:border-gray-600 rounded-lg p-5 w-[320px]"
+        onSubmit={onSubmit}
+      >
+        <h1 class="text-gray-700 dark:text-gray-50 text-xl text-center mb-4">
+          Answer the question
+        </h1>
+        <Show
+          when={challengeRes()}
+          fallback={
+            <p role="status">
+              {challengeRes.loading ? "Loading..." : "Something went wrong..."}
+            </p>
+          }
+        >
+          <label
+            for="gotcha-accessibility-answer"
+            class="block text-gray-700 dark:text-gray-50 mb-2"
+            aria-live="polite"
+          >
+            {challengeRes()!.question}
+          </label>
+          <Show when={challengeRes()!.audio}>
+            {(src) => <audio controls src={src()} class="w-full mb-2" />}
+          </Show>
+          <input
+            id="gotcha-accessibility-answer"
+            ref={setInputRef}
+            type="text"
+            inputmode={audio() ? "numeric" : "text"}
+            autocomplete="off"
+            required
+            value={answer()}
+            onInput={(e) => setAnswer(e.currentTarget.value)}
+            aria-invalid={wrongAnswer()}
+            aria-describedby="gotcha-accessibility-feedback"
+            class="w-full rounded border border-gray-400 px-2 py-1"
+          />
+          <p
+            id="gotcha-accessibility-feedback"
+            role="alert"
+            class="text-sm text-red-700 dark:text-red-300 min-h-5 mt-1"
+          >
+            {wrongAnswer() ? "Wrong answer, try this one." : ""}
+          </p>
+        </Show>
+
+        <div class="flex items-center justify-between mt-4">
+          <div class="flex gap-4">
+            <button
+              type="button"
+              aria-label="Close"
+              class="text-gray-400 hover:text-purple-700 dark:hover:text-purple-400"
+              onClick={onClose}
+            >
+              <CloseSvg />
+            </button>
+            <button
+              type="submit"
+              disabled={submitting() || !challengeRes()}
+              class="text-purple-500 hover:underline cursor-pointer disabled:text-gray-400"
+            >
+              {submitting() ? "Verifying..." : "Verify"}
+            </button>
+            <button
+              type="button"
+              class="text-purple-500 hover:underline cursor-pointer"
+              onClick={toggleAudio}
+            >
+              {audio() ? "Text question" : "Listen instead"}
+            </button>
+          </div>
+          <div class="w-1/4">
+            <Logo />
+          </div>
+        </div>
+      </form>
+    </Modal>
+  );
+}
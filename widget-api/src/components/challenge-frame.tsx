@@ -8,9 +8,15 @@ import {
   Switch,
 } from "solid-js";
 import { defaultRenderParams } from "../gotcha-captcha";
-import { fetchChallenge, processChallenge } from "../server";
+import {
+  fetchChallenge,
+  fetchChallengeInstance,
+  processChallenge,
+  processInstance,
+} from "../server";
 import CloseSvg from "./icons/close";
 import RefreshSvg from "./icons/refresh";
+import { solveProofOfWork } from "./im-not-a-robot";
 import Logo from "./logo";
 import Modal from "./modal";
 import { Challenge } from "./types";
@@ -18,11 +24,15 @@ import { Challenge } from "./types";
 type ChallengeFrameProps = {
   open: boolean;
   params: SearchParams;
+  /** Number of failed attempts, a new challenge is fetched when it changes. */
+  attempt?: number;
   onComplete: (response: string) => void;
   onFail: () => void;
   onError: () => void;
   onClose: () => void;
   onReroll?: () => void;
+  /** Switches to the slider puzzle, e.g. when the challenge can't load. */
+  onFallback?: () => void;
 };
 
 export default function ChallengeFrame(props: ChallengeFrameProps) {
@@ -31,13 +41,51 @@ export default function ChallengeFrame(props: ChallengeFrameProps) {
     iframeRef()?.focus();
   });
 
+  // refetches after a failed attempt, the server escalates the difficulty
   const [challengeRes, challengeActions] = createResource(
-    props.params.k,
+    () => ({ siteKey: props.params.k, attempt: props.attempt ?? 0 }),
     fetchChallengeWrapper,
   );
 
   const isSmallWindow = createMediaQuery("(max-width: 767px)");
 
+  // token of the instance sent to challenges verified by the server, answered once
+  let instanceToken: string | undefined;
+
+  const sendInstance = async () => {
+    const challenge = challengeRes();
+    instanceToken = undefined;
+    if (!challenge || challenge.kind === "widget") return;
+
+    const instance = await fetchChallengeInstance(props.params.k, challenge.url);
+    if (!instance) {
+      props.onError();
+      return;
+    }
+    instanceToken = instance.token;
+    iframeRef()?.contentWindow?.postMessage(
+      { type: "instance", data: instance.data },
+      new URL(challenge.url).origin,
+    );
+  };
+
+  const answerInstance = async (answer: unknown) => {
+    const token = instanceToken;
+    instanceToken = undefined;
+    if (!token) return;
+
+    const pow = await solveProofOfWork(props.params.k);
+    const response =
+      pow && (await processInstance(props.params.k, pow, token, answer));
+    if (!response) {
+      props.onError();
+    } else if (response.result === "success") {
+      props.onComplete(response.response.token);
+    } else {
+      props.onFail();
+    }
+  };
+
   const handleMessage = async (event: MessageEvent) => {
     const challenge = challengeRes();
     if (
@@ -61,6 +109,9 @@ export default function ChallengeFrame(props: ChallengeFrameProps) {
           message.success,
           challenge.url,
           message.interactions,
+          message.attempts,
+          message.game,
+          challenge.game?.token,
         );
         if (response) {
           props.onComplete(response);
@@ -68,6 +119,9 @@ export default function ChallengeFrame(props: ChallengeFrameProps) {
           props.onError();
         }
         break;
+      case "answer-callback":
+        await answerInstance(message.answer);
+        break;
       case "error-callback":
         props.onError();
         break;
@@ -100,13 +154,23 @@ export default function ChallengeFrame(props: ChallengeFrameProps) {
         >
           <Switch>
             <Match when={challengeRes.loading}>Loading...</Match>
-            <Match when={challengeRes.error}>Something went wrong...</Match>
+            <Match when={challengeRes.error || challengeRes() === null}>
+              <p>Something went wrong...</p>
+              <button
+                type="button"
+                class="text-purple-500 hover:underline cursor-pointer mt-2"
+                onClick={() => props.onFallback?.()}
+              >
+                Try a simple puzzle instead
+              </button>
+            </Match>
             <Match when={challengeRes()}>
               <iframe
                 ref={setIframeRef}
                 src={buildChallengeUrl(challengeRes()!, props.params)}
                 class="w-full h-full"
                 sandbox="allow-forms allow-scripts allow-same-origin"
+                onLoad={sendInstance}
               />
             </Match>
           </Switch>
@@ -131,6 +195,13 @@ export default function ChallengeFrame(props: ChallengeFrameProps) {
             >
               <RefreshSvg />
             </button>
+            <button
+              type="button"
+              class="text-sm text-gray-400 hover:text-purple-700 dark:hover:text-purple-400"
+              onClick={() => props.onFallback?.()}
+            >
+              Simple puzzle
+            </button>
           </div>
           <div class="w-1/4">
             <Logo />
@@ -141,9 +212,11 @@ export default function ChallengeFrame(props: ChallengeFrameProps) {
   );
 }
 
-export async function fetchChallengeWrapper(
-  siteKey: string,
-): Promise<Challenge | null> {
+export async function fetchChallengeWrapper({
+  siteKey,
+}: {
+  siteKey: string;
+}): Promise<Challenge | null> {
   const challenge = await fetchChallenge(siteKey);
   if (challenge === null) {
     return null;
@@ -156,14 +229,28 @@ export async function fetchChallengeWrapper(
     smallWidth: challenge.small_width,
     smallHeight: challenge.small_height,
     logoUrl: challenge.logo_url,
+    theme: challenge.theme,
+    localization: challenge.localization,
+    attempts: challenge.attempts,
+    kind: challenge.kind ?? "widget",
+    game: challenge.game ?? null,
   };
 }
 
 function buildChallengeUrl(challenge: Challenge, params: SearchParams): string {
   const url = new URL(challenge.url);
+  const { theme, localization } = challenge;
+  // render params set by the site take precedence over the console settings
+  const mode = theme.mode === "auto" ? undefined : theme.mode;
   url.searchParams.append("k", params.k);
-  url.searchParams.append("hl", params.hl ?? navigator.language);
-  url.searchParams.append("theme", params.theme ?? defaultRenderParams.theme!);
+  url.searchParams.append(
+    "hl",
+    params.hl ?? localization.locale ?? navigator.language,
+  );
+  url.searchParams.append(
+    "theme",
+    params.theme ?? mode ?? defaultRenderParams.theme!,
+  );
   url.searchParams.append("size", params.size ?? defaultRenderParams.size!);
   url.searchParams.append("badge", params.badge ?? defaultRenderParams.badge!);
   // TODO: window.location.origin makes no sense
@@ -171,6 +258,27 @@ function buildChallengeUrl(challenge: Challenge, params: SearchParams): string {
   if (challenge.logoUrl) {
     url.searchParams.append("logoUrl", challenge.logoUrl);
   }
+  if (theme.primary_color)
+    url.searchParams.append("primaryColor", theme.primary_color);
+  if (theme.background_color)
+    url.searchParams.append("backgroundColor", theme.background_color);
+  if (theme.text_color) url.searchParams.append("textColor", theme.text_color);
+  if (theme.corner_radius !== null)
+    url.searchParams.append("cornerRadius", theme.corner_radius.toString());
+  if (theme.font_family) url.searchParams.append("fontFamily", theme.font_family);
+  if (Object.keys(localization.strings).length > 0)
+    url.searchParams.append("strings", JSON.stringify(localization.strings));
+  url.searchParams.append(
+    "maxAttempts",
+    challenge.attempts.max_attempts.toString(),
+  );
+  if (challenge.attempts.moves_per_attempt !== null)
+    url.searchParams.append(
+      "movesPerAttempt",
+      challenge.attempts.moves_per_attempt.toString(),
+    );
+  if (challenge.game)
+    url.searchParams.append("seed", challenge.game.seed.toString());
 
   return url.toString();
 }
@@ -1,11 +1,16 @@
 import { createEffect, createSignal, Show } from "solid-js";
 import { PreAnalysisResponse } from "../server";
+import AccessibilityQuestion from "./accessibility-question";
 import ChallengeFrame from "./challenge-frame";
 import ImNotRobot from "./im-not-a-robot";
+import SliderPuzzle from "./slider-puzzle";
 import { ChallengeState, GotchaWidgetProps } from "./types";
 
 export function GotchaWidget(props: GotchaWidgetProps) {
   const [state, setState] = createSignal<ChallengeState>("blank");
+  const [failedAttempts, setFailedAttempts] = createSignal(0);
+  const [accessibilityOpen, setAccessibilityOpen] = createSignal(false);
+  const [puzzleOpen, setPuzzleOpen] = createSignal(false);
 
   createEffect(() => {
     if (props.liveState() === "expired") {
@@ -23,11 +28,14 @@ export function GotchaWidget(props: GotchaWidgetProps) {
   };
 
   const handleChallengeComplete = (token: string) => {
+    setAccessibilityOpen(false);
+    setPuzzleOpen(false);
     setState("verified");
     props.callback?.(token);
   };
 
   const handleFail = () => {
+    setFailedAttempts((attempts) => attempts + 1);
     setState("failed");
   };
 
@@ -47,13 +55,37 @@ export function GotchaWidget(props: GotchaWidgetProps) {
             state={state()}
             onStateChange={setState}
             onVerificationComplete={handlePreVerificationComplete}
+            onAccessibility={() => setAccessibilityOpen(true)}
             onError={handleError}
           />
+          <AccessibilityQuestion
+            open={accessibilityOpen()}
+            sitekey={props.sitekey}
+            onComplete={handleChallengeComplete}
+            onError={() => {
+              setAccessibilityOpen(false);
+              handleError();
+            }}
+            onClose={() => setAccessibilityOpen(false)}
+          />
+          <SliderPuzzle
+            open={puzzleOpen()}
+            sitekey={props.sitekey}
+            onComplete={handleChallengeComplete}
+            onError={() => {
+              setPuzzleOpen(false);
+              handleError();
+            }}
+            onClose={() => {
+              setPuzzleOpen(false);
+              setState("failed");
+            }}
+          />
           {/* dont run the challenge frame unless we are solving or doing proof of work.
             this way we control prefetching */}
           <Show when={state() === "challenging" || state() === "verifying"}>
             <ChallengeFrame
-              open={state() === "challenging"}
+              open={state() === "challenging" && !puzzleOpen()}
               params={{
                 k: props.sitekey,
                 // TODO: add language support
@@ -65,9 +97,11 @@ export function GotchaWidget(props: GotchaWidgetProps) {
                 // TODO: add branding support
                 logoUrl: null,
               }}
+              attempt={failedAttempts()}
               onComplete={handleChallengeComplete}
               onFail={handleFail}
               onError={handleError}
+              onFallback={() => setPuzzleOpen(true)}
               onClose={() => {
                 if (state() != "verified" && state() != "error") {
                   setState("failed");
@@ -1,13 +1,12 @@
 import { Interaction } from "@gotcha-widget/lib";
 import * as jose from "jose";
-import { createEffect } from "solid-js";
+import { createEffect, createSignal } from "solid-js";
 import { RenderParams } from "../gotcha-captcha";
-import { PowChallenge, ProofOfWork } from "../proof-of-work";
+import { PowChallenge, PowProgress, ProofOfWork } from "../proof-of-work";
 import {
   getProofOfWorkChallenge,
   PowResult,
   PreAnalysisResponse,
-  processAccessibility,
   processPreAnalysis,
 } from "../server";
 import Checkbox, { CheckboxState } from "./checkbox";
@@ -19,11 +18,13 @@ type ImNotRobotProps = {
   state: ChallengeState;
   onStateChange: (state: ChallengeState) => void;
   onVerificationComplete: (response: PreAnalysisResponse) => void;
+  onAccessibility: () => void;
   onError: () => void;
 };
 
 export default function ImNotRobot(props: ImNotRobotProps) {
   const interactions: Interaction[] = [];
+  const [progress, setProgress] = createSignal<number>();
 
   createEffect(() => {
     const cleanup = captureInteractions(interactions);
@@ -41,9 +42,14 @@ export default function ImNotRobot(props: ImNotRobotProps) {
       return;
 
     props.onStateChange("verifying");
+    setProgress(0);
 
     try {
-      const powResult = await solveProofOfWork(props.params.sitekey);
+      const powResult = await solveProofOfWork(
+        props.params.sitekey,
+        (solved, total) => setProgress(Math.floor((solved / total) * 100)),
+      );
+      setProgress(undefined);
       if (!powResult) {
         console.error(powResult);
         props.onStateChange("error");
@@ -62,6 +68,7 @@ export default function ImNotRobot(props: ImNotRobotProps) {
       props.onVerificationComplete(response);
     } catch (e) {
       console.error(e);
+      setProgress(undefined);
       props.onStateChange("error");
       props.onError();
     }
@@ -79,19 +86,19 @@ export default function ImNotRobot(props: ImNotRobotProps) {
               )
             }
           />
-          <span class={`${getTextClass(props.state)}`}>
-            {getText(props.state)}
+          <span class={`${getTextClass(props.state)}`} aria-live="polite">
+            {getText(props.state, progress())}
           </span>
         </div>
         <div class="pr-3 flex flex-col justify-evenly items-center max-w-[35%]">
           <Logo />
           <button
             type="button"
-            onClick={() =>
-              handleVerification((pow) =>
-                processAccessibility(props.params.sitekey, pow),
-              )
-            }
+            onClick={() => {
+              if (props.state === "verified" || props.state === "verifying")
+                return;
+              props.onAccessibility();
+            }}
             class="text-purple-500 text-xs self-end hover:underline cursor-pointer"
           >
             Accessibility
@@ -114,17 +121,20 @@ function checkboxState(state: ChallengeState): CheckboxState {
   }
 }
 
-async function solveProofOfWork(siteKey: string): Promise<PowResult | null> {
+export async function solveProofOfWork(
+  siteKey: string,
+  onProgress?: PowProgress,
+): Promise<PowResult | null> {
   const powChallenge = await getProofOfWorkChallenge(siteKey);
   if (!powChallenge) {
     return null;
   }
   const claims: PowChallenge = jose.decodeJwt(powChallenge.token);
-  const solution = await ProofOfWork.solve(claims);
-  return { challenge: powChallenge.token, solution };
+  const solutions = await ProofOfWork.solve(claims, onProgress);
+  return { challenge: powChallenge.token, solutions };
 }
 
-function getText(state: ChallengeState): string {
+function getText(state: ChallengeState, progress?: number): string {
   switch (state) {
     case "verified":
       return "Verified!";
@@ -135,6 +145,7 @@ function getText(state: ChallengeState): string {
     case "error":
       return "Something went wrong.";
     case "verifying":
+      return progress === undefined ? "Verifying..." : `Verifying... ${progress}%`;
     case "challenging":
       return "Verifying...";
     default:
@@ -159,10 +170,17 @@ function getTextClass(state: ChallengeState): string {
   }
 }
 
-function captureInteractions(interactions: Interaction[]): () => void {
+/** Interactions kept by `captureInteractions`, the oldest are dropped past it. */
+export const MAX_INTERACTIONS = 1000;
+
+export function captureInteractions(interactions: Interaction[]): () => void {
+  const record = (interaction: Interaction) => {
+    interactions.push(interaction);
+    if (interactions.length > MAX_INTERACTIONS) interactions.shift();
+  };
   const handlers = {
     mousemove: (evt: MouseEvent) => {
-      interactions.push({
+      record({
         ts: Date.now(),
         event: {
           kind: "mousemovement",
@@ -172,7 +190,7 @@ function captureInteractions(interactions: Interaction[]): () => void {
       });
     },
     mouseup: (evt: MouseEvent) => {
-      interactions.push({
+      record({
         ts: Date.now(),
         event: {
           kind: "mouseclick",
@@ -181,7 +199,7 @@ function captureInteractions(interactions: Interaction[]): () => void {
       });
     },
     mousedown: (evt: MouseEvent) => {
-      interactions.push({
+      record({
         ts: Date.now(),
         event: {
           kind: "mouseclick",
@@ -190,7 +208,7 @@ function captureInteractions(interactions: Interaction[]): () => void {
       });
     },
     mouseenter: (evt: MouseEvent) => {
-      interactions.push({
+      record({
         ts: Date.now(),
         event: {
           kind: "mouseenter",
@@ -199,7 +217,7 @@ function captureInteractions(interactions: Interaction[]): () => void {
       });
     },
     mouseleave: (evt: MouseEvent) => {
-      interactions.push({
+      record({
         ts: Date.now(),
         event: {
           kind: "mouseenter",
@@ -208,7 +226,7 @@ function captureInteractions(interactions: Interaction[]): () => void {
       });
     },
     keyup: (evt: KeyboardEvent) => {
-      interactions.push({
+      record({
         ts: Date.now(),
         event: {
           kind: "keypress",
@@ -218,7 +236,7 @@ function captureInteractions(interactions: Interaction[]): () => void {
       });
     },
     keydown: (evt: KeyboardEvent) => {
-      interactions.push({
+      record({
         ts: Date.now(),
         event: {
           kind: "keypress",
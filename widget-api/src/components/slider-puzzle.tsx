@@ -0,0 +1,158 @@
+import { createResource, createSignal, Show } from "solid-js";
+import { fetchPuzzleChallenge, processPuzzle } from "../server";
+import { solveProofOfWork } from "./im-not-a-robot";
+import CloseSvg from "./icons/close";
+import RefreshSvg from "./icons/refresh";
+import Logo from "./logo";
+import Modal from "./modal";
+
+// sizes of the images rendered by the server, see `challenges::puzzle`
+const PUZZLE_WIDTH = 280;
+const PUZZLE_HEIGHT = 160;
+const PIECE_WIDTH = 52;
+
+type SliderPuzzleProps = {
+  open: boolean;
+  sitekey: string;
+  onComplete: (response: string) => void;
+  onError: () => void;
+  onClose: () => void;
+};
+
+/** Slider puzzle rendered by the server, for when the interactive challenge can't load. */
+export default function SliderPuzzle(props: SliderPuzzleProps) {
+  const [offset, setOffset] = createSignal(0);
+  const [misplaced, setMisplaced] = createSignal(false);
+  const [submitting, setSubmitting] = createSignal(false);
+  // only fetched while open, each puzzle takes a single answer
+  const [puzzleRes, puzzleActions] = createResource(
+    () => props.open && props.sitekey,
+    fetchPuzzleChallenge,
+  );
+
+  const refetch = async () => {
+    setOffset(0);
+    await puzzleActions.refetch();
+  };
+
+  const onSubmit = async (event: SubmitEvent) => {
+    event.preventDefault();
+    const puzzle = puzzleRes();
+    if (!puzzle || submitting()) return;
+
+    setSubmitting(true);
+    try {
+      const pow = await solveProofOfWork(props.sitekey);
+      const response =
+        pow && (await processPuzzle(props.sitekey, pow, puzzle.token, offset()));
+      if (!response) {
+        props.onError();
+      } else if (response.result === "success") {
+        props.onComplete(response.response.token);
+      } else {
+        setMisplaced(true);
+        await refetch();
+      }
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  const onClose = () => {
+    props.onClose();
+    setMisplaced(false);
+    setOffset(0);
+  };
+
+  return (
+    <Modal open={props.open} onClose={onClose}>
+      <form
+        class="bg-gray-50 dark:bg-gray-700 border-2 border-gray-400 dark:border-gray-600 rounded-lg p-5"
+        onSubmit={onSubmit}
+      >
+        <h1 class="text-gray-700 dark:text-gray-50 text-xl text-center mb-4">
+          Slide the piece into the hole
+        </h1>
+        <Show
+          when={puzzleRes()}
+          fallback={
+            <p role="status">
+              {puzzleRes.loading ? "Loading..." : "Something went wrong..."}
+            </p>
+          }
+        >
+          <div
+            class="relative mx-auto"
+            style={{
+              width: `${PUZZLE_WIDTH}px`,
+              height: `${PUZZLE_HEIGHT}px`,
+            }}
+          >
+            <img
+              src={`data:image/png;base64,${puzzleRes()!.background}`}
+              alt=""
+              draggable={false}
+            />
+            <img
+              src={`data:image/png;base64,${puzzleRes()!.piece}`}
+              alt=""
+              draggable={false}
+              class="absolute"
+              style={{
+                left: `${offset()}px`,
+                top: `${puzzleRes()!.piece_y}px`,
+              }}
+            />
+          </div>
+          <input
+            type="range"
+            min={0}
+            max={PUZZLE_WIDTH - PIECE_WIDTH}
+            value={offset()}
+            onInput={(e) => setOffset(e.currentTarget.valueAsNumber)}
+            aria-label="Piece position"
+            class="w-full mt-3 accent-purple-500"
+            style={{ "max-width": `${PUZZLE_WIDTH}px` }}
+          />
+          <p
+            role="alert"
+            class="text-sm text-red-700 dark:text-red-300 min-h-5 mt-1"
+          >
+            {misplaced() ? "Not quite, try this one." : ""}
+          </p>
+        </Show>
+
+        <div class="flex items-center justify-between mt-4">
+          <div class="flex gap-4">
+            <button
+              type="button"
+              aria-label="Close"
+              class="text-gray-400 hover:text-purple-700 dark:hover:text-purple-400"
+              onClick={onClose}
+            >
+              <CloseSvg />
+            </button>
+            <button
+              type="button"
+              aria-label="New puzzle"
+              class="text-gray-400 hover:text-purple-700 dark:hover:text-purple-400"
+              onClick={refetch}
+            >
+              <RefreshSvg />
+            </button>
+            <button
+              type="submit"
+              disabled={submitting() || !puzzleRes()}
+              class="text-purple-500 hover:underline cursor-pointer disabled:text-gray-400"
+            >
+              {submitting() ? "Verifying..." : "Verify"}
+            </button>
+          </div>
+          <div class="w-1/4">
+            <Logo />
+          </div>
+        </div>
+      </form>
+    </Modal>
+  );
+}
@@ -1,5 +1,12 @@
 import { Accessor } from "solid-js";
 import { RenderParams } from "../gotcha-captcha";
+import {
+  AttemptLimits,
+  ChallengeKind,
+  GameSeed,
+  Localization,
+  Theme,
+} from "../server";
 import { LiveState } from "../widget";
 
 export type ChallengeState =
@@ -22,4 +29,9 @@ export type Challenge = {
   smallWidth: number;
   smallHeight: number;
   logoUrl: string | null;
+  theme: Theme;
+  localization: Localization;
+  attempts: AttemptLimits;
+  kind: ChallengeKind;
+  game: GameSeed | null;
 };
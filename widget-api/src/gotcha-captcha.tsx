@@ -1,10 +1,17 @@
+import { Interaction } from "@gotcha-widget/lib";
 import { render } from "solid-js/web";
+import {
+  captureInteractions,
+  solveProofOfWork,
+} from "./components/im-not-a-robot";
 import { getJsParams } from "./js-params";
-import { siteVerify, VerificationResponse } from "./server";
+import { processInvisible, siteVerify, VerificationResponse } from "./server";
 import { createWidget, Widget } from "./widget";
 
 export class GotchaCaptcha {
   widgets: Widget[] = [];
+  private interactions: Interaction[] = [];
+  private capturing = false;
 
   constructor() {
     const { onload, render, hl } = getJsParams();
@@ -32,6 +39,7 @@ export class GotchaCaptcha {
     if (element === null) {
       return null;
     }
+    this.startCapture();
 
     const widgetId = this.widgets.length;
     const containerId =
@@ -79,6 +87,23 @@ export class GotchaCaptcha {
     this.setResponseTextarea(null, widgetId);
   }
 
+  /**
+   * Invisible mode, the site key must have it enabled. Solves the proof of work in the background
+   * and resolves with a token carrying the interaction score for `action`, without ever prompting
+   * the user with a challenge.
+   */
+  async execute(
+    sitekey: string,
+    options?: ExecuteOptions,
+  ): Promise<string | null> {
+    this.startCapture();
+    const pow = await solveProofOfWork(sitekey);
+    if (!pow) return null;
+    // each execution only sends what happened since the previous one
+    const interactions = this.interactions.splice(0);
+    return processInvisible(sitekey, pow, interactions, options?.action);
+  }
+
   getResponse(widgetId?: number): string | null {
     return this.getResponseElement(widgetId)?.textContent ?? null;
   }
@@ -106,6 +131,13 @@ export class GotchaCaptcha {
     return siteVerify(secret, response);
   }
 
+  /** Captures the page's interactions from the first widget rendered or executed on. */
+  private startCapture() {
+    if (this.capturing) return;
+    captureInteractions(this.interactions);
+    this.capturing = true;
+  }
+
   private getResponseElement(widgetId: number = 0): Element | null {
     return document.getElementById(
       widgetId === 0 ? "gotcha-response" : `gotcha-response-${widgetId}`,
@@ -167,6 +199,14 @@ export type RenderParams = {
   isolated?: boolean;
 };
 
+/**
+ * Options for executing in invisible mode
+ */
+export type ExecuteOptions = {
+  /** Name of the action being protected, echoed back on site verification */
+  action?: string;
+};
+
 /**
  * Default configuration parameters for the widget
  */
@@ -0,0 +1,34 @@
+import assert from "node:assert/strict";
+import { test } from "node:test";
+
+import { ProofOfWork, scrypt } from "./proof-of-work.ts";
+
+const text = (s: string) => new TextEncoder().encode(s);
+const hex = (bytes: Uint8Array) => Buffer.from(bytes).toString("hex");
+
+// RFC 7914 section 12, the vector with `p = 16` is left out as only `p = 1` is implemented
+test("scrypt matches the RFC 7914 test vectors", async () => {
+  assert.equal(
+    hex(await scrypt(text(""), text(""), 4, 1, 64)),
+    "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442" +
+      "fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+  );
+  assert.equal(
+    hex(await scrypt(text("pleaseletmein"), text("SodiumChloride"), 14, 8, 64)),
+    "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2" +
+      "d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887",
+  );
+});
+
+// same challenge as `scrypt_matches_the_widget` in `server/src/analysis/proof_of_work.rs`
+test("scrypt challenges are solved as the server verifies them", async () => {
+  const solutions = await ProofOfWork.solve({
+    nonce: 4077096492,
+    difficulty: 4,
+    timestamp: 1739555092,
+    algorithm: { name: "scrypt", log_n: 10, r: 8 },
+    puzzles: 2,
+  });
+
+  assert.deepEqual(solutions, [0, 13]);
+});
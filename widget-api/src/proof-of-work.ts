@@ -1,54 +1,92 @@
+/** Hash the challenge is solved with, see `analysis::proof_of_work::PowAlgorithm`. */
+export type PowAlgorithm =
+  | { name: "sha256" }
+  | { name: "scrypt"; log_n: number; r: number };
+
 export interface PowChallenge {
   nonce: number;
   difficulty: number;
   timestamp: number;
+  /** SHA-256 when absent. */
+  algorithm?: PowAlgorithm;
+  /** Sub-puzzles to solve, each one's index is hashed along with its solution. */
+  puzzles: number;
 }
 
+/** Called after each sub-puzzle with the number solved and the total. */
+export type PowProgress = (solved: number, total: number) => void;
+
 export class ProofOfWork {
   private static readonly HASH_ALGORITHM = "SHA-256";
 
-  public static async solve(challenge: PowChallenge): Promise<number> {
-    if (challenge.difficulty === 0 || challenge.difficulty > 32) {
+  public static async solve(
+    challenge: PowChallenge,
+    onProgress?: PowProgress,
+  ): Promise<number[]> {
+    const algorithm = challenge.algorithm ?? { name: "sha256" };
+    const maxDifficulty = algorithm.name === "sha256" ? 32 : 16;
+    if (challenge.difficulty === 0 || challenge.difficulty > maxDifficulty) {
       throw new Error("Invalid difficulty");
     }
 
-    let solution = 0;
-    while (true) {
-      const hash = await this.hashSolution(challenge, solution);
-      if (this.isSolution(hash, challenge.difficulty)) {
-        return solution;
-      }
+    const solutions: number[] = [];
+    for (let puzzle = 0; puzzle < challenge.puzzles; puzzle++) {
+      let solution = 0;
+      while (true) {
+        const hash = await this.hashSolution(challenge, puzzle, solution);
+        if (this.isSolution(hash, challenge.difficulty, algorithm)) {
+          break;
+        }
 
-      solution++;
+        solution++;
+      }
+      solutions.push(solution);
+      onProgress?.(puzzle + 1, challenge.puzzles);
     }
+    return solutions;
   }
 
-  private static isSolution(hash: string, difficulty: number): boolean {
-    const prefix = "0".repeat(difficulty);
-    return hash.startsWith(prefix);
+  private static isSolution(
+    hash: Uint8Array,
+    difficulty: number,
+    algorithm: PowAlgorithm,
+  ): boolean {
+    // sha256 difficulties count leading zero hex digits, memory-hard ones leading zero bits
+    const bits = algorithm.name === "sha256" ? difficulty * 4 : difficulty;
+    for (let i = 0; i < bits; i++) {
+      if (hash[i >> 3] & (0x80 >> (i & 7))) return false;
+    }
+    return true;
   }
 
   private static async hashSolution(
     challenge: PowChallenge,
+    puzzle: number,
     solution: number,
-  ): Promise<string> {
+  ): Promise<Uint8Array> {
     const nonce_bytes = this.toBeBytes(challenge.nonce, 4);
     const difficulty_bytes = this.toBeBytes(challenge.difficulty, 2);
     const timestamp_bytes = this.toBeBytes(challenge.timestamp, 8, true);
-    const solution_bytes = this.toBeBytes(solution, 4);
-
-    const data = new Uint8Array([
+    const puzzle_bytes = this.toBeBytes(puzzle, 2);
+    const solution_bytes = this.toBeBytes(solution, 8);
+    const salt = new Uint8Array([
       ...nonce_bytes,
       ...difficulty_bytes,
       ...timestamp_bytes,
-      ...solution_bytes,
+      ...puzzle_bytes,
     ]);
 
-    const hashBuffer = await crypto.subtle.digest(this.HASH_ALGORITHM, data);
-    const hash = Array.from(new Uint8Array(hashBuffer))
-      .map((b) => b.toString(16).padStart(2, "0"))
-      .join("");
-    return hash;
+    const algorithm = challenge.algorithm ?? { name: "sha256" };
+    switch (algorithm.name) {
+      case "sha256": {
+        const data = new Uint8Array([...salt, ...solution_bytes]);
+        return new Uint8Array(
+          await crypto.subtle.digest(this.HASH_ALGORITHM, data),
+        );
+      }
+      case "scrypt":
+        return scrypt(solution_bytes, salt, algorithm.log_n, algorithm.r, 32);
+    }
   }
 
   private static toBeBytes(
@@ -85,3 +123,109 @@ export class ProofOfWork {
     return new Uint8Array(buffer);
   }
 }
+
+/**
+ * scrypt with `p = 1` (RFC 7914), the salsa20/8 mixing done here and PBKDF2 by WebCrypto. Checked
+ * against the RFC test vectors and the server's hashes in `proof-of-work.test.ts`.
+ */
+export async function scrypt(
+  password: Uint8Array,
+  salt: Uint8Array,
+  logN: number,
+  r: number,
+  length: number,
+): Promise<Uint8Array> {
+  const blockWords = 32 * r;
+  const b = await pbkdf2(password, salt, blockWords * 4);
+
+  const x = new Uint32Array(blockWords);
+  const view = new DataView(b.buffer);
+  for (let i = 0; i < blockWords; i++) x[i] = view.getUint32(i * 4, true);
+  roMix(x, 1 << logN, r);
+  for (let i = 0; i < blockWords; i++) view.setUint32(i * 4, x[i], true);
+
+  return pbkdf2(password, b, length);
+}
+
+async function pbkdf2(
+  password: Uint8Array,
+  salt: Uint8Array,
+  length: number,
+): Promise<Uint8Array> {
+  const key = await crypto.subtle.importKey("raw", password, "PBKDF2", false, [
+    "deriveBits",
+  ]);
+  const bits = await crypto.subtle.deriveBits(
+    { name: "PBKDF2", salt, iterations: 1, hash: "SHA-256" },
+    key,
+    length * 8,
+  );
+  return new Uint8Array(bits);
+}
+
+function roMix(x: Uint32Array, n: number, r: number) {
+  const blockWords = 32 * r;
+  const v = new Uint32Array(n * blockWords);
+  const y = new Uint32Array(blockWords);
+  for (let i = 0; i < n; i++) {
+    v.set(x, i * blockWords);
+    blockMix(x, y, r);
+  }
+  for (let i = 0; i < n; i++) {
+    const j = x[(2 * r - 1) * 16] & (n - 1);
+    for (let k = 0; k < blockWords; k++) x[k] ^= v[j * blockWords + k];
+    blockMix(x, y, r);
+  }
+}
+
+/** Mixes `b` in place, using `y` as scratch space. */
+function blockMix(b: Uint32Array, y: Uint32Array, r: number) {
+  const x = b.slice((2 * r - 1) * 16, 2 * r * 16);
+  for (let i = 0; i < 2 * r; i++) {
+    for (let k = 0; k < 16; k++) x[k] ^= b[i * 16 + k];
+    salsa20_8(x);
+    // even blocks go to the first half, odd ones to the second
+    y.set(x, ((i >> 1) + (i & 1) * r) * 16);
+  }
+  b.set(y);
+}
+
+function salsa20_8(b: Uint32Array) {
+  const x = b.slice();
+  const rotl = (a: number, s: number) => (a << s) | (a >>> (32 - s));
+  for (let i = 0; i < 8; i += 2) {
+    x[4] ^= rotl(x[0] + x[12], 7);
+    x[8] ^= rotl(x[4] + x[0], 9);
+    x[12] ^= rotl(x[8] + x[4], 13);
+    x[0] ^= rotl(x[12] + x[8], 18);
+    x[9] ^= rotl(x[5] + x[1], 7);
+    x[13] ^= rotl(x[9] + x[5], 9);
+    x[1] ^= rotl(x[13] + x[9], 13);
+    x[5] ^= rotl(x[1] + x[13], 18);
+    x[14] ^= rotl(x[10] + x[6], 7);
+    x[2] ^= rotl(x[14] + x[10], 9);
+    x[6] ^= rotl(x[2] + x[14], 13);
+    x[10] ^= rotl(x[6] + x[2], 18);
+    x[3] ^= rotl(x[15] + x[11], 7);
+    x[7] ^= rotl(x[3] + x[15], 9);
+    x[11] ^= rotl(x[7] + x[3], 13);
+    x[15] ^= rotl(x[11] + x[7], 18);
+    x[1] ^= rotl(x[0] + x[3], 7);
+    x[2] ^= rotl(x[1] + x[0], 9);
+    x[3] ^= rotl(x[2] + x[1], 13);
+    x[0] ^= rotl(x[3] + x[2], 18);
+    x[6] ^= rotl(x[5] + x[4], 7);
+    x[7] ^= rotl(x[6] + x[5], 9);
+    x[4] ^= rotl(x[7] + x[6], 13);
+    x[5] ^= rotl(x[4] + x[7], 18);
+    x[11] ^= rotl(x[10] + x[9], 7);
+    x[8] ^= rotl(x[11] + x[10], 9);
+    x[9] ^= rotl(x[8] + x[11], 13);
+    x[10] ^= rotl(x[9] + x[8], 18);
+    x[12] ^= rotl(x[15] + x[14], 7);
+    x[13] ^= rotl(x[12] + x[15], 9);
+    x[14] ^= rotl(x[13] + x[12], 13);
+    x[15] ^= rotl(x[14] + x[13], 18);
+  }
+  for (let i = 0; i < 16; i++) b[i] += x[i];
+}
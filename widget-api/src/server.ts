@@ -4,6 +4,9 @@ export type VerificationResponse = {
   success: boolean;
   challenge_ts: string;
   hostname: string | null;
+  score?: number;
+  challenge_type?: ChallengeType;
+  action?: string;
   error_codes: ErrorCodes[] | null;
 };
 
@@ -13,7 +16,16 @@ export type ErrorCodes =
   | "missing-input-response"
   | "invalid-input-response"
   | "bad-request"
-  | "timeout-or-duplicate";
+  | "timeout-or-duplicate"
+  | "remoteip-mismatch"
+  | "sitekey-secret-mismatch";
+
+export type ChallengeType =
+  | "pre-analysis"
+  | "interactive"
+  | "accessibility"
+  | "puzzle"
+  | "invisible";
 
 export async function siteVerify(
   secret: string,
@@ -44,6 +56,25 @@ export async function siteVerify(
   }
 }
 
+export type Theme = {
+  mode: "light" | "dark" | "auto";
+  primary_color: string | null;
+  background_color: string | null;
+  text_color: string | null;
+  corner_radius: number | null;
+  font_family: string | null;
+};
+
+export type Localization = {
+  locale: string | null;
+  strings: Record<string, string>;
+};
+
+export type AttemptLimits = {
+  max_attempts: number;
+  moves_per_attempt: number | null;
+};
+
 export type FetchChallenge = {
   url: string;
   width: number;
@@ -51,14 +82,38 @@ export type FetchChallenge = {
   small_width: number;
   small_height: number;
   logo_url: string | null;
+  theme: Theme;
+  localization: Localization;
+  attempts: AttemptLimits;
+  kind: ChallengeKind;
+  game?: GameSeed | null;
 };
 
+/** Seed games are generated with, its token is sent back with the result. */
+export type GameSeed = { seed: number; token: string };
+
+/** How answers are verified, challenges other than widgets are sent an instance to render. */
+export type ChallengeKind = "widget" | "puzzle" | "remote";
+
+export type DeviceClass = "desktop" | "mobile" | "low-end-mobile";
+
+export function deviceClass(): DeviceClass {
+  if (!window.matchMedia("(pointer: coarse)").matches) return "desktop";
+  // `deviceMemory` is only available in chromium based browsers
+  const memory = (navigator as { deviceMemory?: number }).deviceMemory;
+  const lowEnd =
+    (memory !== undefined && memory <= 2) || navigator.hardwareConcurrency <= 4;
+  return lowEnd ? "low-end-mobile" : "mobile";
+}
+
+/** Difficulty escalation is tracked by the server, the device class is only a hint. */
 export async function fetchChallenge(
   siteKey: string,
 ): Promise<FetchChallenge | null> {
   try {
     const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
     const url = new URL(`${origin}/api/challenge?site_key=${siteKey}`);
+    url.searchParams.append("device", deviceClass());
 
     const response = await fetch(url);
     return await response.json();
@@ -72,11 +127,30 @@ type ChallengeResponse = {
   token: string;
 };
 
+export type TracePoint = { x: number; y: number; t_ms: number };
+
+/** How a game challenge was played, sent by widgets built on `gotcha-plugin`. */
+export type GameResult = {
+  solve_time_ms: number;
+  seed?: number;
+  traces: {
+    success: boolean;
+    moves: {
+      path: TracePoint[];
+      impulse: [number, number, number] | null;
+      keys?: number[];
+    }[];
+  }[];
+};
+
 export async function processChallenge(
   siteKey: string,
   success: boolean,
   challengeUrl: string,
   interactions: Interaction[],
+  attempts?: number,
+  game?: GameResult,
+  seedToken?: string,
 ): Promise<string | null> {
   try {
     const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
@@ -92,6 +166,9 @@ export async function processChallenge(
         hostname: window.location.hostname,
         challenge: challengeUrl,
         interactions,
+        attempts,
+        game,
+        seed_token: seedToken,
       }),
     });
     if (response.status !== 200)
@@ -107,10 +184,10 @@ export async function processChallenge(
   }
 }
 
-export type PowResult = { challenge: string; solution: number };
+export type PowResult = { challenge: string; solutions: number[] };
 
 export type PreAnalysisResponse =
-  | { result: "failure" }
+  | { result: "failure"; min_difficulty?: number }
   | { result: "success"; response: { token: string } };
 
 export async function processPreAnalysis(
@@ -145,9 +222,63 @@ export async function processPreAnalysis(
   }
 }
 
+/** Question answered instead of an interactive challenge, `token` goes back with the answer. */
+export type AccessibilityChallenge = {
+  question: string;
+  token: string;
+};
+
+export async function fetchAccessibilityChallenge(
+  siteKey: string,
+): Promise<AccessibilityChallenge | null> {
+  try {
+    const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
+    const url = new URL(
+      `${origin}/api/challenge/accessibility?site_key=${siteKey}`,
+    );
+    const response = await fetch(url);
+    if (response.status !== 200)
+      throw new Error(
+        `fetchAccessibilityChallenge returned status code ${response.status}`,
+      );
+
+    return await response.json();
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+}
+
+export type AudioChallenge = {
+  /** WAV clip encoded in base64. */
+  audio: string;
+  token: string;
+};
+
+export async function fetchAudioChallenge(
+  siteKey: string,
+): Promise<AudioChallenge | null> {
+  try {
+    const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
+    const url = new URL(`${origin}/api/challenge/audio?site_key=${siteKey}`);
+    const response = await fetch(url);
+    if (response.status !== 200)
+      throw new Error(
+        `fetchAudioChallenge returned status code ${response.status}`,
+      );
+
+    return await response.json();
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+}
+
 export async function processAccessibility(
   site_key: string,
-  proofOfWork: { challenge: string; solution: number },
+  proofOfWork: PowResult,
+  question: string,
+  answer: string,
 ): Promise<PreAnalysisResponse | null> {
   try {
     const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
@@ -161,6 +292,8 @@ export async function processAccessibility(
         site_key,
         hostname: window.location.hostname,
         proof_of_work: proofOfWork,
+        question,
+        answer,
       }),
     });
     if (response.status !== 200)
@@ -175,6 +308,160 @@ export async function processAccessibility(
   }
 }
 
+export type PuzzleChallenge = {
+  /** PNG of the picture with the hole, encoded in base64. */
+  background: string;
+  /** PNG of the piece to slide, encoded in base64. */
+  piece: string;
+  piece_y: number;
+  token: string;
+};
+
+export async function fetchPuzzleChallenge(
+  siteKey: string,
+): Promise<PuzzleChallenge | null> {
+  try {
+    const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
+    const url = new URL(`${origin}/api/challenge/puzzle?site_key=${siteKey}`);
+    const response = await fetch(url);
+    if (response.status !== 200)
+      throw new Error(
+        `fetchPuzzleChallenge returned status code ${response.status}`,
+      );
+
+    return await response.json();
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+}
+
+export async function processPuzzle(
+  site_key: string,
+  proofOfWork: PowResult,
+  puzzle: string,
+  answer_x: number,
+): Promise<PreAnalysisResponse | null> {
+  try {
+    const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
+    const url = new URL(`${origin}/api/challenge/process-puzzle`);
+    const response = await fetch(url, {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({
+        site_key,
+        hostname: window.location.hostname,
+        proof_of_work: proofOfWork,
+        puzzle,
+        answer_x,
+      }),
+    });
+    if (response.status !== 200)
+      throw new Error(`processPuzzle returned status code ${response.status}`);
+
+    return await response.json();
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+}
+
+export type ChallengeInstance = {
+  data: unknown;
+  token: string;
+};
+
+export async function fetchChallengeInstance(
+  siteKey: string,
+  challenge: string,
+): Promise<ChallengeInstance | null> {
+  try {
+    const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
+    const url = new URL(`${origin}/api/challenge/instance`);
+    url.searchParams.append("site_key", siteKey);
+    url.searchParams.append("challenge", challenge);
+    const response = await fetch(url);
+    if (response.status !== 200)
+      throw new Error(
+        `fetchChallengeInstance returned status code ${response.status}`,
+      );
+
+    return await response.json();
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+}
+
+export async function processInstance(
+  site_key: string,
+  proofOfWork: PowResult,
+  instance: string,
+  answer: unknown,
+): Promise<PreAnalysisResponse | null> {
+  try {
+    const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
+    const url = new URL(`${origin}/api/challenge/process-instance`);
+    const response = await fetch(url, {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({
+        site_key,
+        hostname: window.location.hostname,
+        proof_of_work: proofOfWork,
+        instance,
+        answer,
+      }),
+    });
+    if (response.status !== 200)
+      throw new Error(`processInstance returned status code ${response.status}`);
+
+    return await response.json();
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+}
+
+export async function processInvisible(
+  site_key: string,
+  proofOfWork: PowResult,
+  interactions: Interaction[],
+  action?: string,
+): Promise<string | null> {
+  try {
+    const origin = import.meta.env.VITE_GOTCHA_SV_ORIGIN;
+    const url = new URL(`${origin}/api/challenge/process-invisible`);
+    const response = await fetch(url, {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({
+        site_key,
+        hostname: window.location.hostname,
+        action,
+        interactions,
+        proof_of_work: proofOfWork,
+      }),
+    });
+    if (response.status !== 200)
+      throw new Error(
+        `processInvisible returned status code ${response.status}`,
+      );
+    const { token }: ChallengeResponse = await response.json();
+
+    return token;
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+}
+
 export type ProofOfWorkChallenge = {
   token: string;
 };
@@ -3,15 +3,23 @@ use bevy::{
     prelude::*,
 };
 use bevy_rapier3d::prelude::*;
-use gotcha_plugin::{AttemptCount, GameplayAttempt, GotchaState};
+use gotcha_plugin::{AttemptCount, AttemptLimits, GameSeed, GameplayAttempt, GotchaState};
 
-use crate::{cup::*, throwable::ThrowablesLeftCount};
+use crate::{
+    cup::*,
+    throwable::{THROWABLES_PER_ATTEMPT, ThrowablesLeftCount},
+};
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, (setup_lighting, setup_entities));
+        // the seed issued by the server arrives with the challenge configuration
+        app.add_systems(
+            Update,
+            respawn_cups.run_if(resource_changed::<GameSeed>.and(in_state(GotchaState::Welcome))),
+        );
         app.add_systems(
             PreUpdate,
             check_game_over.run_if(not(
@@ -46,8 +54,11 @@ pub fn is_first_attempt(attempts: Res<AttemptCount>) -> bool {
     attempts.0 == 0
 }
 
-fn setup_throwables_left(mut throwables_left: ResMut<ThrowablesLeftCount>) {
-    throwables_left.0 = 3;
+fn setup_throwables_left(
+    mut throwables_left: ResMut<ThrowablesLeftCount>,
+    limits: Res<AttemptLimits>,
+) {
+    throwables_left.0 = limits.moves_per_attempt.unwrap_or(THROWABLES_PER_ATTEMPT);
 }
 
 fn setup_lighting(mut commands: Commands, _asset_server: Res<AssetServer>) {
@@ -73,11 +84,14 @@ const GROUND_DIM: Vec3 = Vec3::new(200., 0.2, 200.);
 pub const WALL_DIM: Vec3 = Vec3::new(20., 5., 0.5);
 pub const TABLE_POS: Vec3 = Vec3::new(0., 1.5, 0.5);
 pub const TABLE_DIM: Vec3 = Vec3::new(WALL_DIM.x, 0.1, 0.1);
+/// Furthest the stack of cups is moved along the table either way, picked from the seed.
+pub const MAX_STACK_OFFSET: f32 = 0.3;
 
 fn setup_entities(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    seed: Res<GameSeed>,
 ) {
     // Ground
     commands.spawn((
@@ -109,7 +123,29 @@ fn setup_entities(
         RigidBody::Fixed,
         Collider::cuboid(TABLE_DIM.x / 2., TABLE_DIM.y / 2., TABLE_DIM.z / 2.),
     ));
-    // Cups
+    spawn_cups(&mut commands, &mut meshes, &mut materials, *seed);
+}
+
+fn respawn_cups(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cups: Query<Entity, With<Cup>>,
+    seed: Res<GameSeed>,
+) {
+    for cup in &cups {
+        commands.entity(cup).despawn_recursive();
+    }
+    spawn_cups(&mut commands, &mut meshes, &mut materials, *seed);
+}
+
+fn spawn_cups(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    seed: GameSeed,
+) {
+    let offset = stack_offset(seed);
     let mesh = meshes.add(Cylinder::new(CUP_RADIUS, CUP_HEIGHT));
     let material = materials.add(StandardMaterial { base_color: RED.into(), ..Default::default() });
     let cup_builder = |pos_x: f32, pos_y: f32, pos_z: f32| -> CupBundle {
@@ -128,13 +164,78 @@ fn setup_entities(
             const GAP: f32 = CUP_RADIUS * 2. + 0.01;
             let x = GAP * i as f32 + x_start_pad;
             // shift everything left to center
-            commands.spawn(cup_builder(x - (GAP * 3. / 2.), y, TABLE_POS.z));
+            commands.spawn(cup_builder(x - (GAP * 3. / 2.) + offset, y, TABLE_POS.z));
         }
     }
 }
 
+/// Where the stack of cups stands along the table, a script can't aim at a fixed spot.
+fn stack_offset(seed: GameSeed) -> f32 {
+    (seed.unit(0) * 2. - 1.) * MAX_STACK_OFFSET
+}
+
 fn despawn_entities(mut commands: Commands, rigid_bodies: Query<Entity, With<RigidBody>>) {
     for entity in &rigid_bodies {
         commands.entity(entity).despawn_recursive();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gotcha_plugin::{
+        GotchaPlugin,
+        testing::{FRAME, GotchaTestExt, test_app},
+    };
+
+    use super::*;
+
+    fn app(targets_left: u8, throwables_left: u8) -> App {
+        let mut app = test_app(GotchaPlugin::default());
+        app.insert_resource(TargetsLeft(targets_left));
+        app.insert_resource(ThrowablesLeftCount(throwables_left));
+        app.add_systems(
+            PreUpdate,
+            check_game_over.run_if(in_state(GotchaState::Gameplay)),
+        );
+        app.start();
+        app.advance(FRAME * 2);
+        app
+    }
+
+    #[test]
+    fn no_targets_left_succeeds() {
+        let app = app(0, 2);
+
+        assert_eq!(app.gotcha_state(), GotchaState::GameOver);
+        assert!(app.results()[0].success);
+    }
+
+    #[test]
+    fn no_throwables_left_fails_the_attempt() {
+        let app = app(3, 0);
+
+        assert_eq!(app.gotcha_state(), GotchaState::TryAgain);
+        assert_eq!(*app.world().resource::<AttemptCount>(), AttemptCount(1));
+    }
+
+    #[test]
+    fn seeds_move_the_stack_within_the_table() {
+        let offsets: Vec<_> = (0..100).map(|seed| stack_offset(GameSeed(seed))).collect();
+
+        assert!(
+            offsets
+                .iter()
+                .all(|offset| offset.abs() <= MAX_STACK_OFFSET)
+        );
+        assert_ne!(offsets[0], offsets[1]);
+        assert_eq!(stack_offset(GameSeed(7)), stack_offset(GameSeed(7)));
+    }
+
+    #[test]
+    fn keeps_playing_while_both_left() {
+        let app = app(3, 2);
+
+        assert_eq!(app.gotcha_state(), GotchaState::Gameplay);
+        assert!(app.results().is_empty());
+    }
+}
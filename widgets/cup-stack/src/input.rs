@@ -1,6 +1,6 @@
 use bevy::input::touch::TouchPhase;
 use bevy::{input::InputSystem, prelude::*, window::PrimaryWindow};
-use gotcha_plugin::GotchaState;
+use gotcha_plugin::{ACTIVATION_KEYS, GameplayInput, GotchaState};
 use rust_fsm::{StateMachine, StateMachineImpl, TransitionImpossibleError};
 
 pub struct ThrowInputPlugin;
@@ -9,10 +9,15 @@ impl Plugin for ThrowInputPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ThrowAction>();
         app.init_resource::<DragStateMachine>();
+        app.init_resource::<KeyboardAim>();
         // app.add_systems(OnEnter(GotchaState::Gameplay), start_debounce_timer);
         app.add_systems(
             PreUpdate,
-            (mouse_input_system, touch_input_system)
+            (
+                mouse_input_system,
+                touch_input_system,
+                keyboard_input_system,
+            )
                 .run_if(in_state(GotchaState::Gameplay))
                 .after(InputSystem),
         );
@@ -56,6 +61,7 @@ struct DragStateMachine(StateMachine<DragInputSystem>);
 fn mouse_input_system(
     mut drag_sm: ResMut<DragStateMachine>,
     mut throw_events: EventWriter<ThrowAction>,
+    mut input_events: EventWriter<GameplayInput>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
     window: Single<&Window, With<PrimaryWindow>>,
 ) {
@@ -68,12 +74,18 @@ fn mouse_input_system(
     };
 
     let output = match &*mouse_button_input {
-        mouse if mouse.just_pressed(MouseButton::Left) => drag_sm
-            .0
-            .consume(&DragIn::start(pointer, current_mouse_position)),
-        mouse if mouse.pressed(MouseButton::Left) => drag_sm
-            .0
-            .consume(&DragIn::moved(pointer, current_mouse_position)),
+        mouse if mouse.just_pressed(MouseButton::Left) => {
+            input_events.send(GameplayInput::Drag(current_mouse_position));
+            drag_sm
+                .0
+                .consume(&DragIn::start(pointer, current_mouse_position))
+        }
+        mouse if mouse.pressed(MouseButton::Left) => {
+            input_events.send(GameplayInput::Drag(current_mouse_position));
+            drag_sm
+                .0
+                .consume(&DragIn::moved(pointer, current_mouse_position))
+        }
         mouse if mouse.just_released(MouseButton::Left) => drag_sm.0.consume(&DragIn::end(pointer)),
         _ => return,
     };
@@ -84,21 +96,78 @@ fn touch_input_system(
     mut drag_sm: ResMut<DragStateMachine>,
     mut touch_events: EventReader<TouchInput>,
     mut throw_events: EventWriter<ThrowAction>,
+    mut input_events: EventWriter<GameplayInput>,
 ) {
     for touch in touch_events.read() {
         let pointer = PointerId::Touch(touch.id);
         let position = touch.position;
 
         let output = match touch.phase {
-            TouchPhase::Started => drag_sm.0.consume(&DragIn::start(pointer, position)),
-            TouchPhase::Moved => drag_sm.0.consume(&DragIn::moved(pointer, position)),
+            TouchPhase::Started => {
+                input_events.send(GameplayInput::Drag(position));
+                drag_sm.0.consume(&DragIn::start(pointer, position))
+            }
+            TouchPhase::Moved => {
+                input_events.send(GameplayInput::Drag(position));
+                drag_sm.0.consume(&DragIn::moved(pointer, position))
+            }
             TouchPhase::Ended => drag_sm.0.consume(&DragIn::end(pointer)),
-            TouchPhase::Canceled => drag_sm.0.consume(&DragIn::cancel(pointer)),
+            TouchPhase::Canceled => {
+                input_events.send(GameplayInput::Release { impulse: None });
+                drag_sm.0.consume(&DragIn::cancel(pointer))
+            }
         };
         DragInputSystem::handle_output(&mut throw_events, output);
     }
 }
 
+/// Drag distance each arrow key press adds to the keyboard aim.
+const KEYBOARD_AIM_STEP: f32 = 25.;
+
+/// Drag the arrow keys have built so far, `None` until the first one is pressed.
+#[derive(Resource, Debug, Default)]
+struct KeyboardAim(Option<Vec2>);
+
+/// Aims with the arrow keys as if dragging, up and down for power and left and right for
+/// direction, and throws with Enter or Space.
+fn keyboard_input_system(
+    mut aim: ResMut<KeyboardAim>,
+    mut throw_events: EventWriter<ThrowAction>,
+    mut input_events: EventWriter<GameplayInput>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    let step = [
+        (KeyCode::ArrowUp, Vec2::NEG_Y),
+        (KeyCode::ArrowDown, Vec2::Y),
+        (KeyCode::ArrowLeft, Vec2::NEG_X),
+        (KeyCode::ArrowRight, Vec2::X),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keyboard_input.just_pressed(*key))
+    .map(|(_, dir)| dir * KEYBOARD_AIM_STEP)
+    .sum::<Vec2>();
+    let throw = keyboard_input.any_just_pressed(ACTIVATION_KEYS);
+    if step == Vec2::ZERO && !throw {
+        return;
+    }
+
+    let drag = aim.0.unwrap_or(Vec2::new(0., -MAX_DRAG_DISTANCE / 2.)) + step;
+    let drag = drag.clamp_length_max(MAX_DRAG_DISTANCE);
+    if step != Vec2::ZERO || aim.0.is_none() {
+        input_events.send(GameplayInput::Aim(drag));
+    }
+    match throw {
+        true => {
+            aim.0 = None;
+            throw_events.send(ThrowAction::Throw(compute_throw_params(drag)));
+        }
+        false => {
+            aim.0 = Some(drag);
+            throw_events.send(ThrowAction::Holding(compute_throw_params(drag)));
+        }
+    }
+}
+
 // Constants for throw mechanics
 const MAX_DRAG_DISTANCE: f32 = 300.0; // Maximum drag distance for full power
 const MAX_IMPULSE: f32 = 0.05; // Maximum throw force
@@ -240,3 +309,77 @@ impl DragIn {
         Self { kind: DragInKind::Cancel, pointer }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::EventCursor;
+    use gotcha_plugin::{
+        GotchaPlugin,
+        testing::{GotchaTestExt, test_app},
+    };
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = test_app(GotchaPlugin::default());
+        app.add_event::<ThrowAction>();
+        app.init_resource::<KeyboardAim>();
+        app.add_systems(
+            PreUpdate,
+            keyboard_input_system
+                .run_if(in_state(GotchaState::Gameplay))
+                .after(InputSystem),
+        );
+        app.start();
+        app
+    }
+
+    fn throws(app: &App) -> Vec<ThrowParams> {
+        let events = app.world().resource::<Events<ThrowAction>>();
+        EventCursor::default()
+            .read(events)
+            .filter_map(|action| match action {
+                ThrowAction::Throw(params) => Some(*params),
+                ThrowAction::Holding(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arrows_aim_and_enter_throws() {
+        let mut app = app();
+
+        app.press_key(KeyCode::ArrowUp);
+        app.press_key(KeyCode::ArrowRight);
+        assert!(throws(&app).is_empty());
+        assert_eq!(
+            app.world().resource::<KeyboardAim>().0,
+            Some(Vec2::new(
+                KEYBOARD_AIM_STEP,
+                -MAX_DRAG_DISTANCE / 2. - KEYBOARD_AIM_STEP
+            ))
+        );
+
+        app.press_key(KeyCode::Enter);
+        let [throw] = throws(&app)[..] else {
+            panic!("expected a single throw");
+        };
+        assert!(throw.dir.x > 0. && throw.dir.y < 0.);
+        assert!(throw.impulse > MIN_IMPULSE && throw.impulse < MAX_IMPULSE);
+        assert_eq!(app.world().resource::<KeyboardAim>().0, None);
+    }
+
+    #[test]
+    fn aim_is_limited_to_full_power() {
+        let mut app = app();
+
+        for _ in 0..20 {
+            app.press_key(KeyCode::ArrowUp);
+        }
+        app.press_key(KeyCode::Space);
+        let [throw] = throws(&app)[..] else {
+            panic!("expected a single throw");
+        };
+        assert!((throw.impulse - MAX_IMPULSE).abs() < f32::EPSILON);
+    }
+}
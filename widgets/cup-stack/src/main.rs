@@ -30,7 +30,7 @@ fn main() {
             RapierPhysicsPlugin::<NoUserData>::default(),
             // RapierDebugRenderPlugin::default(),
         ))
-        .add_plugins(GotchaPlugin)
+        .add_plugins(GotchaPlugin::default())
         .add_plugins((
             UiPlugin,
             GamePlugin,
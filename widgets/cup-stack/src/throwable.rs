@@ -2,7 +2,7 @@ use std::f32::consts::PI;
 
 use bevy::{color::palettes::css::PURPLE, prelude::*};
 use bevy_rapier3d::prelude::*;
-use gotcha_plugin::GotchaState;
+use gotcha_plugin::{GameplayInput, GotchaState};
 
 use crate::{
     camera::move_camera,
@@ -14,7 +14,7 @@ pub struct ThrowablePlugin;
 
 impl Plugin for ThrowablePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ThrowablesLeftCount(3));
+        app.insert_resource(ThrowablesLeftCount(THROWABLES_PER_ATTEMPT));
         app.add_event::<ThrownEvent>();
         app.add_systems(Startup, spawn_throwable);
         app.add_systems(
@@ -127,6 +127,7 @@ fn throwing_object(
     mut commands: Commands,
     mut event_r: EventReader<ThrowAction>,
     mut throw_w: EventWriter<ThrownEvent>,
+    mut input_w: EventWriter<GameplayInput>,
     mut throwable: Single<(Entity, &mut ExternalImpulse), With<Throwable>>,
     camera: Single<&Transform, With<Camera3d>>,
     mut aiming: Option<ResMut<Aiming>>,
@@ -144,6 +145,7 @@ fn throwing_object(
 
                 let throw_direction = throw_dir3(&camera.forward(), &camera.right(), *dir);
                 external_impulse.impulse = throw_direction * *impulse;
+                input_w.send(GameplayInput::Release { impulse: Some(external_impulse.impulse) });
 
                 throw_w.send(ThrownEvent);
                 commands.remove_resource::<Aiming>();
@@ -156,6 +158,9 @@ fn throwing_object(
     }
 }
 
+/// Balls per attempt unless the challenge configuration sets `moves_per_attempt`.
+pub const THROWABLES_PER_ATTEMPT: u8 = 3;
+
 #[derive(Resource)]
 pub struct ThrowablesLeftCount(pub u8);
 
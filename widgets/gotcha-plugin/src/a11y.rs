@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use super::*;
+
+/// Keys activating the focused action, like a button on the web.
+pub const ACTIVATION_KEYS: [KeyCode; 3] = [KeyCode::Enter, KeyCode::NumpadEnter, KeyCode::Space];
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Announcement>();
+        app.add_systems(
+            Update,
+            // announced again in the page language once the configuration arrives
+            announce_state_changes.run_if(
+                state_changed::<GotchaState>
+                    .or(state_changed::<GameOverState>)
+                    .or(resource_changed::<Localization>),
+            ),
+        );
+    }
+}
+
+/// Message for screen readers, read out through an ARIA live region on the page.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct Announcement(pub String);
+
+fn announce_state_changes(
+    gotcha_state: Res<State<GotchaState>>,
+    game_over_state: Option<Res<State<GameOverState>>>,
+    localization: Res<Localization>,
+    mut announcement_w: EventWriter<Announcement>,
+) {
+    let key = match (
+        gotcha_state.get(),
+        game_over_state.as_deref().map(State::get),
+    ) {
+        (GotchaState::Welcome, _) => "announce-welcome",
+        (GotchaState::Gameplay, _) => "announce-gameplay",
+        (GotchaState::TryAgain, _) => "announce-try-again",
+        (GotchaState::GameOver, Some(GameOverState::Fail)) => "announce-failure",
+        (GotchaState::GameOver, _) => "announce-success",
+    };
+    announcement_w.send(Announcement(localization.get(key).to_owned()));
+}
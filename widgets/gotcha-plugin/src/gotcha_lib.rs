@@ -1,4 +1,75 @@
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future},
+};
 use wasm_bindgen::prelude::*;
+use web_sys::UrlSearchParams;
+
+use crate::{Announcement, ChallengeConfig, ChallengeResult, GotchaState, i18n::LocaleParams};
+
+/// Relays the [`ChallengeConfig`] from the page and the [`ChallengeResult`] and [`Announcement`]s
+/// back to it.
+pub struct GotchaLibPlugin;
+
+impl Plugin for GotchaLibPlugin {
+    fn build(&self, app: &mut App) {
+        // FIXME: should wait for this task to complete before continuing
+        app.add_systems(Startup, set_up_gotcha);
+        app.add_systems(
+            Update,
+            poll_set_up_gotcha.run_if(resource_exists::<SetUpGotchaTask>),
+        );
+        app.add_systems(Last, (relay_challenge_result, relay_announcements));
+        app.add_systems(
+            Update,
+            request_focus
+                .run_if(state_changed::<GotchaState>.and(not(in_state(GotchaState::Welcome)))),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct SetUpGotchaTask(Task<ChallengeConfig>);
+
+fn set_up_gotcha(mut commands: Commands) {
+    let task = AsyncComputeTaskPool::get().spawn(init());
+    commands.insert_resource(SetUpGotchaTask(task));
+}
+
+fn poll_set_up_gotcha(
+    mut commands: Commands,
+    mut task: ResMut<SetUpGotchaTask>,
+    mut config_w: EventWriter<ChallengeConfig>,
+) {
+    let Some(config) = block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+    config_w.send(config);
+    commands.remove_resource::<SetUpGotchaTask>();
+}
+
+fn relay_challenge_result(mut result_r: EventReader<ChallengeResult>) {
+    for result in result_r.read() {
+        let result = result.clone();
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                send_challenge_result(&result).await;
+            })
+            .detach();
+    }
+}
+
+fn relay_announcements(mut announcement_r: EventReader<Announcement>) {
+    for Announcement(message) in announcement_r.read() {
+        announce(message);
+    }
+}
+
+/// Keeps the keyboard focus on the game as its screens change once the user started playing, so
+/// keyboard users never have to look for it. The page keeps its focus until then.
+fn request_focus() {
+    focus();
+}
 
 #[wasm_bindgen]
 extern "C" {
@@ -6,20 +77,67 @@ extern "C" {
     async fn setup();
 
     #[wasm_bindgen(js_namespace = ["window", "GotchaWidgetLib"])]
-    async fn onChallengeResponse(success: bool);
+    async fn onChallengeResponse(success: bool, result: JsValue);
 
     #[wasm_bindgen(js_namespace = ["window", "GotchaWidgetLib"])]
     async fn onChallengeError();
+
+    /// Reads `message` out through the page's ARIA live region.
+    #[wasm_bindgen(js_namespace = ["window", "GotchaWidgetLib"])]
+    fn announce(message: &str);
+
+    /// Moves the focus to the game canvas.
+    #[wasm_bindgen(js_namespace = ["window", "GotchaWidgetLib"])]
+    fn focus();
 }
 
-#[wasm_bindgen]
-pub async fn init() {
+/// Sets up the widget lib and reads the configuration from the params the challenge was loaded
+/// with: `hl` and `strings` for the locale, defaulting to the browser language, `maxAttempts`
+/// and `movesPerAttempt` for the attempt limits and `seed` for the game.
+pub async fn init() -> ChallengeConfig {
     setup().await;
+
+    let window = web_sys::window();
+    let params = window
+        .as_ref()
+        .and_then(|window| window.location().search().ok())
+        .and_then(|search| UrlSearchParams::new_with_str(&search).ok());
+    let locale = params
+        .as_ref()
+        .and_then(|params| params.get("hl"))
+        .or_else(|| {
+            window
+                .as_ref()
+                .and_then(|window| window.navigator().language())
+        });
+    let strings = params
+        .as_ref()
+        .and_then(|params| params.get("strings"))
+        .and_then(|strings| serde_json::from_str(&strings).ok())
+        .unwrap_or_default();
+    let limit = |name: &str| {
+        params
+            .as_ref()
+            .and_then(|params| params.get(name))
+            .and_then(|value| value.parse().ok())
+    };
+
+    ChallengeConfig {
+        locale: LocaleParams { locale, strings },
+        max_attempts: limit("maxAttempts"),
+        moves_per_attempt: limit("movesPerAttempt"),
+        seed: params
+            .as_ref()
+            .and_then(|params| params.get("seed"))
+            .and_then(|seed| seed.parse().ok()),
+    }
 }
 
-#[wasm_bindgen]
-pub async fn send_challenge_result(success: bool) {
-    onChallengeResponse(success).await;
+/// Sends the result to the page as an object, `{ success, attempts, game }`.
+pub async fn send_challenge_result(result: &ChallengeResult) {
+    let json = serde_json::to_string(result).expect("challenge result should serialize");
+    let value = js_sys::JSON::parse(&json).unwrap_or(JsValue::NULL);
+    onChallengeResponse(result.success, value).await;
 }
 
 #[wasm_bindgen]
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+/// Locale used when none is requested or for keys missing from a bundle.
+pub const FALLBACK_LOCALE: &str = "en";
+
+/// Bundles shipped with the plugin, keyed by language.
+const BUNDLES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.json")),
+    ("pt", include_str!("../locales/pt.json")),
+    ("es", include_str!("../locales/es.json")),
+    ("fr", include_str!("../locales/fr.json")),
+    ("de", include_str!("../locales/de.json")),
+    ("ar", include_str!("../locales/ar.json")),
+];
+
+/// Languages written right to left.
+const RTL_LANGUAGES: &[&str] = &["ar", "fa", "he", "ur"];
+
+pub struct I18nPlugin;
+
+impl Plugin for I18nPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Localization::default());
+        app.add_systems(Update, update_localized_text);
+    }
+}
+
+/// Locale requested by the embedding page and strings overriding the bundled ones.
+#[derive(Debug, Clone, Default)]
+pub struct LocaleParams {
+    /// BCP 47 language tag, e.g. `pt-PT`.
+    pub locale: Option<String>,
+    pub strings: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Strings of the current locale. Replacing it updates every [`LocalizedText`].
+#[derive(Resource, Debug, Clone)]
+pub struct Localization {
+    locale: String,
+    direction: TextDirection,
+    strings: HashMap<String, String>,
+}
+
+impl Localization {
+    /// Resolves `locale` against the bundles, falling back to [`FALLBACK_LOCALE`] per key, with
+    /// `overrides` taking precedence over both.
+    pub fn new(locale: &str, overrides: HashMap<String, String>) -> Self {
+        let language = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let mut strings = bundle(FALLBACK_LOCALE);
+        if language != FALLBACK_LOCALE {
+            strings.extend(bundle(&language));
+        }
+        strings.extend(overrides);
+
+        let direction = match RTL_LANGUAGES.contains(&language.as_str()) {
+            true => TextDirection::RightToLeft,
+            false => TextDirection::LeftToRight,
+        };
+        Self { locale: locale.to_owned(), direction, strings }
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    pub fn direction(&self) -> TextDirection {
+        self.direction
+    }
+
+    /// Localized string for `key`, or the key itself when it has no translation.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map_or(key, String::as_str)
+    }
+
+    /// Justification matching the reading direction.
+    pub fn justify(&self) -> JustifyText {
+        match self.direction {
+            TextDirection::LeftToRight => JustifyText::Left,
+            TextDirection::RightToLeft => JustifyText::Right,
+        }
+    }
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self::new(FALLBACK_LOCALE, HashMap::new())
+    }
+}
+
+impl From<LocaleParams> for Localization {
+    fn from(params: LocaleParams) -> Self {
+        let locale = params.locale.as_deref().unwrap_or(FALLBACK_LOCALE);
+        Self::new(locale, params.strings)
+    }
+}
+
+/// Text looked up by key in [`Localization`].
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Text, TextLayout)]
+pub struct LocalizedText(pub &'static str);
+
+fn update_localized_text(
+    localization: Res<Localization>,
+    mut query: Query<(Ref<LocalizedText>, &mut Text, &mut TextLayout)>,
+) {
+    for (key, mut text, mut layout) in &mut query {
+        if !localization.is_changed() && !key.is_added() {
+            continue;
+        }
+        text.0 = localization.get(key.0).to_owned();
+        layout.justify = localization.justify();
+    }
+}
+
+fn bundle(language: &str) -> HashMap<String, String> {
+    BUNDLES
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map(|(_, bundle)| serde_json::from_str(bundle).expect("bundled locale should be valid"))
+        .unwrap_or_default()
+}
@@ -3,23 +3,46 @@ use bevy::{
     prelude::*,
 };
 
+use a11y::AccessibilityPlugin;
+use i18n::I18nPlugin;
+use trace::TracePlugin;
 use ui::*;
 
+pub use a11y::{ACTIVATION_KEYS, Announcement};
+pub use i18n::{FALLBACK_LOCALE, LocaleParams, Localization, LocalizedText, TextDirection};
+pub use trace::{
+    AttemptTrace, ChallengeResult, GameResult, GameSeed, GameplayInput, GameplayTrace, MoveTrace,
+    TracePoint,
+};
+
+mod a11y;
 #[cfg(target_arch = "wasm32")]
 mod gotcha_lib;
+mod i18n;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trace;
 mod ui;
 
-pub struct GotchaPlugin;
+#[derive(Default)]
+pub struct GotchaPlugin {
+    /// Limits used unless the challenge configuration sets its own.
+    pub limits: AttemptLimits,
+}
 
 impl Plugin for GotchaPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GotchaState>();
         app.add_sub_state::<GameOverState>();
         app.insert_resource(AttemptCount(0));
+        app.insert_resource(self.limits);
         app.add_event::<GameplayAttempt>();
-        app.add_plugins(UiPlugin);
-        // FIXME: should wait for this task to complete before continuing
-        app.add_systems(Startup, set_up_gotcha);
+        app.add_event::<ChallengeConfig>();
+        app.add_event::<ChallengeResult>();
+        app.add_plugins((I18nPlugin, TracePlugin, UiPlugin, AccessibilityPlugin));
+        #[cfg(target_arch = "wasm32")]
+        app.add_plugins(gotcha_lib::GotchaLibPlugin);
+        app.add_systems(Update, apply_challenge_config);
         app.add_systems(
             PreUpdate,
             start_gameplay
@@ -62,14 +85,39 @@ enum GameOverState {
 pub struct AttemptCount(pub u8);
 
 impl AttemptCount {
-    pub fn as_result(&self) -> Result<u8, u8> {
+    pub fn as_result(&self, limits: &AttemptLimits) -> Result<u8, u8> {
         match **self {
-            count @ 0..=3 => Ok(count),
+            count if count <= limits.max_attempts => Ok(count),
             count => Err(count),
         }
     }
 }
 
+/// How many tries the user gets, delivered by the server along with the challenge.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AttemptLimits {
+    /// Failed attempts before the challenge is over.
+    pub max_attempts: u8,
+    /// Moves per attempt, meaning is up to the game. `None` keeps the game default.
+    pub moves_per_attempt: Option<u8>,
+}
+
+impl Default for AttemptLimits {
+    fn default() -> Self {
+        Self { max_attempts: 3, moves_per_attempt: None }
+    }
+}
+
+/// Configuration the challenge was loaded with, `None` fields keep the plugin defaults.
+#[derive(Event, Debug, Clone, Default)]
+pub struct ChallengeConfig {
+    pub locale: LocaleParams,
+    pub max_attempts: Option<u8>,
+    pub moves_per_attempt: Option<u8>,
+    /// Seed issued by the server, games played on any other seed aren't scored.
+    pub seed: Option<u64>,
+}
+
 #[derive(Event, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum GameplayAttempt {
     Success,
@@ -94,19 +142,30 @@ fn remove_debounce_timer(mut commands: Commands) {
     commands.remove_resource::<GameplayDebounceTimer>();
 }
 
-fn set_up_gotcha() {
-    #[cfg(target_arch = "wasm32")]
-    use bevy::tasks::AsyncComputeTaskPool;
-
-    #[cfg(target_arch = "wasm32")]
-    AsyncComputeTaskPool::get().spawn(async {
-        let _ = gotcha_lib::init().await;
-    });
+fn apply_challenge_config(
+    mut config_r: EventReader<ChallengeConfig>,
+    mut localization: ResMut<Localization>,
+    mut limits: ResMut<AttemptLimits>,
+    mut seed: ResMut<GameSeed>,
+) {
+    for config in config_r.read() {
+        *localization = config.locale.clone().into();
+        if let Some(max_attempts) = config.max_attempts {
+            limits.max_attempts = max_attempts;
+        }
+        if let Some(moves) = config.moves_per_attempt {
+            limits.moves_per_attempt = Some(moves);
+        }
+        if let Some(issued) = config.seed {
+            *seed = GameSeed(issued);
+        }
+    }
 }
 
 fn start_gameplay(
     commands: Commands,
     mouse_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
     mut touch_events: EventReader<TouchInput>,
     debounce_gameplay_timer: Option<Res<GameplayDebounceTimer>>,
     mut gotcha_state: ResMut<NextState<GotchaState>>,
@@ -114,7 +173,9 @@ fn start_gameplay(
     if debounce_gameplay_timer.is_some_and(|timer| timer.0.finished()) {
         gotcha_state.set(GotchaState::Gameplay);
     }
-    if mouse_input.just_pressed(MouseButton::Left) {
+    if mouse_input.just_pressed(MouseButton::Left)
+        || keyboard_input.any_just_pressed(ACTIVATION_KEYS)
+    {
         start_gameplay_timer(commands);
         return;
     }
@@ -129,20 +190,23 @@ fn start_gameplay(
 
 fn handle_gameplay_attempt_event(
     mut attempt_count: ResMut<AttemptCount>,
+    limits: Res<AttemptLimits>,
+    mut trace: ResMut<GameplayTrace>,
     mut event_r: EventReader<GameplayAttempt>,
     mut gotcha_state: ResMut<NextState<GotchaState>>,
     mut game_over_state: ResMut<NextState<GameOverState>>,
 ) {
     for evt in event_r.read() {
         **attempt_count += 1;
+        trace.end_attempt(*evt == GameplayAttempt::Success);
         match evt {
             GameplayAttempt::Success => {
                 gotcha_state.set(GotchaState::GameOver);
                 game_over_state.set(GameOverState::Success);
             }
-            GameplayAttempt::Failure => match **attempt_count {
-                0..3 => gotcha_state.set(GotchaState::TryAgain),
-                _ => {
+            GameplayAttempt::Failure => match **attempt_count < limits.max_attempts {
+                true => gotcha_state.set(GotchaState::TryAgain),
+                false => {
                     gotcha_state.set(GotchaState::GameOver);
                     game_over_state.set(GameOverState::Fail);
                 }
@@ -151,24 +215,18 @@ fn handle_gameplay_attempt_event(
     }
 }
 
-fn handle_gameover(game_over_state: Res<State<GameOverState>>) {
-    #[cfg(target_arch = "wasm32")]
-    use bevy::tasks::AsyncComputeTaskPool;
-
-    match game_over_state.get() {
-        GameOverState::Success => {
-            info!("success");
-            #[cfg(target_arch = "wasm32")]
-            AsyncComputeTaskPool::get().spawn(async {
-                gotcha_lib::send_challenge_result(true).await;
-            });
-        }
-        GameOverState::Fail => {
-            info!("failure");
-            #[cfg(target_arch = "wasm32")]
-            AsyncComputeTaskPool::get().spawn(async {
-                gotcha_lib::send_challenge_result(false).await;
-            });
-        }
+fn handle_gameover(
+    game_over_state: Res<State<GameOverState>>,
+    attempt_count: Res<AttemptCount>,
+    trace: Res<GameplayTrace>,
+    seed: Res<GameSeed>,
+    mut result_w: EventWriter<ChallengeResult>,
+) {
+    let success = *game_over_state.get() == GameOverState::Success;
+    let result = trace.result(success, **attempt_count, *seed);
+    match success {
+        true => info!(attempts = result.attempts, "success"),
+        false => info!(attempts = result.attempts, "failure"),
     }
+    result_w.send(result);
 }
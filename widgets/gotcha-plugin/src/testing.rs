@@ -0,0 +1,182 @@
+//! Headless harness to test the plugin and the games built on it without a window, GPU or browser.
+//! The `gotcha_lib` bridge is replaced by a [`MockBridge`] collecting the results and announcements
+//! sent to the page, configuration is delivered with [`GotchaTestExt::configure`].
+
+use std::time::Duration;
+
+use bevy::{
+    input::{
+        ButtonState, InputPlugin,
+        keyboard::{Key, KeyboardInput, NativeKey},
+        mouse::MouseButtonInput,
+        touch::TouchPhase,
+    },
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+
+use crate::{ui::TryAgainButton, *};
+
+/// Time advanced by each update.
+pub const FRAME: Duration = Duration::from_millis(16);
+/// Long enough for the debounce before gameplay starts.
+pub const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Stands in for the page, keeping every result and announcement sent to it.
+#[derive(Resource, Debug, Default)]
+pub struct MockBridge {
+    pub results: Vec<ChallengeResult>,
+    pub announcements: Vec<String>,
+}
+
+/// Headless app running `plugin` with the [`MockBridge`], each update advances time by [`FRAME`].
+pub fn test_app(plugin: GotchaPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, InputPlugin));
+    if !app.is_plugin_added::<StatesPlugin>() {
+        app.add_plugins(StatesPlugin);
+    }
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+    app.init_resource::<MockBridge>();
+    app.add_plugins(plugin);
+    app.add_systems(Last, (collect_challenge_results, collect_announcements));
+    app.update();
+    app
+}
+
+/// Scripts inputs into a [`test_app`] and inspects its state.
+pub trait GotchaTestExt {
+    fn gotcha_state(&self) -> GotchaState;
+    fn results(&self) -> &[ChallengeResult];
+    fn announcements(&self) -> &[String];
+    /// Runs updates until `duration` has passed.
+    fn advance(&mut self, duration: Duration);
+    /// Presses and releases the left mouse button.
+    fn click(&mut self);
+    /// Touches the screen and lifts the finger.
+    fn tap(&mut self);
+    /// Presses and releases `key`.
+    fn press_key(&mut self, key: KeyCode);
+    /// Clicks through the welcome screen and waits for the gameplay.
+    fn start(&mut self);
+    /// Presses the try again button and waits for the gameplay.
+    fn try_again(&mut self);
+    /// Delivers a configuration as the page would.
+    fn configure(&mut self, config: ChallengeConfig);
+    /// Ends the current attempt as a game would.
+    fn attempt(&mut self, attempt: GameplayAttempt);
+    /// Traces an input as a game would.
+    fn input(&mut self, input: GameplayInput);
+}
+
+impl GotchaTestExt for App {
+    fn gotcha_state(&self) -> GotchaState {
+        self.world().resource::<State<GotchaState>>().get().clone()
+    }
+
+    fn results(&self) -> &[ChallengeResult] {
+        &self.world().resource::<MockBridge>().results
+    }
+
+    fn announcements(&self) -> &[String] {
+        &self.world().resource::<MockBridge>().announcements
+    }
+
+    fn advance(&mut self, duration: Duration) {
+        for _ in 0..duration.div_duration_f32(FRAME).ceil() as u32 {
+            self.update();
+        }
+    }
+
+    fn click(&mut self) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.world_mut().send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state,
+                window: Entity::PLACEHOLDER,
+            });
+            self.update();
+        }
+    }
+
+    fn tap(&mut self) {
+        for phase in [TouchPhase::Started, TouchPhase::Ended] {
+            self.world_mut().send_event(TouchInput {
+                phase,
+                position: Vec2::ZERO,
+                window: Entity::PLACEHOLDER,
+                force: None,
+                id: 0,
+            });
+            self.update();
+        }
+    }
+
+    fn press_key(&mut self, key: KeyCode) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.world_mut().send_event(KeyboardInput {
+                key_code: key,
+                logical_key: Key::Unidentified(NativeKey::Unidentified),
+                state,
+                repeat: false,
+                window: Entity::PLACEHOLDER,
+            });
+            self.update();
+        }
+    }
+
+    fn start(&mut self) {
+        self.click();
+        self.advance(DEBOUNCE);
+    }
+
+    fn try_again(&mut self) {
+        let button = self
+            .world_mut()
+            .query_filtered::<Entity, With<TryAgainButton>>()
+            .single(self.world());
+        for interaction in [Interaction::Pressed, Interaction::None] {
+            self.world_mut().entity_mut(button).insert(interaction);
+            self.update();
+        }
+        self.advance(DEBOUNCE);
+    }
+
+    fn configure(&mut self, config: ChallengeConfig) {
+        self.world_mut().send_event(config);
+        // one update to apply it and another for the systems reacting to it
+        self.update();
+        self.update();
+    }
+
+    fn attempt(&mut self, attempt: GameplayAttempt) {
+        self.world_mut().send_event(attempt);
+        // the state changes on the update after the event is handled
+        self.update();
+        self.update();
+    }
+
+    fn input(&mut self, input: GameplayInput) {
+        self.world_mut().send_event(input);
+        self.update();
+    }
+}
+
+fn collect_challenge_results(
+    mut result_r: EventReader<ChallengeResult>,
+    mut bridge: ResMut<MockBridge>,
+) {
+    bridge.results.extend(result_r.read().cloned());
+}
+
+fn collect_announcements(
+    mut announcement_r: EventReader<Announcement>,
+    mut bridge: ResMut<MockBridge>,
+) {
+    bridge.announcements.extend(
+        announcement_r
+            .read()
+            .map(|Announcement(message)| message.clone()),
+    );
+}
@@ -0,0 +1,205 @@
+use std::mem;
+
+use bevy::{prelude::*, time::Stopwatch};
+use serde::Serialize;
+
+use super::*;
+
+pub struct TracePlugin;
+
+impl Plugin for TracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameplayTrace>();
+        app.insert_resource(GameSeed(random_seed()));
+        app.add_event::<GameplayInput>();
+        app.add_systems(OnEnter(GotchaState::Gameplay), start_attempt_trace);
+        app.add_systems(
+            PostUpdate,
+            (
+                tick_gameplay_trace,
+                record_key_presses,
+                record_gameplay_input,
+            )
+                .chain()
+                .before(handle_gameplay_attempt_event)
+                .run_if(in_state(GotchaState::Gameplay).or(in_state(GotchaState::TryAgain))),
+        );
+    }
+}
+
+/// Seed games should generate their randomness from, reported with the result. Random until the
+/// one issued by the server is configured, games should react to it changing.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Deref)]
+pub struct GameSeed(pub u64);
+
+impl GameSeed {
+    /// Value in `[0, 1)` drawn from the seed, the same for a given `index`.
+    pub fn unit(&self, index: u64) -> f32 {
+        // splitmix64 of the seed offset by the index
+        let mut z = self
+            .0
+            .wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Input sent by games while playing, traced into the challenge result.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum GameplayInput {
+    /// Pointer position while dragging, in window coordinates.
+    Drag(Vec2),
+    /// Aim set with the keyboard, as the drag it stands in for.
+    Aim(Vec2),
+    /// End of a move, with the impulse it applied if any.
+    Release { impulse: Option<Vec3> },
+}
+
+/// Result of the challenge sent back to the page.
+#[derive(Event, Debug, Clone, PartialEq, Serialize)]
+pub struct ChallengeResult {
+    pub success: bool,
+    pub attempts: u8,
+    pub game: GameResult,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameResult {
+    /// Time from the first attempt until the game was over.
+    pub solve_time_ms: u32,
+    pub seed: u64,
+    /// One trace per attempt, in order.
+    pub traces: Vec<AttemptTrace>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AttemptTrace {
+    pub success: bool,
+    pub moves: Vec<MoveTrace>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MoveTrace {
+    pub path: Vec<TracePoint>,
+    pub impulse: Option<[f32; 3]>,
+    /// Milliseconds since the attempt started of every key pressed during the move.
+    pub keys: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TracePoint {
+    pub x: f32,
+    pub y: f32,
+    /// Milliseconds since the attempt started.
+    pub t_ms: u32,
+}
+
+/// Moves traced so far, attempt by attempt.
+#[derive(Resource, Debug, Default)]
+pub struct GameplayTrace {
+    solve_time: Stopwatch,
+    attempt_time: Stopwatch,
+    attempts: Vec<AttemptTrace>,
+    moves: Vec<MoveTrace>,
+    path: Vec<TracePoint>,
+    keys: Vec<u32>,
+    /// Aimed with the keyboard since the last move.
+    keyboard: bool,
+}
+
+impl GameplayTrace {
+    /// Closes the current attempt, a drag never released counts as a move without impulse.
+    pub(crate) fn end_attempt(&mut self, success: bool) {
+        self.end_move(None);
+        let moves = mem::take(&mut self.moves);
+        self.attempts.push(AttemptTrace { success, moves });
+    }
+
+    pub fn result(&self, success: bool, attempts: u8, seed: GameSeed) -> ChallengeResult {
+        ChallengeResult {
+            success,
+            attempts,
+            game: GameResult {
+                solve_time_ms: self.solve_time.elapsed().as_millis() as u32,
+                seed: *seed,
+                traces: self.attempts.clone(),
+            },
+        }
+    }
+
+    fn elapsed_ms(&self) -> u32 {
+        self.attempt_time.elapsed().as_millis() as u32
+    }
+
+    fn push_point(&mut self, position: Vec2) {
+        let t_ms = self.elapsed_ms();
+        self.path
+            .push(TracePoint { x: position.x, y: position.y, t_ms });
+    }
+
+    fn end_move(&mut self, impulse: Option<Vec3>) {
+        if self.path.is_empty() && impulse.is_none() {
+            self.keys.clear();
+            return;
+        }
+        let keyboard = mem::take(&mut self.keyboard);
+        if let Some(last) = self.path.last().filter(|_| keyboard) {
+            // the time spent aiming is all there is to tell a keyboard user from a script
+            self.push_point(Vec2::new(last.x, last.y));
+        }
+        let path = mem::take(&mut self.path);
+        let keys = mem::take(&mut self.keys);
+        self.moves
+            .push(MoveTrace { path, impulse: impulse.map(|i| i.to_array()), keys });
+    }
+}
+
+fn start_attempt_trace(mut trace: ResMut<GameplayTrace>) {
+    trace.attempt_time.reset();
+    trace.path.clear();
+    trace.keys.clear();
+    trace.keyboard = false;
+}
+
+fn tick_gameplay_trace(mut trace: ResMut<GameplayTrace>, time: Res<Time<Real>>) {
+    trace.solve_time.tick(time.delta());
+    trace.attempt_time.tick(time.delta());
+}
+
+/// Key presses are traced as they happen, the server judges keyboard aims by them rather than by
+/// what the game reports.
+fn record_key_presses(mut trace: ResMut<GameplayTrace>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    let t_ms = trace.elapsed_ms();
+    let pressed = keyboard_input.get_just_pressed().count();
+    trace.keys.extend(std::iter::repeat_n(t_ms, pressed));
+}
+
+fn record_gameplay_input(
+    mut trace: ResMut<GameplayTrace>,
+    mut input_r: EventReader<GameplayInput>,
+) {
+    for input in input_r.read() {
+        match *input {
+            GameplayInput::Drag(position) => trace.push_point(position),
+            GameplayInput::Aim(aim) => {
+                trace.keyboard = true;
+                trace.push_point(aim);
+            }
+            GameplayInput::Release { impulse } => trace.end_move(impulse),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn random_seed() -> u64 {
+    (js_sys::Math::random() * u64::MAX as f64) as u64
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
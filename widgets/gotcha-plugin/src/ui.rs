@@ -37,7 +37,7 @@ fn setup_welcome_ui(mut commands: Commands) {
         ))
         .with_children(|parent| {
             parent.spawn((
-                Text::new("Click to start!"),
+                LocalizedText("welcome"),
                 TextFont { font_size: 30., ..default() },
                 TextColor(Color::WHITE),
                 Node { margin: UiRect::all(Val::Auto), ..default() },
@@ -87,14 +87,14 @@ fn setup_try_again_ui(mut commands: Commands) {
                 ))
                 .with_children(|parent| {
                     parent.spawn((
-                        Text::new("Game Over"),
+                        LocalizedText("game-over"),
                         TextFont { font_size: 40., ..default() },
                         TextColor(Color::WHITE),
                     ));
                     parent.spawn((
                         TryAgainButton,
                         Button,
-                        Text::new("Try again"),
+                        LocalizedText("try-again"),
                         TextFont { font_size: 30., ..default() },
                         TextColor(Color::WHITE),
                     ));
@@ -105,6 +105,7 @@ fn setup_try_again_ui(mut commands: Commands) {
 #[allow(clippy::type_complexity)]
 fn try_again_action(
     commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
     mut button: Option<
         Single<(&Interaction, &mut TextColor), (With<TryAgainButton>, Changed<Interaction>)>,
     >,
@@ -114,6 +115,11 @@ fn try_again_action(
     if debounce_gameplay_timer.is_some_and(|timer| timer.0.finished()) {
         gotcha_state.set(GotchaState::Gameplay);
     }
+    // the try again button is the only control, so it always has the keyboard focus
+    if keyboard_input.any_just_pressed(ACTIVATION_KEYS) {
+        start_gameplay_timer(commands);
+        return;
+    }
     let Some((interaction, text_color)) = button.as_deref_mut() else {
         return;
     };
@@ -184,7 +190,7 @@ fn setup_gameover_failure_ui(parent: &mut ChildBuilder<'_>) {
         ))
         .with_children(|parent| {
             parent.spawn((
-                Text::new("Game Over"),
+                LocalizedText("game-over"),
                 TextFont { font_size: 40., ..default() },
                 TextColor(Color::WHITE),
             ));
@@ -208,11 +214,11 @@ fn setup_gameover_success_ui(parent: &mut ChildBuilder<'_>, attempt_count: Attem
         ))
         .with_children(|parent| {
             parent.spawn((
-                Text::new(match *attempt_count {
-                    1 => "Perfect!",
-                    2 => "Good job.",
-                    3 => "Close enough...",
-                    _ => "Hmmm 🤨",
+                LocalizedText(match *attempt_count {
+                    1 => "success-perfect",
+                    2 => "success-good",
+                    3 => "success-close",
+                    _ => "success-other",
                 }),
                 TextFont { font_size: 40., ..default() },
                 TextColor(Color::WHITE),
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use gotcha_plugin::{
+    AttemptCount, AttemptLimits, ChallengeConfig, GameSeed, GameplayAttempt, GameplayInput,
+    GotchaPlugin, GotchaState, LocaleParams, Localization, LocalizedText,
+    testing::{DEBOUNCE, GotchaTestExt, test_app},
+};
+
+#[test]
+fn click_starts_gameplay_after_debounce() {
+    let mut app = test_app(GotchaPlugin::default());
+    assert_eq!(app.gotcha_state(), GotchaState::Welcome);
+
+    app.click();
+    app.advance(Duration::from_millis(100));
+    assert_eq!(app.gotcha_state(), GotchaState::Welcome);
+
+    app.advance(DEBOUNCE);
+    assert_eq!(app.gotcha_state(), GotchaState::Gameplay);
+}
+
+#[test]
+fn tap_starts_gameplay() {
+    let mut app = test_app(GotchaPlugin::default());
+
+    app.tap();
+    app.advance(DEBOUNCE);
+    assert_eq!(app.gotcha_state(), GotchaState::Gameplay);
+}
+
+#[test]
+fn keyboard_plays_through_and_announces_each_screen() {
+    let mut app = test_app(GotchaPlugin {
+        limits: AttemptLimits { max_attempts: 2, moves_per_attempt: None },
+    });
+    assert_eq!(app.announcements(), ["Press Enter or click to start."]);
+
+    app.press_key(KeyCode::Enter);
+    app.advance(DEBOUNCE);
+    assert_eq!(app.gotcha_state(), GotchaState::Gameplay);
+
+    app.attempt(GameplayAttempt::Failure);
+    assert_eq!(app.gotcha_state(), GotchaState::TryAgain);
+    app.press_key(KeyCode::Space);
+    app.advance(DEBOUNCE);
+    assert_eq!(app.gotcha_state(), GotchaState::Gameplay);
+
+    app.attempt(GameplayAttempt::Failure);
+    assert_eq!(app.gotcha_state(), GotchaState::GameOver);
+    assert_eq!(
+        app.announcements(),
+        [
+            "Press Enter or click to start.",
+            "Challenge started.",
+            "Attempt failed. Press Enter to try again.",
+            "Challenge started.",
+            "Challenge failed.",
+        ]
+    );
+}
+
+#[test]
+fn keyboard_moves_are_traced_with_their_keys_and_release() {
+    let mut app = test_app(GotchaPlugin::default());
+    app.start();
+
+    app.press_key(KeyCode::ArrowUp);
+    app.input(GameplayInput::Aim(Vec2::new(0., -100.)));
+    app.advance(Duration::from_millis(100));
+    app.press_key(KeyCode::Enter);
+    app.input(GameplayInput::Release { impulse: Some(Vec3::new(0., 0.01, -0.04)) });
+    app.attempt(GameplayAttempt::Success);
+
+    let [throw] = app.results()[0].game.traces[0].moves.as_slice() else {
+        panic!(
+            "expected a single move, got {:?}",
+            app.results()[0].game.traces
+        );
+    };
+    let [aim_key, release_key] = throw.keys[..] else {
+        panic!(
+            "expected the keys pressed to aim and release, got {:?}",
+            throw.keys
+        );
+    };
+    assert!(release_key >= aim_key + 100);
+    let [aim, release] = throw.path.as_slice() else {
+        panic!("expected the aim and its release, got {:?}", throw.path);
+    };
+    assert_eq!((release.x, release.y), (aim.x, aim.y));
+    assert!(release.t_ms >= aim.t_ms + 100);
+}
+
+#[test]
+fn failures_until_limit_fail_the_challenge() {
+    let mut app = test_app(GotchaPlugin::default());
+    app.start();
+
+    for attempt in 1..3 {
+        app.attempt(GameplayAttempt::Failure);
+        assert_eq!(app.gotcha_state(), GotchaState::TryAgain);
+        assert_eq!(
+            *app.world().resource::<AttemptCount>(),
+            AttemptCount(attempt)
+        );
+        app.try_again();
+        assert_eq!(app.gotcha_state(), GotchaState::Gameplay);
+    }
+    app.attempt(GameplayAttempt::Failure);
+    assert_eq!(app.gotcha_state(), GotchaState::GameOver);
+
+    let [result] = app.results() else {
+        panic!("expected a single result, got {:?}", app.results());
+    };
+    assert!(!result.success);
+    assert_eq!(result.attempts, 3);
+    assert_eq!(result.game.traces.len(), 3);
+    assert!(result.game.traces.iter().all(|trace| !trace.success));
+}
+
+#[test]
+fn success_reports_traced_moves() {
+    let mut app = test_app(GotchaPlugin::default());
+    app.start();
+
+    for position in [
+        Vec2::new(100., 300.),
+        Vec2::new(102., 280.),
+        Vec2::new(99., 250.),
+    ] {
+        app.input(GameplayInput::Drag(position));
+    }
+    app.input(GameplayInput::Release { impulse: Some(Vec3::new(0., 0.01, -0.04)) });
+    app.attempt(GameplayAttempt::Success);
+    assert_eq!(app.gotcha_state(), GotchaState::GameOver);
+
+    let [result] = app.results() else {
+        panic!("expected a single result, got {:?}", app.results());
+    };
+    assert!(result.success);
+    assert_eq!(result.attempts, 1);
+    assert_eq!(result.game.seed, **app.world().resource::<GameSeed>());
+    assert!(result.game.solve_time_ms > 0);
+
+    let [trace] = result.game.traces.as_slice() else {
+        panic!("expected a single attempt, got {:?}", result.game.traces);
+    };
+    assert!(trace.success);
+    let [throw] = trace.moves.as_slice() else {
+        panic!("expected a single move, got {:?}", trace.moves);
+    };
+    assert_eq!(throw.path.len(), 3);
+    assert!(throw.path.windows(2).all(|w| w[0].t_ms < w[1].t_ms));
+    assert_eq!(throw.impulse, Some([0., 0.01, -0.04]));
+}
+
+#[test]
+fn configuration_overrides_limits_and_locale() {
+    let mut app = test_app(GotchaPlugin::default());
+    app.configure(ChallengeConfig {
+        locale: LocaleParams { locale: Some("pt-PT".into()), ..default() },
+        max_attempts: Some(1),
+        moves_per_attempt: Some(5),
+        seed: Some(7),
+    });
+
+    assert_eq!(
+        *app.world().resource::<AttemptLimits>(),
+        AttemptLimits { max_attempts: 1, moves_per_attempt: Some(5) }
+    );
+    assert_eq!(*app.world().resource::<GameSeed>(), GameSeed(7));
+    assert_eq!(app.world().resource::<Localization>().locale(), "pt-PT");
+    let welcome = app
+        .world_mut()
+        .query::<(&LocalizedText, &Text)>()
+        .iter(app.world())
+        .find(|(key, _)| key.0 == "welcome")
+        .map(|(_, text)| text.0.clone());
+    assert_eq!(welcome.as_deref(), Some("Clique para começar!"));
+    assert_eq!(
+        app.announcements().last().map(String::as_str),
+        Some("Pressione Enter ou clique para começar.")
+    );
+
+    app.start();
+    app.attempt(GameplayAttempt::Failure);
+    assert_eq!(app.gotcha_state(), GotchaState::GameOver);
+    assert!(!app.results()[0].success);
+}
+
+#[test]
+fn seeds_draw_the_same_values() {
+    let seed = GameSeed(7);
+
+    assert_eq!(seed.unit(0), GameSeed(7).unit(0));
+    assert_ne!(seed.unit(0), seed.unit(1));
+    assert_ne!(seed.unit(0), GameSeed(8).unit(0));
+    assert!((0. ..1.).contains(&seed.unit(0)));
+}